@@ -60,6 +60,7 @@ fn bench_fastqr_qrcode(c: &mut Criterion) {
     }
 }
 
+#[allow(dead_code)]
 fn bench_mask(c: &mut Criterion) {
     let mut group = c.benchmark_group("mask");
     group.measurement_time(Duration::from_secs(10));
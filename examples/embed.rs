@@ -15,8 +15,9 @@ fn main() {
         .shape(Shape::Square)
         .fit_width(600)
         .background_color([255, 255, 255, 255])
-        // New: embed an image
-        .image(String::from("./assets/example.com.svg"))
+        // New: embed an image, inlined so the output does not depend on the file
+        .image_file("./assets/example.com.svg")
+        .unwrap()
         // .image_size(15f64, 2f64)
         // .image_position(37f64 / 2f64, 0f64)
         .image_background_color([165, 34, 247, 255])
@@ -0,0 +1,491 @@
+//! Damage and occlusion robustness analysis
+//!
+//! Maps every module of a [`QRCode`] to the codeword and Reed-Solomon block it
+//! belongs to, so you can check how much of a code can be covered (by a logo,
+//! a sticker, a scratch, ...) before it stops decoding.
+//!
+//! ```rust
+//! use fast_qr::analysis::{DamageReport, Occlusion};
+//! use fast_qr::{QRBuilder, ECL};
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .ecl(ECL::H)
+//!     .build()
+//!     .unwrap();
+//!
+//! // Covers a 5x5 square in the middle of the code
+//! let center = qrcode.size / 2;
+//! let occlusion = Occlusion::Rectangle {
+//!     x: center - 2,
+//!     y: center - 2,
+//!     width: 5,
+//!     height: 5,
+//! };
+//!
+//! let report = DamageReport::new(&qrcode, &occlusion);
+//! assert!(report.is_recoverable());
+//! println!("Weakest block: {:?}", report.weakest_block());
+//! ```
+
+#![deny(unsafe_code)]
+#![warn(missing_docs)]
+
+use core::ops::Range;
+use std::collections::HashSet;
+
+use crate::module::ModuleType;
+use crate::placement::BiRange;
+use crate::qr::LogoPosition;
+use crate::{default, hardcode, QRCode, Version, ECL};
+
+/// Location of a single module inside the encoded data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodewordPosition {
+    /// Index of the Reed-Solomon block the codeword belongs to
+    pub block: usize,
+    /// Index of the codeword inside its block, data codewords first, then
+    /// error correction codewords
+    pub codeword: usize,
+    /// Index of the bit inside the codeword, `0` being the most significant
+    pub bit: usize,
+}
+
+/// Structure of a single Reed-Solomon block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    /// Number of data codewords in the block
+    pub data_codewords: usize,
+    /// Number of error correction codewords in the block
+    pub ec_codewords: usize,
+    /// Maximum number of codewords that can be damaged while the block is
+    /// still recoverable, referring to Table 9 of the spec
+    pub capacity: usize,
+}
+
+impl Block {
+    /// Total number of codewords in the block
+    #[must_use]
+    pub const fn codewords(&self) -> usize {
+        self.data_codewords + self.ec_codewords
+    }
+}
+
+/// Maps each module of a `QRCode` to its codeword and block.
+///
+/// The mapping only depends on the `Version` and `ECL`, it is the same for
+/// every mask and every content.
+#[derive(Debug, Clone)]
+pub struct CodewordMap {
+    size: usize,
+    blocks: Vec<Block>,
+    modules: Vec<Option<CodewordPosition>>,
+}
+
+/// Number of misdecode protection codewords, referring to Table 9 of the spec.
+///
+/// These are not used for error correction on small symbols.
+const fn misdecode_protection(version: Version, ecl: ECL) -> usize {
+    match (version, ecl) {
+        (Version::V01, ECL::L) => 3,
+        (Version::V01, ECL::M) | (Version::V02, ECL::L) => 2,
+        (Version::V01, ECL::Q | ECL::H) | (Version::V03, ECL::L) => 1,
+        _ => 0,
+    }
+}
+
+impl CodewordMap {
+    /// Computes the codeword map for a specified `version` and `ecl`
+    #[must_use]
+    pub fn new(version: Version, ecl: ECL) -> Self {
+        let blocks = Self::blocks_structure(version, ecl);
+        let interleaving = Self::interleaving(&blocks);
+
+        let matrix = default::create_matrix(version);
+        let size = matrix.size;
+        let mut modules = vec![None; size * size];
+
+        let mut rev = true;
+        let mut idx = 0;
+
+        // Same walk as `placement::place_on_matrix_data`
+        for x in (0..6).chain(7..size).rev().step_by(2) {
+            let y_range = if rev {
+                BiRange::Backwards((0..size).rev())
+            } else {
+                BiRange::Forward(0..size)
+            };
+
+            for y in y_range {
+                for x in [x, x - 1] {
+                    if matrix[y][x].module_type() != ModuleType::Data {
+                        continue;
+                    }
+
+                    // Remainder bits do not belong to any codeword
+                    if let Some(&(block, codeword)) = interleaving.get(idx / 8) {
+                        modules[y * size + x] = Some(CodewordPosition {
+                            block,
+                            codeword,
+                            bit: idx % 8,
+                        });
+                    }
+                    idx += 1;
+                }
+            }
+
+            rev = !rev;
+        }
+
+        CodewordMap {
+            size,
+            blocks,
+            modules,
+        }
+    }
+
+    /// Computes the codeword map of a `QRCode`.
+    ///
+    /// If the `QRCode` has no `ecl`, Quartile is used, same as [`crate::QRBuilder`].
+    #[must_use]
+    pub fn from_qr(qr: &QRCode) -> Self {
+        let version = qr.version.unwrap_or_else(|| Version::from_n(qr.size));
+        Self::new(version, qr.ecl.unwrap_or(ECL::Q))
+    }
+
+    fn blocks_structure(version: Version, ecl: ECL) -> Vec<Block> {
+        let [(g1_count, g1_size), (g2_count, g2_size)] = hardcode::ecc_to_groups(ecl, version);
+        let ec_codewords = hardcode::get_polynomial(version, ecl).len() - 1;
+        let capacity = (ec_codewords - misdecode_protection(version, ecl)) / 2;
+
+        let group = |data_codewords| Block {
+            data_codewords,
+            ec_codewords,
+            capacity,
+        };
+
+        core::iter::repeat(group(g1_size))
+            .take(g1_count)
+            .chain(core::iter::repeat(group(g2_size)).take(g2_count))
+            .collect()
+    }
+
+    /// Returns the `(block, codeword)` pair of each codeword, in the
+    /// interleaved order they are placed on the matrix, referring to 7.6 of
+    /// the spec.
+    fn interleaving(blocks: &[Block]) -> Vec<(usize, usize)> {
+        let total: usize = blocks.iter().map(Block::codewords).sum();
+        let mut out = Vec::with_capacity(total);
+
+        let max_data = blocks.iter().map(|b| b.data_codewords).max().unwrap_or(0);
+        for i in 0..max_data {
+            for (block, b) in blocks.iter().enumerate() {
+                if i < b.data_codewords {
+                    out.push((block, i));
+                }
+            }
+        }
+
+        let max_ec = blocks.iter().map(|b| b.ec_codewords).max().unwrap_or(0);
+        for i in 0..max_ec {
+            for (block, b) in blocks.iter().enumerate() {
+                if i < b.ec_codewords {
+                    out.push((block, b.data_codewords + i));
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Width & Height of the mapped `QRCode`
+    #[must_use]
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Reed-Solomon blocks of the `QRCode`, in order
+    #[must_use]
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    /// Returns the codeword the module at `(y, x)` belongs to.
+    ///
+    /// Function patterns and remainder bits return `None`.
+    #[must_use]
+    pub fn get(&self, y: usize, x: usize) -> Option<CodewordPosition> {
+        if y >= self.size || x >= self.size {
+            return None;
+        }
+        self.modules[y * self.size + x]
+    }
+
+    /// Returns the damage caused by an occlusion.
+    #[must_use]
+    pub fn damage(&self, occlusion: &Occlusion) -> DamageReport {
+        let mut damaged: Vec<Vec<bool>> = self
+            .blocks
+            .iter()
+            .map(|block| vec![false; block.codewords()])
+            .collect();
+
+        let (rows, columns) = occlusion.bounds(self.size);
+        for y in rows {
+            for x in columns.clone() {
+                if !occlusion.contains(y, x) {
+                    continue;
+                }
+                if let Some(position) = self.modules[y * self.size + x] {
+                    damaged[position.block][position.codeword] = true;
+                }
+            }
+        }
+
+        let blocks = self
+            .blocks
+            .iter()
+            .zip(damaged)
+            .enumerate()
+            .map(|(index, (block, damaged))| BlockDamage {
+                index,
+                block: *block,
+                damaged: damaged.into_iter().filter(|&d| d).count(),
+            })
+            .collect();
+
+        DamageReport { blocks }
+    }
+}
+
+/// Area of a `QRCode` that cannot be read.
+///
+/// Coordinates are in modules, without margin, `(0, 0)` being the top left module.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Occlusion {
+    /// Rectangle starting at `(x, y)` (top left)
+    Rectangle {
+        /// Left column
+        x: usize,
+        /// Top row
+        y: usize,
+        /// Width, in modules
+        width: usize,
+        /// Height, in modules
+        height: usize,
+    },
+    /// Circle centered on `(x, y)`, a module is covered if its center is
+    /// inside the circle
+    Circle {
+        /// Column of the center
+        x: f64,
+        /// Row of the center
+        y: f64,
+        /// Radius, in modules
+        radius: f64,
+    },
+    /// Arbitrary set of `(y, x)` modules
+    Modules(HashSet<(usize, usize)>),
+}
+
+impl Occlusion {
+    /// Checks if the module at `(y, x)` is covered
+    #[must_use]
+    pub fn contains(&self, y: usize, x: usize) -> bool {
+        match self {
+            Occlusion::Rectangle {
+                x: left,
+                y: top,
+                width,
+                height,
+            } => (*left..left + width).contains(&x) && (*top..top + height).contains(&y),
+            Occlusion::Circle {
+                x: cx,
+                y: cy,
+                radius,
+            } => {
+                let dx = x as f64 + 0.5 - cx;
+                let dy = y as f64 + 0.5 - cy;
+                dx * dx + dy * dy <= radius * radius
+            }
+            Occlusion::Modules(modules) => modules.contains(&(y, x)),
+        }
+    }
+
+    /// Returns the rows and columns that may contain covered modules, clamped
+    /// to a `QRCode` of width `size`
+    fn bounds(&self, size: usize) -> (Range<usize>, Range<usize>) {
+        let clamp = |range: Range<usize>| range.start.min(size)..range.end.min(size);
+
+        match self {
+            Occlusion::Rectangle {
+                x,
+                y,
+                width,
+                height,
+            } => (clamp(*y..y + height), clamp(*x..x + width)),
+            Occlusion::Circle { x, y, radius } => {
+                let range = |center: f64| {
+                    let start = (center - radius).floor().max(0f64) as usize;
+                    let end = (center + radius).ceil().max(0f64) as usize;
+                    clamp(start..end)
+                };
+                (range(*y), range(*x))
+            }
+            Occlusion::Modules(_) => (0..size, 0..size),
+        }
+    }
+}
+
+/// Damage of a single Reed-Solomon block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockDamage {
+    /// Index of the block
+    pub index: usize,
+    /// Structure of the block
+    pub block: Block,
+    /// Number of damaged codewords
+    pub damaged: usize,
+}
+
+impl BlockDamage {
+    /// Checks if the block can still be corrected
+    #[must_use]
+    pub const fn is_recoverable(&self) -> bool {
+        self.damaged <= self.block.capacity
+    }
+
+    /// Number of codewords that can still be damaged before the block is lost,
+    /// negative if the block is already lost
+    #[must_use]
+    pub const fn margin(&self) -> isize {
+        self.block.capacity as isize - self.damaged as isize
+    }
+}
+
+/// Result of a damage analysis, see [`CodewordMap::damage`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DamageReport {
+    /// Damage for each block, in order
+    pub blocks: Vec<BlockDamage>,
+}
+
+impl DamageReport {
+    /// Computes the damage an occlusion causes to a `QRCode`
+    #[must_use]
+    pub fn new(qr: &QRCode, occlusion: &Occlusion) -> Self {
+        CodewordMap::from_qr(qr).damage(occlusion)
+    }
+
+    /// Checks if every block can still be corrected
+    #[must_use]
+    pub fn is_recoverable(&self) -> bool {
+        self.blocks.iter().all(BlockDamage::is_recoverable)
+    }
+
+    /// Returns the block with the lowest margin, it is the first one to fail
+    #[must_use]
+    pub fn weakest_block(&self) -> &BlockDamage {
+        self.blocks
+            .iter()
+            .min_by_key(|block| block.margin())
+            .expect("A QRCode has at least one block")
+    }
+
+    /// Total number of damaged codewords
+    #[must_use]
+    pub fn damaged_codewords(&self) -> usize {
+        self.blocks.iter().map(|block| block.damaged).sum()
+    }
+}
+
+/// Finds where a `side` x `side` logo can be placed on a `QRCode` while every
+/// block keeps at least `safety_margin` codewords of correction capacity.
+///
+/// The logo can cover data and alignment modules, but never finder patterns,
+/// timing patterns or format / version information.
+pub(crate) fn place_logo(
+    version: Version,
+    ecl: ECL,
+    side: usize,
+    position: LogoPosition,
+    safety_margin: usize,
+) -> Option<Occlusion> {
+    let size = version.size();
+    if side == 0 || side > size {
+        return None;
+    }
+
+    let matrix = default::create_matrix(version);
+    let map = CodewordMap::new(version, ecl);
+    let max = size - side;
+
+    let covers_function_pattern = |top: usize, left: usize| {
+        (top..top + side).any(|y| {
+            (left..left + side).any(|x| {
+                !matches!(
+                    matrix[y][x].module_type(),
+                    ModuleType::Data | ModuleType::Alignment
+                )
+            })
+        })
+    };
+
+    let rectangle = |top: usize, left: usize| Occlusion::Rectangle {
+        x: left,
+        y: top,
+        width: side,
+        height: side,
+    };
+
+    let margin = |top: usize, left: usize| -> Option<isize> {
+        if covers_function_pattern(top, left) {
+            return None;
+        }
+
+        let margin = map.damage(&rectangle(top, left)).weakest_block().margin();
+        if margin >= safety_margin as isize {
+            Some(margin)
+        } else {
+            None
+        }
+    };
+
+    let (top, left) = match position {
+        LogoPosition::Center => (max / 2, max / 2),
+        LogoPosition::At { x, y } => {
+            let start = |center: f64| {
+                let start = (center - side as f64 / 2f64).round().max(0f64) as usize;
+                start.min(max)
+            };
+            (start(y), start(x))
+        }
+        LogoPosition::Auto => {
+            let step = core::cmp::max(1, side / 4);
+            let mut candidates: Vec<(usize, usize)> = (0..=max)
+                .step_by(step)
+                .flat_map(|top| (0..=max).step_by(step).map(move |left| (top, left)))
+                .collect();
+            candidates.push((max / 2, max / 2));
+
+            // Favors positions close to the center when margins are equal
+            let distance = |&(top, left): &(usize, usize)| {
+                (top as isize - (max / 2) as isize).pow(2)
+                    + (left as isize - (max / 2) as isize).pow(2)
+            };
+            candidates.sort_by_key(distance);
+
+            let mut best: Option<((usize, usize), isize)> = None;
+            for (top, left) in candidates {
+                if let Some(margin) = margin(top, left) {
+                    if best.map_or(true, |(_, best)| margin > best) {
+                        best = Some(((top, left), margin));
+                    }
+                }
+            }
+
+            return best.map(|((top, left), _)| rectangle(top, left));
+        }
+    };
+
+    margin(top, left).map(|_| rectangle(top, left))
+}
@@ -0,0 +1,204 @@
+//! Traces the outlines of dark regions into polygons
+//!
+//! Dark modules are merged into non-overlapping polygons with holes, for
+//! engraving, cutting or any vector format without svg paths.
+//!
+//! ```rust
+//! use fast_qr::convert::contour;
+//! use fast_qr::qr::QRBuilder;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .build()
+//!     .unwrap();
+//!
+//! // Each finder pattern is a square ring around a square
+//! let outlines = contour::outlines(&qrcode, 4, false);
+//! assert!(outlines.iter().any(|outline| outline.holes.len() == 1));
+//! ```
+
+use crate::QRCode;
+
+use super::dots;
+
+/// Polygon with holes, corners are `(x, y)` in modules from the top left of
+/// the margin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outline {
+    /// Outer boundary, clockwise with `y` going down
+    pub outer: Vec<(usize, usize)>,
+    /// Boundaries of the holes, counterclockwise with `y` going down
+    pub holes: Vec<Vec<(usize, usize)>>,
+}
+
+/// Outlines of the dark modules, or of the light ones and the margin if
+/// `inverted`. Modules touching by a corner only are in different outlines.
+#[must_use]
+pub fn outlines(qr: &QRCode, margin: usize, inverted: bool) -> Vec<Outline> {
+    let size = qr.size + 2 * margin;
+    let filled: Vec<bool> = (0..size * size)
+        .map(|i| dots::dark(qr, margin, i % size, i / size) != inverted)
+        .collect();
+
+    group(&filled, size, size)
+}
+
+/// The cell on the right of the side `from` → `to` of a polygon of [`trace`],
+/// next to `from`
+pub(crate) fn right_cell((x0, y0): (usize, usize), (x1, y1): (usize, usize)) -> (usize, usize) {
+    let (dx, dy) = (x1 as isize - x0 as isize, y1 as isize - y0 as isize);
+    let (dx, dy) = (dx.signum(), dy.signum());
+    let x = (2 * x0 as isize + dx - dy - 1) / 2;
+    let y = (2 * y0 as isize + dy + dx - 1) / 2;
+    (x as usize, y as usize)
+}
+
+/// Groups the polygons of [`trace`] into outlines, holes go with the region
+/// on their right
+pub(crate) fn group(dark: &[bool], width: usize, height: usize) -> Vec<Outline> {
+    // Regions of cells sharing a side
+    let mut regions = vec![usize::MAX; dark.len()];
+    let mut count = 0;
+    for start in 0..dark.len() {
+        if !dark[start] || regions[start] != usize::MAX {
+            continue;
+        }
+        regions[start] = count;
+        let mut stack = vec![start];
+        while let Some(cell) = stack.pop() {
+            let (x, y) = (cell % width, cell / width);
+            let neighbors = [
+                (x > 0).then(|| cell - 1),
+                (x + 1 < width).then(|| cell + 1),
+                (y > 0).then(|| cell - width),
+                (y + 1 < height).then(|| cell + width),
+            ];
+            for next in neighbors.iter().flatten().copied() {
+                if dark[next] && regions[next] == usize::MAX {
+                    regions[next] = count;
+                    stack.push(next);
+                }
+            }
+        }
+        count += 1;
+    }
+
+    let region = |polygon: &[(usize, usize)]| {
+        let (x, y) = right_cell(polygon[0], polygon[1]);
+        regions[y * width + x]
+    };
+    // Twice the signed area, positive for clockwise polygons
+    let area = |polygon: &[(usize, usize)]| {
+        let mut sum = 0isize;
+        for (i, &(x0, y0)) in polygon.iter().enumerate() {
+            let (x1, y1) = polygon[(i + 1) % polygon.len()];
+            sum += x0 as isize * y1 as isize - x1 as isize * y0 as isize;
+        }
+        sum
+    };
+
+    let (outer, holes): (Vec<_>, Vec<_>) = trace(dark, width, height)
+        .into_iter()
+        .partition(|polygon| area(polygon) > 0);
+
+    let mut indices = vec![usize::MAX; count];
+    let mut outlines = Vec::with_capacity(outer.len());
+    for polygon in outer {
+        indices[region(&polygon)] = outlines.len();
+        outlines.push(Outline {
+            outer: polygon,
+            holes: Vec::new(),
+        });
+    }
+    for polygon in holes {
+        let index = indices[region(&polygon)];
+        outlines[index].holes.push(polygon);
+    }
+
+    outlines
+}
+
+/// Traces the outlines of the dark cells of a `width` x `height` grid.
+///
+/// Polygons are lists of `(x, y)` corners, without collinear points. Outer
+/// outlines are clockwise and holes are counterclockwise (the dark side is
+/// always on the right), so they can be filled with the `nonzero` rule.
+pub(crate) fn trace(dark: &[bool], width: usize, height: usize) -> Vec<Vec<(usize, usize)>> {
+    let is_dark = |x: usize, y: usize| dark[y * width + x];
+    // Corners are on a (width + 1) x (height + 1) grid
+    let corners = width + 1;
+    let vertex = |x: usize, y: usize| y * corners + x;
+
+    // Outgoing edges of each corner, shared edges between dark cells cancel out
+    let mut edges = vec![Vec::new(); corners * (height + 1)];
+    for y in 0..height {
+        for x in 0..width {
+            if !is_dark(x, y) {
+                continue;
+            }
+
+            if y == 0 || !is_dark(x, y - 1) {
+                edges[vertex(x, y)].push(vertex(x + 1, y));
+            }
+            if x + 1 == width || !is_dark(x + 1, y) {
+                edges[vertex(x + 1, y)].push(vertex(x + 1, y + 1));
+            }
+            if y + 1 == height || !is_dark(x, y + 1) {
+                edges[vertex(x + 1, y + 1)].push(vertex(x, y + 1));
+            }
+            if x == 0 || !is_dark(x - 1, y) {
+                edges[vertex(x, y + 1)].push(vertex(x, y));
+            }
+        }
+    }
+
+    let direction = |from: usize, to: usize| {
+        let dx = (to % corners) as isize - (from % corners) as isize;
+        let dy = (to / corners) as isize - (from / corners) as isize;
+        (dx.signum(), dy.signum())
+    };
+
+    let mut polygons = Vec::new();
+    for start in 0..edges.len() {
+        while !edges[start].is_empty() {
+            let mut points = vec![start];
+            let mut current = start;
+            let mut heading = (0, 0);
+
+            loop {
+                // Turning right at corners touching diagonally keeps cells
+                // of different regions in different polygons
+                let right = (-heading.1, heading.0);
+                let outgoing = &mut edges[current];
+                let index = outgoing
+                    .iter()
+                    .position(|&next| direction(current, next) == right)
+                    .unwrap_or(0);
+                let next = outgoing.swap_remove(index);
+
+                let next_heading = direction(current, next);
+                if next_heading == heading {
+                    // Collinear, replaces the previous point
+                    points.pop();
+                }
+                heading = next_heading;
+                current = next;
+
+                if current == start {
+                    break;
+                }
+                points.push(current);
+            }
+
+            // The start can be in the middle of a side
+            if points.len() > 1
+                && direction(points[points.len() - 1], start) == direction(start, points[1])
+            {
+                points.remove(0);
+            }
+
+            polygons.push(points.iter().map(|&v| (v % corners, v / corners)).collect());
+        }
+    }
+
+    polygons
+}
@@ -0,0 +1,14 @@
+//! CRC-32 of PNG chunks and zip entries
+
+/// CRC-32 with the reflected polynomial `0xedb88320`
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
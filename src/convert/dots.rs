@@ -0,0 +1,29 @@
+//! Packs a [`QRCode`] into rows of 1 bit per dot, for the label printer and
+//! source backends. The most significant bit is the leftmost dot and 1 is
+//! dark. Also tells whether a module is dark, margin included, for the
+//! backends drawing modules one by one.
+
+use crate::QRCode;
+
+/// Whether the module at `(x, y)` is dark, `(0, 0)` being the top left of
+/// the margin
+pub(crate) fn dark(qr: &QRCode, margin: usize, x: usize, y: usize) -> bool {
+    let inside = |i: usize| i >= margin && i < margin + qr.size;
+    inside(x) && inside(y) && qr[y - margin][x - margin].value()
+}
+
+/// Rows of `(size + 2 * margin) * dots` dots, padded with zeros to whole bytes
+#[cfg(any(feature = "zpl", feature = "escpos", feature = "source"))]
+pub(crate) fn rows(qr: &QRCode, margin: usize, dots: usize) -> Vec<Vec<u8>> {
+    let side = (qr.size + 2 * margin) * dots;
+
+    (0..side)
+        .map(|y| {
+            let mut row = vec![0u8; (side + 7) / 8];
+            for x in (0..side).filter(|x| dark(qr, margin, x / dots, y / dots)) {
+                row[x / 8] |= 0x80 >> (x % 8);
+            }
+            row
+        })
+        .collect()
+}
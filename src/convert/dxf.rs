@@ -0,0 +1,143 @@
+//! Converts [`QRCode`] to DXF outlines, for laser cutters, engravers and CNC
+//!
+//! Dark modules are merged into closed polygons with [`contour::outlines`],
+//! written as R12 `POLYLINE` entities. R12 has no units: coordinates are in
+//! millimeters by convention, which is what laser cutting software assumes.
+//!
+//! ```rust
+//! use fast_qr::convert::dxf::DxfBuilder;
+//! use fast_qr::qr::QRBuilder;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .build()
+//!     .unwrap();
+//!
+//! // Modules of 0.8 mm, the light regions are engraved
+//! let _dxf = DxfBuilder::default()
+//!     .module_size(0.8f64)
+//!     .inverted(true)
+//!     .to_str(&qrcode);
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::io;
+
+use crate::QRCode;
+
+use super::{contour, number};
+
+/// Builder for DXF, can set margin, module size, layer and polarity
+pub struct DxfBuilder {
+    /// The margin, in modules, default is 4
+    margin: usize,
+    /// Width of a module in millimeters, default is 1
+    module_size: f64,
+    /// Outlines the light modules and the margin, default is false
+    inverted: bool,
+    /// Layer of the polylines, default is `QR`
+    layer: String,
+}
+
+impl Default for DxfBuilder {
+    fn default() -> Self {
+        DxfBuilder {
+            margin: 4,
+            module_size: 1f64,
+            inverted: false,
+            layer: String::from("QR"),
+        }
+    }
+}
+
+impl DxfBuilder {
+    /// Updates margin, in modules (default: 4)
+    pub fn margin(&mut self, margin: usize) -> &mut Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Updates the width of a module, in millimeters (default: 1)
+    pub fn module_size(&mut self, millimeters: f64) -> &mut Self {
+        self.module_size = millimeters;
+        self
+    }
+
+    /// Outlines the light modules and the margin instead of the dark modules
+    /// (default: false)
+    pub fn inverted(&mut self, inverted: bool) -> &mut Self {
+        self.inverted = inverted;
+        self
+    }
+
+    /// Updates the layer of the polylines (default: `QR`)
+    pub fn layer(&mut self, layer: &str) -> &mut Self {
+        self.layer = layer.to_string();
+        self
+    }
+
+    /// Return a string containing the DXF for a qr code.
+    ///
+    /// The origin is the bottom left of the margin and `y` goes up: outer
+    /// boundaries are counterclockwise and holes clockwise.
+    pub fn to_str(&self, qr: &QRCode) -> String {
+        let size = qr.size + 2 * self.margin;
+        let extent = number(size as f64 * self.module_size);
+
+        // Group codes and values alternate, one per line
+        let mut out = String::new();
+        let mut push = |code: u16, value: &str| {
+            out.push_str(&format!("{code}\n{value}\n"));
+        };
+
+        push(0, "SECTION");
+        push(2, "HEADER");
+        push(9, "$ACADVER");
+        push(1, "AC1009");
+        push(9, "$EXTMIN");
+        push(10, "0");
+        push(20, "0");
+        push(9, "$EXTMAX");
+        push(10, &extent);
+        push(20, &extent);
+        push(0, "ENDSEC");
+
+        push(0, "SECTION");
+        push(2, "ENTITIES");
+        let outlines = contour::outlines(qr, self.margin, self.inverted);
+        for polygon in outlines
+            .iter()
+            .flat_map(|outline| core::iter::once(&outline.outer).chain(&outline.holes))
+        {
+            // Closed polyline, followed by its vertices
+            push(0, "POLYLINE");
+            push(8, &self.layer);
+            push(66, "1");
+            push(10, "0");
+            push(20, "0");
+            push(30, "0");
+            push(70, "1");
+            for &(x, y) in polygon {
+                push(0, "VERTEX");
+                push(8, &self.layer);
+                push(10, &number(x as f64 * self.module_size));
+                push(20, &number((size - y) as f64 * self.module_size));
+                push(30, "0");
+            }
+            push(0, "SEQEND");
+            push(8, &self.layer);
+        }
+        push(0, "ENDSEC");
+        push(0, "EOF");
+
+        out
+    }
+
+    /// Saves the DXF for a qr code to a file
+    ///
+    /// # Errors
+    /// - `io::Error` if the file cannot be written
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn to_file(&self, qr: &QRCode, file: &str) -> io::Result<()> {
+        std::fs::write(file, self.to_str(qr))
+    }
+}
@@ -0,0 +1,201 @@
+//! Embeds images as `data:` URIs so generated SVGs are self-contained
+
+use super::base64;
+use super::svg::SvgError;
+
+/// Image formats that can be embedded in a SVG
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MimeType {
+    Png,
+    Jpeg,
+    Svg,
+    Webp,
+}
+
+impl MimeType {
+    /// Finds the format of an image using its first bytes
+    pub(crate) fn sniff(bytes: &[u8]) -> Option<Self> {
+        const PNG: &[u8] = b"\x89PNG\r\n\x1a\n";
+        const JPEG: &[u8] = b"\xff\xd8\xff";
+
+        if bytes.starts_with(PNG) {
+            return Some(MimeType::Png);
+        }
+        if bytes.starts_with(JPEG) {
+            return Some(MimeType::Jpeg);
+        }
+        if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+            return Some(MimeType::Webp);
+        }
+
+        // SVG is text, it may start with a BOM, an XML declaration or comments
+        let text = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+        let head = &text[..text.len().min(4096)];
+        let starts_with_tag = head
+            .iter()
+            .find(|c| !c.is_ascii_whitespace())
+            .map_or(false, |&c| c == b'<');
+        if starts_with_tag && head.windows(4).any(|w| w == b"<svg") {
+            return Some(MimeType::Svg);
+        }
+
+        None
+    }
+
+    pub(crate) const fn as_str(self) -> &'static str {
+        match self {
+            MimeType::Png => "image/png",
+            MimeType::Jpeg => "image/jpeg",
+            MimeType::Svg => "image/svg+xml",
+            MimeType::Webp => "image/webp",
+        }
+    }
+}
+
+/// Decodes standard base64, whitespace is ignored
+#[cfg(any(feature = "pdf", feature = "eps"))]
+pub(crate) fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    };
+
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text.bytes() {
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+        if c == b'=' {
+            break;
+        }
+        buffer = buffer << 6 | u32::from(value(c)?);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(out)
+}
+
+/// Bytes of an image given to [`super::Builder::image`], from a base64 `data:`
+/// URI. Paths and urls are neither read nor fetched, local files go through
+/// `image_file`.
+#[cfg(any(feature = "pdf", feature = "eps"))]
+pub(crate) fn source_bytes(image: &str) -> Option<Vec<u8>> {
+    let uri = image.strip_prefix("data:")?;
+    let (header, data) = uri.split_at(uri.find(',')?);
+    if !header.ends_with(";base64") {
+        return None;
+    }
+    decode_base64(&data[1..])
+}
+
+/// Error for images PDF and EPS cannot embed
+#[cfg(any(feature = "pdf", feature = "eps"))]
+pub(crate) fn unsupported_image() -> SvgError {
+    SvgError::SvgError(String::from(
+        "Unsupported image, expected a PNG or JPEG image in a base64 data URI",
+    ))
+}
+
+/// Creates a `data:` URI from an image, its format is sniffed from its content
+pub(crate) fn data_uri(bytes: &[u8]) -> Result<String, SvgError> {
+    let mime = MimeType::sniff(bytes).ok_or_else(|| {
+        SvgError::SvgError(String::from(
+            "Unsupported image format, expected PNG, JPEG, SVG or WebP",
+        ))
+    })?;
+
+    Ok(format!("data:{};base64,{}", mime.as_str(), base64(bytes)))
+}
+
+/// Width, height and number of components of a JPEG image
+#[cfg(any(feature = "image", feature = "pdf", feature = "eps"))]
+pub(crate) fn jpeg_header(bytes: &[u8]) -> Option<(u32, u32, u8)> {
+    let be_u16 = |i: usize| Some(u32::from(*bytes.get(i)?) << 8 | u32::from(*bytes.get(i + 1)?));
+
+    // Walks the segments until a Start Of Frame
+    let mut i = 2;
+    while i + 9 < bytes.len() {
+        if bytes[i] != 0xff {
+            return None;
+        }
+        let marker = bytes[i + 1];
+        let is_sof = matches!(marker, 0xc0..=0xcf) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+        if is_sof {
+            return Some((be_u16(i + 7)?, be_u16(i + 5)?, bytes[i + 9]));
+        }
+        i += 2 + be_u16(i + 2)? as usize;
+    }
+    None
+}
+
+/// Width & height of a PNG or JPEG image
+#[cfg(feature = "image")]
+fn dimensions(bytes: &[u8], mime: MimeType) -> Option<(u32, u32)> {
+    let be_u16 = |i: usize| Some(u32::from(*bytes.get(i)?) << 8 | u32::from(*bytes.get(i + 1)?));
+    let be_u32 = |i: usize| Some(be_u16(i)? << 16 | be_u16(i + 2)?);
+
+    match mime {
+        // IHDR is always the first chunk
+        MimeType::Png => Some((be_u32(16)?, be_u32(20)?)),
+        MimeType::Jpeg => jpeg_header(bytes).map(|(width, height, _)| (width, height)),
+        MimeType::Svg | MimeType::Webp => None,
+    }
+}
+
+/// Re-encodes a PNG or JPEG image as PNG, downscaled so neither side is
+/// larger than `max_size` pixels. SVG images are returned as is.
+#[cfg(feature = "image")]
+pub(crate) fn downscale(bytes: &[u8], max_size: u32) -> Result<Vec<u8>, SvgError> {
+    use resvg::tiny_skia::{Pixmap, Transform};
+    use resvg::usvg;
+
+    let unsupported = |msg: &str| SvgError::SvgError(String::from(msg));
+
+    let mime = MimeType::sniff(bytes)
+        .ok_or_else(|| unsupported("Unsupported image format, expected PNG, JPEG, SVG or WebP"))?;
+    if mime == MimeType::Svg {
+        return Ok(bytes.to_vec());
+    }
+
+    let (width, height) = dimensions(bytes, mime)
+        .ok_or_else(|| unsupported("Only PNG and JPEG can be re-encoded"))?;
+    if width == 0 || height == 0 {
+        return Err(unsupported("Image is empty"));
+    }
+
+    let scale = (f64::from(max_size.max(1)) / f64::from(width.max(height))).min(1f64);
+    let target_width = ((f64::from(width) * scale).round() as u32).max(1);
+    let target_height = ((f64::from(height) * scale).round() as u32).max(1);
+
+    let svg = format!(
+        r#"<svg viewBox="0 0 {0} {1}" width="{0}" height="{1}" xmlns="http://www.w3.org/2000/svg"><image width="{0}" height="{1}" href="{2}"/></svg>"#,
+        width,
+        height,
+        data_uri(bytes)?
+    );
+
+    let tree = usvg::Tree::from_data(svg.as_bytes(), &usvg::Options::default())
+        .map_err(|err| SvgError::SvgError(err.to_string()))?;
+    let mut pixmap = Pixmap::new(target_width, target_height)
+        .ok_or_else(|| unsupported("Failed to create pixmap"))?;
+    resvg::render(
+        &tree,
+        usvg::FitTo::Size(target_width, target_height),
+        Transform::default(),
+        pixmap.as_mut(),
+    )
+    .ok_or_else(|| unsupported("Failed to decode image"))?;
+
+    pixmap
+        .encode_png()
+        .map_err(|err| SvgError::SvgError(err.to_string()))
+}
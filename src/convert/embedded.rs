@@ -0,0 +1,119 @@
+//! Draws [`QRCode`] on `embedded-graphics` displays
+//!
+//! Every row is drawn as filled rectangles, one per run of modules of the
+//! same color, so displays with accelerated fills are not sent single pixels.
+//!
+//! ```rust
+//! use embedded_graphics_core::geometry::Point;
+//! use embedded_graphics_core::pixelcolor::BinaryColor;
+//! use fast_qr::convert::embedded::QrDrawable;
+//! use fast_qr::qr::QRBuilder;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .build()
+//!     .unwrap();
+//!
+//! let mut drawable = QrDrawable::new(&qrcode, BinaryColor::On, BinaryColor::Off);
+//! drawable.position(Point::new(10, 4)).scale(2).margin(1);
+//! // drawable.draw(&mut display)?;
+//! ```
+
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Dimensions, Point, Size};
+use embedded_graphics_core::pixelcolor::PixelColor;
+use embedded_graphics_core::primitives::Rectangle;
+use embedded_graphics_core::Drawable;
+
+use crate::QRCode;
+
+use super::dots;
+
+/// [`Drawable`] for a [`QRCode`], can set position, scale, margin and colors
+pub struct QrDrawable<'a, C: PixelColor> {
+    qr: &'a QRCode,
+    /// Top left of the margin, default is `(0, 0)`
+    position: Point,
+    /// Pixels per module, default is 1
+    scale: u32,
+    /// The margin, in modules, default is 4
+    margin: usize,
+    /// Color of the dark modules
+    on: C,
+    /// Color of the light modules and the margin
+    off: C,
+}
+
+impl<'a, C: PixelColor> QrDrawable<'a, C> {
+    /// Creates a drawable with the colors of the dark modules (`on`) and of
+    /// the light modules & margin (`off`)
+    pub fn new(qr: &'a QRCode, on: C, off: C) -> Self {
+        QrDrawable {
+            qr,
+            position: Point::zero(),
+            scale: 1,
+            margin: 4,
+            on,
+            off,
+        }
+    }
+
+    /// Updates the top left of the margin (default: `(0, 0)`)
+    pub fn position(&mut self, position: Point) -> &mut Self {
+        self.position = position;
+        self
+    }
+
+    /// Updates the number of pixels per module, at least 1 (default: 1)
+    pub fn scale(&mut self, scale: u32) -> &mut Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Updates margin, in modules (default: 4)
+    pub fn margin(&mut self, margin: usize) -> &mut Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Updates the colors of the dark modules (`on`) and of the light modules
+    /// & margin (`off`)
+    pub fn colors(&mut self, on: C, off: C) -> &mut Self {
+        self.on = on;
+        self.off = off;
+        self
+    }
+}
+
+impl<C: PixelColor> Dimensions for QrDrawable<'_, C> {
+    fn bounding_box(&self) -> Rectangle {
+        let side = (self.qr.size + 2 * self.margin) as u32 * self.scale;
+        Rectangle::new(self.position, Size::new_equal(side))
+    }
+}
+
+impl<C: PixelColor> Drawable for QrDrawable<'_, C> {
+    type Color = C;
+    type Output = ();
+
+    fn draw<D: DrawTarget<Color = C>>(&self, target: &mut D) -> Result<(), D::Error> {
+        let side = self.qr.size + 2 * self.margin;
+        let scale = self.scale as usize;
+
+        for y in 0..side {
+            let mut x = 0;
+            while x < side {
+                let dark = dots::dark(self.qr, self.margin, x, y);
+                let run = (x..side)
+                    .take_while(|&i| dots::dark(self.qr, self.margin, i, y) == dark)
+                    .count();
+
+                let top_left = Point::new((x * scale) as i32, (y * scale) as i32);
+                let size = Size::new((run * scale) as u32, self.scale);
+                let color = if dark { self.on } else { self.off };
+                target.fill_solid(&Rectangle::new(self.position + top_left, size), color)?;
+                x += run;
+            }
+        }
+        Ok(())
+    }
+}
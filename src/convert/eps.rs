@@ -0,0 +1,607 @@
+//! Converts [`QRCode`] to Encapsulated PostScript
+//!
+//! ```rust
+//! use fast_qr::convert::eps::{ColorModel, EpsBuilder};
+//! use fast_qr::convert::{Builder, Shape};
+//! use fast_qr::qr::QRBuilder;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .build()
+//!     .unwrap();
+//!
+//! // Modules of 0.5 mm, black is printed with a spot color
+//! let _eps = EpsBuilder::default()
+//!     .shape(Shape::RoundedSquare)
+//!     .module_size(0.5f64)
+//!     .color_model(ColorModel::Cmyk)
+//!     .spot_color("#000000", "PANTONE Black C", [0f64, 0f64, 0f64, 1f64])
+//!     .to_str(&qrcode);
+//! ```
+
+use crate::qr::LogoSize;
+use crate::QRCode;
+
+use super::embed::{self, MimeType};
+use super::frame::Frame;
+use super::path;
+use super::svg::{Layer, PathMode, SvgBuilder, SvgError};
+use super::{
+    coord, number, png, Builder, Color, EyeBallShape, EyeFrameShape, ImageBackgroundShape,
+    ModuleRenderer, Paint, Shape,
+};
+
+/// Points per millimeter
+const POINTS: f64 = 72f64 / 25.4f64;
+
+/// Color space colors are written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorModel {
+    /// `setrgbcolor`, colors as they are given (default)
+    Rgb,
+    /// `setcmykcolor`, colors are converted without a color profile
+    Cmyk,
+}
+
+/// Named ink replacing a color
+struct Spot {
+    /// Red, green and blue of the replaced color
+    rgb: [u8; 3],
+    name: String,
+    /// Cyan, magenta, yellow and black approximating the ink on screen
+    cmyk: [f64; 4],
+}
+
+/// Builder for EPS, contains an [`SvgBuilder`] and adds module size & colors.
+///
+/// Everything is drawn as vector paths, square modules are merged with
+/// [`PathMode::Outline`] by default. PNG and JPEG logos are embedded, from a
+/// base64 `data:` URI or a local file given to [`EpsBuilder::image_file`].
+///
+/// PostScript has no transparency: transparent colors are not drawn, other
+/// colors are opaque and logos are drawn over white. [`Paint::Pattern`] is
+/// filled in black and gradients need PostScript 3.
+pub struct EpsBuilder {
+    /// Width of a module in millimeters, default is 1
+    module_size: f64,
+    color_model: ColorModel,
+    spots: Vec<Spot>,
+    svg_builder: SvgBuilder,
+}
+
+/// Creates an EpsBuilder instance, which contains an [`SvgBuilder`]
+impl Default for EpsBuilder {
+    fn default() -> Self {
+        let mut svg_builder = SvgBuilder::default();
+        svg_builder.path_mode(PathMode::Outline);
+
+        EpsBuilder {
+            module_size: 1f64,
+            color_model: ColorModel::Rgb,
+            spots: Vec::new(),
+            svg_builder,
+        }
+    }
+}
+
+impl Builder for EpsBuilder {
+    fn margin(&mut self, margin: usize) -> &mut Self {
+        self.svg_builder.margin(margin);
+        self
+    }
+
+    fn module_color<P: Into<Paint>>(&mut self, module_color: P) -> &mut Self {
+        self.svg_builder.module_color(module_color);
+        self
+    }
+
+    fn background_color<P: Into<Paint>>(&mut self, background_color: P) -> &mut Self {
+        self.svg_builder.background_color(background_color);
+        self
+    }
+
+    fn shape(&mut self, shape: Shape) -> &mut Self {
+        self.svg_builder.shape(shape);
+        self
+    }
+
+    fn renderer<R: ModuleRenderer + Send + Sync + 'static>(&mut self, renderer: R) -> &mut Self {
+        self.svg_builder.renderer(renderer);
+        self
+    }
+
+    fn image(&mut self, image: String) -> &mut Self {
+        self.svg_builder.image(image);
+        self
+    }
+
+    fn image_background_color<C: Into<Color>>(&mut self, image_background_color: C) -> &mut Self {
+        self.svg_builder
+            .image_background_color(image_background_color);
+        self
+    }
+
+    fn image_background_shape(
+        &mut self,
+        image_background_shape: ImageBackgroundShape,
+    ) -> &mut Self {
+        self.svg_builder
+            .image_background_shape(image_background_shape);
+        self
+    }
+
+    fn image_size(&mut self, image_size: f64, gap: f64) -> &mut Self {
+        self.svg_builder.image_size(image_size, gap);
+        self
+    }
+
+    fn image_position(&mut self, x: f64, y: f64) -> &mut Self {
+        self.svg_builder.image_position(x, y);
+        self
+    }
+
+    fn image_area(&mut self, size: LogoSize) -> &mut Self {
+        self.svg_builder.image_area(size);
+        self
+    }
+
+    fn clear_under_image(&mut self, clear: bool) -> &mut Self {
+        self.svg_builder.clear_under_image(clear);
+        self
+    }
+
+    fn frame(&mut self, frame: Frame) -> &mut Self {
+        self.svg_builder.frame(frame);
+        self
+    }
+
+    fn finder_shape(&mut self, frame: EyeFrameShape, ball: EyeBallShape) -> &mut Self {
+        self.svg_builder.finder_shape(frame, ball);
+        self
+    }
+
+    fn finder_color<P: Into<Paint>>(&mut self, frame: P, ball: P) -> &mut Self {
+        self.svg_builder.finder_color(frame, ball);
+        self
+    }
+
+    fn alignment_shape(&mut self, frame: EyeFrameShape, ball: EyeBallShape) -> &mut Self {
+        self.svg_builder.alignment_shape(frame, ball);
+        self
+    }
+
+    fn alignment_color<P: Into<Paint>>(&mut self, frame: P, ball: P) -> &mut Self {
+        self.svg_builder.alignment_color(frame, ball);
+        self
+    }
+
+    fn shape_color<P: Into<Paint>>(&mut self, shape: Shape, color: P) -> &mut Self {
+        self.svg_builder.shape_color(shape, color);
+        self
+    }
+}
+
+/// How a layer is painted
+enum Fill {
+    /// Operators setting the color
+    Color(String),
+    /// Shading dictionary, painted in the clipped path
+    Shading(String),
+}
+
+/// Cyan, magenta, yellow and black of a color, without a color profile
+fn cmyk([r, g, b]: [u8; 3]) -> [f64; 4] {
+    let [r, g, b] = [r, g, b].map(|c| f64::from(c) / 255f64);
+    let k = 1f64 - r.max(g).max(b);
+    if k >= 1f64 {
+        return [0f64, 0f64, 0f64, 1f64];
+    }
+    [
+        (1f64 - r - k) / (1f64 - k),
+        (1f64 - g - k) / (1f64 - k),
+        (1f64 - b - k) / (1f64 - k),
+        k,
+    ]
+}
+
+/// Escapes a PostScript string, without its parentheses
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if c.is_ascii() && !c.is_ascii_control() => out.push(c),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+/// Hexadecimal data, 64 bytes per line, ending with the `>` of
+/// `ASCIIHexDecode`
+fn hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    let mut out = String::with_capacity(bytes.len() * 2 + bytes.len() / 32 + 2);
+    for line in bytes.chunks(64) {
+        for &byte in line {
+            out.push(DIGITS[usize::from(byte >> 4)] as char);
+            out.push(DIGITS[usize::from(byte & 0xf)] as char);
+        }
+        out.push('\n');
+    }
+    out.push('>');
+    out
+}
+
+/// Embedded image, as an image dictionary and its data
+struct Image {
+    width: usize,
+    height: usize,
+    /// Color space & number of components
+    color_space: &'static str,
+    components: usize,
+    /// JPEG images are decoded by the interpreter
+    dct: bool,
+    data: Vec<u8>,
+}
+
+impl EpsBuilder {
+    /// Updates the width of a module, in millimeters (default: 1)
+    pub fn module_size(&mut self, millimeters: f64) -> &mut Self {
+        self.module_size = millimeters;
+        self
+    }
+
+    /// Updates the color space colors are written in (default: `ColorModel::Rgb`)
+    pub fn color_model(&mut self, color_model: ColorModel) -> &mut Self {
+        self.color_model = color_model;
+        self
+    }
+
+    /// Prints `color` with the spot color `name`, `cmyk` is shown on screen
+    /// and used by printers without the ink.
+    ///
+    /// Solid colors are compared without their alpha, gradients are not
+    /// affected.
+    pub fn spot_color<C: Into<Color>>(
+        &mut self,
+        color: C,
+        name: &str,
+        cmyk: [f64; 4],
+    ) -> &mut Self {
+        if let Some([r, g, b, _]) = color.into().rgba() {
+            self.spots.retain(|spot| spot.rgb != [r, g, b]);
+            self.spots.push(Spot {
+                rgb: [r, g, b],
+                name: name.to_string(),
+                cmyk: cmyk.map(|c| c.clamp(0f64, 1f64)),
+            });
+        }
+        self
+    }
+
+    /// Embeds an image from its bytes, see [`SvgBuilder::image_bytes`]
+    ///
+    /// # Errors
+    /// - `SvgError::SvgError` if the image format is not supported
+    pub fn image_bytes(&mut self, bytes: &[u8]) -> Result<&mut Self, SvgError> {
+        self.svg_builder.image_bytes(bytes)?;
+        Ok(self)
+    }
+
+    /// Embeds a local image file, see [`SvgBuilder::image_file`]
+    ///
+    /// # Errors
+    /// - `SvgError::IoError` if the file cannot be read
+    /// - `SvgError::SvgError` if the image format is not supported
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn image_file<P: AsRef<std::path::Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, SvgError> {
+        self.svg_builder.image_file(path)?;
+        Ok(self)
+    }
+
+    /// Checks the `QRCode` stays readable with the embedded image, see
+    /// [`SvgBuilder::check_image`]
+    ///
+    /// # Errors
+    /// - `SvgError::SvgError` if the image hides too many modules for the `QRCode` to be
+    ///   recovered
+    pub fn check_image(&self, qr: &QRCode) -> Result<(), SvgError> {
+        self.svg_builder.check_image(qr)
+    }
+
+    /// Updates how square modules are written, see [`SvgBuilder::path_mode`]
+    /// (default: `PathMode::Outline`)
+    pub fn path_mode(&mut self, path_mode: PathMode) -> &mut Self {
+        self.svg_builder.path_mode(path_mode);
+        self
+    }
+
+    /// Components of a color in the color model
+    fn components(&self, rgb: [u8; 3]) -> String {
+        let values = match self.color_model {
+            ColorModel::Rgb => rgb.iter().map(|&c| f64::from(c) / 255f64).collect(),
+            ColorModel::Cmyk => cmyk(rgb).to_vec(),
+        };
+        values
+            .into_iter()
+            .map(number)
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// How `paint` is drawn, `None` if it is transparent
+    fn fill(&self, paint: &Paint, size: f64) -> Option<Fill> {
+        let color = match paint {
+            Paint::Color(color) => color.rgba().unwrap_or([0, 0, 0, 255]),
+            Paint::Pattern(_) => [0, 0, 0, 255],
+            _ => {
+                let color_space = match self.color_model {
+                    ColorModel::Rgb => "DeviceRGB",
+                    ColorModel::Cmyk => "DeviceCMYK",
+                };
+                let components = |color: &Color| {
+                    let [r, g, b, _] = color.rgba().unwrap_or([0, 0, 0, 255]);
+                    self.components([r, g, b])
+                };
+                return path::shading(paint, size, color_space, &components).map(Fill::Shading);
+            }
+        };
+
+        let [r, g, b, a] = color;
+        if a == 0 {
+            return None;
+        }
+        if let Some(index) = self.spots.iter().position(|spot| spot.rgb == [r, g, b]) {
+            return Some(Fill::Color(format!("S{index} setcolorspace 1 setcolor ")));
+        }
+        let operator = match self.color_model {
+            ColorModel::Rgb => "setrgbcolor",
+            ColorModel::Cmyk => "setcmykcolor",
+        };
+        Some(Fill::Color(format!(
+            "{} {operator} ",
+            self.components([r, g, b])
+        )))
+    }
+
+    /// Draws a layer, alone in its graphics state. Returns whether a shading
+    /// was used.
+    fn draw(&self, layer: &Layer, size: f64, out: &mut String) -> bool {
+        let fill = match self.fill(&layer.paint, size) {
+            Some(fill) => fill,
+            None => return false,
+        };
+        let path = path::operators(&layer.path);
+        if path.is_empty() {
+            return false;
+        }
+
+        out.push_str("gsave ");
+        let shading = match fill {
+            Fill::Color(color) => {
+                out.push_str(&color);
+                out.push_str(&path);
+                if layer.stroke {
+                    out.push_str("gsave fill grestore .3 setlinewidth 1 setlinejoin stroke");
+                } else {
+                    out.push_str("fill");
+                }
+                false
+            }
+            Fill::Shading(shading) => {
+                out.push_str(&path);
+                if layer.stroke {
+                    out.push_str(&format!("gsave clip newpath {shading} shfill grestore "));
+                    out.push_str(".3 setlinewidth 1 setlinejoin strokepath ");
+                }
+                out.push_str(&format!("clip newpath {shading} shfill"));
+                true
+            }
+        };
+        out.push_str(" grestore\n");
+        shading
+    }
+
+    /// Decodes a PNG image over white, JPEG images are embedded as is
+    fn embedded_image(&self, bytes: &[u8]) -> Option<Image> {
+        match MimeType::sniff(bytes)? {
+            MimeType::Jpeg => {
+                let (width, height, components) = embed::jpeg_header(bytes)?;
+                let color_space = match components {
+                    1 => "/DeviceGray",
+                    3 => "/DeviceRGB",
+                    4 => "/DeviceCMYK",
+                    _ => return None,
+                };
+                Some(Image {
+                    width: width as usize,
+                    height: height as usize,
+                    color_space,
+                    components: usize::from(components),
+                    dct: true,
+                    data: bytes.to_vec(),
+                })
+            }
+            MimeType::Png => {
+                let image = png::decode(bytes)?;
+                let (color_space, components) = match self.color_model {
+                    ColorModel::Rgb => ("/DeviceRGB", 3),
+                    ColorModel::Cmyk => ("/DeviceCMYK", 4),
+                };
+
+                let mut data = Vec::with_capacity(image.width * image.height * components);
+                for pixel in image.pixels.chunks(4) {
+                    let alpha = u32::from(pixel[3]);
+                    let [r, g, b] = [pixel[0], pixel[1], pixel[2]]
+                        .map(|c| ((u32::from(c) * alpha + 255 * (255 - alpha) + 127) / 255) as u8);
+                    match self.color_model {
+                        ColorModel::Rgb => data.extend_from_slice(&[r, g, b]),
+                        ColorModel::Cmyk => {
+                            data.extend(cmyk([r, g, b]).iter().map(|c| (c * 255f64).round() as u8))
+                        }
+                    }
+                }
+
+                Some(Image {
+                    width: image.width,
+                    height: image.height,
+                    color_space,
+                    components,
+                    dct: false,
+                    data,
+                })
+            }
+            MimeType::Svg | MimeType::Webp => None,
+        }
+    }
+
+    /// Return a string containing the EPS for a qr code
+    ///
+    /// # Errors
+    /// - `SvgError::SvgError` if the image is not a PNG or JPEG image in a base64
+    ///   `data:` URI, it would leave a hole in the code
+    pub fn to_str(&self, qr: &QRCode) -> Result<String, SvgError> {
+        let size = (self.svg_builder.margin_size() * 2 + qr.size) as f64;
+        let scale = self.module_size * POINTS;
+        let frame = self.svg_builder.frame_layers(size);
+        // Size of the drawing and position of the code in it, in modules
+        let (drawing, offset) = match &frame {
+            Some((layout, _)) => ((layout.width, layout.height), layout.offset),
+            None => ((size, size), (0f64, 0f64)),
+        };
+        let (width, height) = (drawing.0 * scale, drawing.1 * scale);
+
+        // Module space, going down, to page space, going up
+        let mut body = format!(
+            "{} {} translate {} {} scale\n",
+            number(offset.0 * scale),
+            number(height - offset.1 * scale),
+            number(scale),
+            number(-scale)
+        );
+        let mut shading = false;
+
+        if let Some((_, layers)) = &frame {
+            // The frame starts at the top left of the drawing
+            body.push_str(&format!(
+                "gsave {} {} translate\n",
+                number(-offset.0),
+                number(-offset.1)
+            ));
+            for layer in layers {
+                shading |= self.draw(layer, size, &mut body);
+            }
+            body.push_str("grestore\n");
+        }
+
+        let background = Layer {
+            path: format!("M0,0h{0}v{0}h-{0}z", coord(size)),
+            paint: self.svg_builder.background().clone(),
+            stroke: false,
+        };
+        shading |= self.draw(&background, size, &mut body);
+        for layer in self.svg_builder.layers(qr) {
+            shading |= self.draw(&layer, size, &mut body);
+        }
+
+        if let Some(placed) = self.svg_builder.placed_image(qr) {
+            for layer in &placed.backgrounds {
+                shading |= self.draw(layer, size, &mut body);
+            }
+
+            let image = embed::source_bytes(placed.source)
+                .and_then(|bytes| self.embedded_image(&bytes))
+                .ok_or_else(embed::unsupported_image)?;
+
+            // Fits in the square, keeping the aspect ratio
+            let ratio = placed.size / image.width.max(image.height).max(1) as f64;
+            let (w, h) = (image.width as f64 * ratio, image.height as f64 * ratio);
+            let x = placed.x + (placed.size - w) / 2f64;
+            let y = placed.y + (placed.size - h) / 2f64;
+
+            // The data source is flushed to its end, after the image
+            body.push_str(&format!(
+                "gsave {} {} translate {} {} scale {} setcolorspace\n",
+                number(x),
+                number(y),
+                number(w),
+                number(h),
+                image.color_space
+            ));
+            body.push_str("/Data currentfile /ASCIIHexDecode filter def\n");
+            body.push_str(&format!(
+                "{{ << /ImageType 1 /Width {0} /Height {1} /BitsPerComponent 8 /Decode [{2}] /ImageMatrix [{0} 0 0 {1} 0 0] /DataSource Data{3} >> image Data flushfile }} exec\n",
+                image.width,
+                image.height,
+                vec!["0 1"; image.components].join(" "),
+                if image.dct { " /DCTDecode filter" } else { "" }
+            ));
+            body.push_str(&hex(&image.data));
+            body.push_str("\ngrestore\n");
+        }
+
+        let mut out = String::from("%!PS-Adobe-3.0 EPSF-3.0\n%%Creator: fast_qr\n");
+        out.push_str(&format!(
+            "%%BoundingBox: 0 0 {} {}\n%%HiResBoundingBox: 0 0 {} {}\n",
+            width.ceil(),
+            height.ceil(),
+            number(width),
+            number(height)
+        ));
+        out.push_str(&format!(
+            "%%LanguageLevel: {}\n",
+            if shading { 3 } else { 2 }
+        ));
+        if !self.spots.is_empty() {
+            let names: Vec<String> = self
+                .spots
+                .iter()
+                .map(|spot| format!("({})", escape(&spot.name)))
+                .collect();
+            out.push_str(&format!("%%DocumentCustomColors: {}\n", names.join(" ")));
+            for spot in &self.spots {
+                let [c, m, y, k] = spot.cmyk.map(number);
+                out.push_str(&format!(
+                    "%%CMYKCustomColor: {c} {m} {y} {k} ({})\n",
+                    escape(&spot.name)
+                ));
+            }
+        }
+        out.push_str("%%EndComments\n%%BeginProlog\n");
+        out.push_str("/fast_qr 16 dict def fast_qr begin\n");
+        out.push_str(
+            "/m /moveto load def /l /lineto load def /c /curveto load def /h /closepath load def\n",
+        );
+        for (i, spot) in self.spots.iter().enumerate() {
+            // The tint scales each component of the alternate color
+            let [c, m, y, k] = spot.cmyk.map(number);
+            out.push_str(&format!(
+                "/S{i} [/Separation ({}) /DeviceCMYK {{ dup {c} mul exch dup {m} mul exch dup {y} mul exch {k} mul }}] def\n",
+                escape(&spot.name)
+            ));
+        }
+        out.push_str("end\n%%EndProlog\n");
+
+        out.push_str("fast_qr begin gsave\n");
+        out.push_str(&body);
+        out.push_str("grestore end\nshowpage\n%%EOF\n");
+        Ok(out)
+    }
+
+    /// Saves the EPS for a qr code to a file
+    ///
+    /// # Errors
+    /// - `SvgError::IoError` if the file cannot be written
+    /// - `SvgError::SvgError` if the image cannot be embedded, see [`Self::to_str`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn to_file(&self, qr: &QRCode, file: &str) -> Result<(), SvgError> {
+        std::fs::write(file, self.to_str(qr)?).map_err(SvgError::IoError)
+    }
+}
@@ -0,0 +1,115 @@
+//! Converts [`QRCode`] to ESC/POS commands, for receipt printers
+//!
+//! The code is either sent as a `GS v 0` raster bit image, dot for dot, or
+//! left to the printer with its own `GS ( k` QR code functions.
+//!
+//! ```rust
+//! use fast_qr::convert::escpos::EscPosBuilder;
+//! use fast_qr::qr::QRBuilder;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .build()
+//!     .unwrap();
+//!
+//! let _bytes = EscPosBuilder::default()
+//!     .dots_per_module(6)
+//!     .to_bytes(&qrcode);
+//!
+//! // Same payload and ECL, encoded by the printer
+//! let _bytes = EscPosBuilder::default().to_native(&qrcode, b"https://example.com/");
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::io;
+
+use crate::{QRCode, ECL};
+
+use super::dots;
+
+/// Builder for ESC/POS commands, can set margin and dots per module
+pub struct EscPosBuilder {
+    /// The margin, in modules, default is 4
+    margin: usize,
+    /// Dots per module, default is 4
+    dots_per_module: usize,
+}
+
+impl Default for EscPosBuilder {
+    fn default() -> Self {
+        EscPosBuilder {
+            margin: 4,
+            dots_per_module: 4,
+        }
+    }
+}
+
+/// `GS ( k` command of the QR code symbol (`cn = 49`)
+fn qr_function(out: &mut Vec<u8>, function: u8, parameters: &[u8]) {
+    let len = parameters.len() + 2;
+    out.extend_from_slice(&[0x1d, b'(', b'k', len as u8, (len >> 8) as u8, 49, function]);
+    out.extend_from_slice(parameters);
+}
+
+impl EscPosBuilder {
+    /// Updates margin, in modules (default: 4)
+    pub fn margin(&mut self, margin: usize) -> &mut Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Updates the number of dots per module, at least 1 (default: 4).
+    /// The native command is limited to 16
+    pub fn dots_per_module(&mut self, dots: usize) -> &mut Self {
+        self.dots_per_module = dots.max(1);
+        self
+    }
+
+    /// Return the `GS v 0` raster bit image command drawing the qr code
+    pub fn to_bytes(&self, qr: &QRCode) -> Vec<u8> {
+        let rows = dots::rows(qr, self.margin, self.dots_per_module);
+        let width = rows.first().map_or(0, Vec::len);
+
+        // Normal density, width in bytes then height in dots
+        let mut out = vec![0x1d, b'v', b'0', 0];
+        out.extend_from_slice(&(width as u16).to_le_bytes());
+        out.extend_from_slice(&(rows.len() as u16).to_le_bytes());
+        for row in &rows {
+            out.extend_from_slice(row);
+        }
+        out
+    }
+
+    /// Return the printer's own QR code commands, storing `payload` with the
+    /// ECL of `qr` and printing it.
+    ///
+    /// `payload` should be the input the qr code was built from. The printer
+    /// chooses the version, the mode and the mask.
+    pub fn to_native(&self, qr: &QRCode, payload: &[u8]) -> Vec<u8> {
+        let ecl = match qr.ecl.unwrap_or(ECL::Q) {
+            ECL::L => 48,
+            ECL::M => 49,
+            ECL::Q => 50,
+            ECL::H => 51,
+        };
+
+        let mut out = Vec::new();
+        // Model 2
+        qr_function(&mut out, b'A', &[50, 0]);
+        qr_function(&mut out, b'C', &[self.dots_per_module.min(16) as u8]);
+        qr_function(&mut out, b'E', &[ecl]);
+        let mut store = vec![48];
+        store.extend_from_slice(payload);
+        qr_function(&mut out, b'P', &store);
+        qr_function(&mut out, b'Q', &[48]);
+        out
+    }
+
+    /// Saves the raster bit image command for a qr code to a file
+    ///
+    /// # Errors
+    /// - `io::Error` if the file cannot be written
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn to_file(&self, qr: &QRCode, file: &str) -> io::Result<()> {
+        std::fs::write(file, self.to_bytes(qr))
+    }
+}
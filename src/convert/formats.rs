@@ -0,0 +1,211 @@
+//! Encoders for the image formats other than PNG
+//!
+//! BMP, PBM and PGM are written here, JPEG, WebP, GIF and TIFF use optional
+//! dependencies enabled by the feature of the same name.
+
+use resvg::tiny_skia::Pixmap;
+
+use super::image::ImageError;
+
+/// Straight RGBA pixels
+#[cfg(any(feature = "webp", feature = "gif", feature = "tiff"))]
+pub(crate) fn rgba(pixmap: &Pixmap) -> Vec<u8> {
+    pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect()
+}
+
+/// RGB pixels, transparent parts are drawn over white
+pub(crate) fn rgb(pixmap: &Pixmap) -> Vec<u8> {
+    pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            // Colors are premultiplied, so adding the missing alpha is
+            // blending over white
+            let white = 255 - pixel.alpha();
+            [
+                pixel.red() + white,
+                pixel.green() + white,
+                pixel.blue() + white,
+            ]
+        })
+        .collect()
+}
+
+/// Gray levels, transparent parts are drawn over white
+pub(crate) fn gray(pixmap: &Pixmap) -> Vec<u8> {
+    rgb(pixmap)
+        .chunks(3)
+        .map(|c| {
+            let luma = 299 * u32::from(c[0]) + 587 * u32::from(c[1]) + 114 * u32::from(c[2]);
+            (luma / 1000) as u8
+        })
+        .collect()
+}
+
+/// 24 bits uncompressed BMP
+pub(crate) fn bmp(pixmap: &Pixmap) -> Vec<u8> {
+    const HEADERS: u32 = 14 + 40;
+
+    let (width, height) = (pixmap.width(), pixmap.height());
+    // Rows are padded to 4 bytes
+    let stride = (width * 3 + 3) & !3;
+    let data_size = stride * height;
+
+    let mut out = Vec::with_capacity((HEADERS + data_size) as usize);
+    // File header
+    out.extend_from_slice(b"BM");
+    out.extend_from_slice(&(HEADERS + data_size).to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&HEADERS.to_le_bytes());
+    // BITMAPINFOHEADER, a positive height stores rows bottom-up
+    out.extend_from_slice(&40u32.to_le_bytes());
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&height.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&24u16.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.extend_from_slice(&data_size.to_le_bytes());
+    // 72 dpi
+    out.extend_from_slice(&2835u32.to_le_bytes());
+    out.extend_from_slice(&2835u32.to_le_bytes());
+    out.extend_from_slice(&[0; 8]);
+
+    let pixels = rgb(pixmap);
+    let padding = (stride - width * 3) as usize;
+    for row in pixels.chunks(width as usize * 3).rev() {
+        for c in row.chunks(3) {
+            out.extend_from_slice(&[c[2], c[1], c[0]]);
+        }
+        out.extend(std::iter::repeat(0).take(padding));
+    }
+
+    out
+}
+
+/// Binary PBM, pixels darker than middle gray are black
+pub(crate) fn pbm(pixmap: &Pixmap) -> Vec<u8> {
+    let mut out = format!("P4\n{} {}\n", pixmap.width(), pixmap.height()).into_bytes();
+    for row in gray(pixmap).chunks(pixmap.width() as usize) {
+        // 1 is black, rows are padded to a whole byte
+        out.extend(row.chunks(8).map(|pixels| {
+            pixels.iter().enumerate().fold(0u8, |byte, (i, &pixel)| {
+                byte | (u8::from(pixel < 128) << (7 - i))
+            })
+        }));
+    }
+    out
+}
+
+/// Binary 8 bits PGM
+pub(crate) fn pgm(pixmap: &Pixmap) -> Vec<u8> {
+    let mut out = format!("P5\n{} {}\n255\n", pixmap.width(), pixmap.height()).into_bytes();
+    out.extend_from_slice(&gray(pixmap));
+    out
+}
+
+/// Error for a format whose feature is not enabled
+#[cfg(not(all(feature = "jpeg", feature = "webp", feature = "gif", feature = "tiff")))]
+fn disabled(feature: &str) -> ImageError {
+    ImageError::EncodingError(format!("the `{feature}` feature is not enabled"))
+}
+
+/// Width and height of a format limited to 16 bits sizes
+#[cfg(any(feature = "jpeg", feature = "gif"))]
+fn small_size(pixmap: &Pixmap) -> Result<(u16, u16), ImageError> {
+    use core::convert::TryFrom;
+
+    match (
+        u16::try_from(pixmap.width()),
+        u16::try_from(pixmap.height()),
+    ) {
+        (Ok(width), Ok(height)) => Ok((width, height)),
+        _ => Err(ImageError::EncodingError(String::from(
+            "image is larger than 65535 pixels",
+        ))),
+    }
+}
+
+/// Baseline JPEG
+#[cfg(feature = "jpeg")]
+pub(crate) fn jpeg(pixmap: &Pixmap, quality: u8) -> Result<Vec<u8>, ImageError> {
+    let (width, height) = small_size(pixmap)?;
+    let mut out = Vec::new();
+    jpeg_encoder::Encoder::new(&mut out, quality.clamp(1, 100))
+        .encode(&rgb(pixmap), width, height, jpeg_encoder::ColorType::Rgb)
+        .map_err(|err| ImageError::EncodingError(err.to_string()))?;
+    Ok(out)
+}
+
+#[cfg(not(feature = "jpeg"))]
+pub(crate) fn jpeg(_: &Pixmap, _: u8) -> Result<Vec<u8>, ImageError> {
+    Err(disabled("jpeg"))
+}
+
+/// Lossless WebP
+#[cfg(feature = "webp")]
+pub(crate) fn webp(pixmap: &Pixmap) -> Result<Vec<u8>, ImageError> {
+    let mut out = Vec::new();
+    image_webp::WebPEncoder::new(&mut out)
+        .encode(
+            &rgba(pixmap),
+            pixmap.width(),
+            pixmap.height(),
+            image_webp::ColorType::Rgba8,
+        )
+        .map_err(|err| ImageError::EncodingError(err.to_string()))?;
+    Ok(out)
+}
+
+#[cfg(not(feature = "webp"))]
+pub(crate) fn webp(_: &Pixmap) -> Result<Vec<u8>, ImageError> {
+    Err(disabled("webp"))
+}
+
+/// GIF with a palette of at most 256 colors
+#[cfg(feature = "gif")]
+pub(crate) fn gif(pixmap: &Pixmap) -> Result<Vec<u8>, ImageError> {
+    let (width, height) = small_size(pixmap)?;
+    let mut pixels = rgba(pixmap);
+    let frame = gif::Frame::from_rgba_speed(width, height, &mut pixels, 10);
+
+    let mut out = Vec::new();
+    {
+        let mut encoder = gif::Encoder::new(&mut out, width, height, &[])
+            .map_err(|err| ImageError::EncodingError(err.to_string()))?;
+        encoder
+            .write_frame(&frame)
+            .map_err(|err| ImageError::EncodingError(err.to_string()))?;
+    }
+    Ok(out)
+}
+
+#[cfg(not(feature = "gif"))]
+pub(crate) fn gif(_: &Pixmap) -> Result<Vec<u8>, ImageError> {
+    Err(disabled("gif"))
+}
+
+/// Uncompressed RGBA TIFF
+#[cfg(feature = "tiff")]
+pub(crate) fn tiff(pixmap: &Pixmap) -> Result<Vec<u8>, ImageError> {
+    use tiff::encoder::{colortype::RGBA8, TiffEncoder};
+
+    let mut out = std::io::Cursor::new(Vec::new());
+    TiffEncoder::new(&mut out)
+        .and_then(|mut encoder| {
+            encoder.write_image::<RGBA8>(pixmap.width(), pixmap.height(), &rgba(pixmap))
+        })
+        .map_err(|err| ImageError::EncodingError(err.to_string()))?;
+    Ok(out.into_inner())
+}
+
+#[cfg(not(feature = "tiff"))]
+pub(crate) fn tiff(_: &Pixmap) -> Result<Vec<u8>, ImageError> {
+    Err(disabled("tiff"))
+}
@@ -0,0 +1,199 @@
+//! Frames and caption text around a [`crate::QRCode`]
+//!
+//! ```rust
+//! # #[cfg(feature = "svg")]
+//! # fn main() {
+//! use fast_qr::convert::frame::{CaptionPosition, Frame, FrameStyle};
+//! use fast_qr::convert::{svg::SvgBuilder, Builder};
+//! use fast_qr::qr::QRBuilder;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+//! let _svg = SvgBuilder::default()
+//!     .frame(
+//!         Frame::new(FrameStyle::Tab)
+//!             .caption("SCAN ME")
+//!             .position(CaptionPosition::Below),
+//!     )
+//!     .to_str(&qrcode);
+//! # }
+//! # #[cfg(not(feature = "svg"))]
+//! # fn main() {}
+//! ```
+
+/// Different possible frame styles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameStyle {
+    /// Border around the code, the caption is drawn outside of it
+    Border,
+    /// Border around the code, the caption is in a tab pointing to the code
+    Tab,
+    /// Rounded card holding both the code and the caption
+    Card,
+}
+
+/// Position of the caption
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptionPosition {
+    /// Above the code
+    Above,
+    /// Below the code
+    Below,
+}
+
+/// Frame drawn around the code, in the module color, with an optional caption
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// Style of the frame
+    pub style: FrameStyle,
+    /// Caption text, drawn with a built-in pixel font
+    pub caption: Option<String>,
+    /// Position of the caption
+    pub position: CaptionPosition,
+}
+
+impl Frame {
+    /// Creates a frame without caption
+    #[must_use]
+    pub const fn new(style: FrameStyle) -> Self {
+        Frame {
+            style,
+            caption: None,
+            position: CaptionPosition::Below,
+        }
+    }
+
+    /// Adds a caption, letters are drawn in uppercase (default: none)
+    #[must_use]
+    pub fn caption<S: Into<String>>(mut self, caption: S) -> Self {
+        self.caption = Some(caption.into());
+        self
+    }
+
+    /// Updates the position of the caption (default: below)
+    #[must_use]
+    pub const fn position(mut self, position: CaptionPosition) -> Self {
+        self.position = position;
+        self
+    }
+}
+
+/// Frame elements and the size they need, in modules
+#[cfg(feature = "svg")]
+pub(crate) struct FrameLayout {
+    /// Width of the framed svg
+    pub(crate) width: f64,
+    /// Height of the framed svg
+    pub(crate) height: f64,
+    /// Position of the code, margin included
+    pub(crate) offset: (f64, f64),
+    /// Path data of the frame parts, drawn under the code, and whether they
+    /// are filled with the background instead of the module paint
+    pub(crate) paths: Vec<(String, bool)>,
+}
+
+#[cfg(feature = "svg")]
+impl Frame {
+    /// Width of the border
+    const THICKNESS: f64 = 1f64;
+    /// Largest width of a caption pixel
+    const PIXEL: f64 = 0.5f64;
+    /// Space around the caption
+    const PADDING: f64 = 1f64;
+    /// Space between the border and the tab
+    const TAB_GAP: f64 = 1f64;
+
+    /// Lays the frame out around a code of width `size`, margin included
+    pub(crate) fn layout(&self, size: f64) -> FrameLayout {
+        use super::{coord, font};
+
+        let outer = size + 2f64 * Self::THICKNESS;
+        let caption = self.caption.as_deref().unwrap_or("");
+        let text_width = font::width(caption);
+
+        // Long captions are drawn smaller to fit in the frame
+        let (pixel, band) = if text_width == 0 {
+            (0f64, 0f64)
+        } else {
+            let pixel = Self::PIXEL.min((outer - 2f64 * Self::PADDING) / text_width as f64);
+            (
+                pixel,
+                pixel * font::GLYPH_HEIGHT as f64 + 2f64 * Self::PADDING,
+            )
+        };
+        let gap = if self.style == FrameStyle::Tab && band > 0f64 {
+            Self::TAB_GAP
+        } else {
+            0f64
+        };
+
+        let (border_y, band_y) = match self.position {
+            CaptionPosition::Above => (band + gap, 0f64),
+            CaptionPosition::Below => (0f64, outer + gap),
+        };
+        let text_x = (outer - text_width as f64 * pixel) / 2f64;
+        let text = font::text_path(caption, text_x, band_y + Self::PADDING, pixel);
+
+        let border = format!(
+            "M0,{0}h{1}v{1}h-{1}zM{2},{3}v{4}h{4}v-{4}z",
+            coord(border_y),
+            coord(outer),
+            coord(Self::THICKNESS),
+            coord(border_y + Self::THICKNESS),
+            coord(size)
+        );
+
+        let mut paths = Vec::new();
+        match self.style {
+            FrameStyle::Border => {
+                paths.push((format!("{border}{text}"), false));
+            }
+            FrameStyle::Tab => {
+                paths.push((border, false));
+                if !text.is_empty() {
+                    let tab_width = (text_width as f64 * pixel + 4f64 * Self::PADDING).min(outer);
+                    let center = outer / 2f64;
+                    // Triangle from the tab to the border
+                    let (base, apex) = match self.position {
+                        CaptionPosition::Above => (band, band + gap),
+                        CaptionPosition::Below => (band_y, band_y - gap),
+                    };
+                    let tab = format!(
+                        "M{},{}h{}v{}h-{}zM{},{}h2L{},{}z",
+                        coord((outer - tab_width) / 2f64),
+                        coord(band_y),
+                        coord(tab_width),
+                        coord(band),
+                        coord(tab_width),
+                        coord(center - 1f64),
+                        coord(base),
+                        coord(center),
+                        coord(apex)
+                    );
+                    paths.push((tab, false));
+                    paths.push((text, true));
+                }
+            }
+            FrameStyle::Card => {
+                // Rectangle with corners rounded by 2 modules
+                paths.push((
+                    format!(
+                        "M2,0h{0}a2,2 0 0 1 2,2v{1}a2,2 0 0 1 -2,2h-{0}a2,2 0 0 1 -2,-2v-{1}a2,2 0 0 1 2,-2z",
+                        coord(outer - 4f64),
+                        coord(outer + band - 4f64)
+                    ),
+                    false,
+                ));
+                if !text.is_empty() {
+                    paths.push((text, true));
+                }
+            }
+        }
+
+        FrameLayout {
+            width: outer,
+            height: outer + gap + band,
+            offset: (Self::THICKNESS, border_y + Self::THICKNESS),
+            paths,
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Converts [`QRCode`] to an HTML table with inline styles, for email clients
+//! that strip svg and block images
+//!
+//! ```rust
+//! use fast_qr::convert::html::{HtmlBuilder, Layout};
+//! use fast_qr::qr::QRBuilder;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .build()
+//!     .unwrap();
+//!
+//! let _html = HtmlBuilder::default()
+//!     .margin(2)
+//!     .module_color("#1a237e")
+//!     .module_size(3)
+//!     .layout(Layout::Table)
+//!     .to_str(&qrcode);
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::io;
+
+use crate::QRCode;
+
+use super::Paint;
+
+/// Elements the modules are drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// `<table>` with one cell per run of modules, supported by every email
+    /// client (default)
+    Table,
+    /// `<div>` CSS grid, lighter but not supported by every email client
+    Grid,
+}
+
+/// Builder for HTML, with a margin, colors, module size & layout.
+///
+/// It does not implement [`super::Builder`]: modules are always squares, and
+/// images or frames cannot be drawn with table cells. Gradients are drawn with
+/// the color of their first stop and patterns in black.
+pub struct HtmlBuilder {
+    /// Width & height of a module in CSS pixels, default is 4
+    module_size: usize,
+    layout: Layout,
+    /// Margin in modules, default is 4
+    margin: usize,
+    module_color: Paint,
+    background_color: Paint,
+}
+
+/// Creates a HtmlBuilder instance
+impl Default for HtmlBuilder {
+    fn default() -> Self {
+        HtmlBuilder {
+            module_size: 4,
+            layout: Layout::Table,
+            margin: 4,
+            module_color: [0, 0, 0, 255].into(),
+            background_color: [255; 4].into(),
+        }
+    }
+}
+
+/// CSS color of a paint, safe inside a `style` attribute: only the characters
+/// of hexadecimal, functional and named colors are kept
+fn css_color(paint: &Paint) -> String {
+    let color = match paint {
+        Paint::Color(color) => &color.0,
+        Paint::Pattern(_) => "#000000",
+        Paint::LinearGradient(gradient) => gradient.stops.first().map_or("#000000", |(_, c)| &c.0),
+        Paint::RadialGradient(gradient) => gradient.stops.first().map_or("#000000", |(_, c)| &c.0),
+    };
+    color
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || "#(),.%/ +-".contains(*c))
+        .collect()
+}
+
+/// Lengths of the runs of light then dark modules of a row, starting with
+/// light. The margin is added to the first & last light runs.
+fn runs(qr: &QRCode, y: usize, margin: usize) -> Vec<usize> {
+    let mut runs = vec![margin];
+    for module in &qr[y][..qr.size] {
+        // Odd runs are dark
+        if module.value() != (runs.len() % 2 == 0) {
+            runs.push(0);
+        }
+        *runs.last_mut().unwrap() += 1;
+    }
+    if runs.len() % 2 == 0 {
+        runs.push(0);
+    }
+    *runs.last_mut().unwrap() += margin;
+    runs
+}
+
+impl HtmlBuilder {
+    /// Updates the margin, in modules (default: 4)
+    pub fn margin(&mut self, margin: usize) -> &mut Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Updates the module color (default: #000000)
+    pub fn module_color<P: Into<Paint>>(&mut self, module_color: P) -> &mut Self {
+        self.module_color = module_color.into();
+        self
+    }
+
+    /// Updates the background color (default: #FFFFFF)
+    pub fn background_color<P: Into<Paint>>(&mut self, background_color: P) -> &mut Self {
+        self.background_color = background_color.into();
+        self
+    }
+
+    /// Updates the width & height of a module, in CSS pixels, at least 1
+    /// (default: 4)
+    pub fn module_size(&mut self, pixels: usize) -> &mut Self {
+        self.module_size = pixels.max(1);
+        self
+    }
+
+    /// Updates the elements the modules are drawn with (default: `Layout::Table`)
+    pub fn layout(&mut self, layout: Layout) -> &mut Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Return a string containing the HTML for a qr code
+    pub fn to_str(&self, qr: &QRCode) -> String {
+        match self.layout {
+            Layout::Table => self.table(qr),
+            Layout::Grid => self.grid(qr),
+        }
+    }
+
+    /// Table with one row per row of modules, and margin rows
+    fn table(&self, qr: &QRCode) -> String {
+        let margin = self.margin;
+        let (px, columns) = (self.module_size, qr.size + 2 * margin);
+        let width = columns * px;
+        let dark = format!(r#" style="background:{}""#, css_color(&self.module_color));
+
+        let mut out = format!(
+            r#"<table role="presentation" cellpadding="0" cellspacing="0" border="0" width="{width}" style="border-collapse:collapse;table-layout:fixed;width:{width}px;background:{}">"#,
+            css_color(&self.background_color)
+        );
+        // Columns are sized here, cells only span them
+        out.push_str(&format!(
+            r#"<colgroup><col span="{columns}" width="{px}" style="width:{px}px"></colgroup>"#
+        ));
+        let margin_row = format!(
+            r#"<tr style="height:{0}px"><td colspan="{columns}" height="{0}"></td></tr>"#,
+            margin * px
+        );
+
+        if margin > 0 {
+            out.push_str(&margin_row);
+        }
+        for y in 0..qr.size {
+            out.push_str(&format!(r#"<tr style="height:{px}px">"#));
+            for (i, &run) in runs(qr, y, margin).iter().enumerate() {
+                if run == 0 {
+                    continue;
+                }
+                out.push_str("<td");
+                if run > 1 {
+                    out.push_str(&format!(r#" colspan="{run}""#));
+                }
+                if i % 2 == 1 {
+                    out.push_str(&dark);
+                }
+                out.push_str("></td>");
+            }
+            out.push_str("</tr>");
+        }
+        if margin > 0 {
+            out.push_str(&margin_row);
+        }
+
+        out.push_str("</table>");
+        out
+    }
+
+    /// CSS grid with one cell per run of modules, the margin is a padding
+    fn grid(&self, qr: &QRCode) -> String {
+        let margin = self.margin;
+        let px = self.module_size;
+        let dark = format!(r#";background:{}""#, css_color(&self.module_color));
+
+        let mut out = format!(
+            r#"<div style="display:inline-grid;grid-template-columns:repeat({},{px}px);grid-auto-rows:{px}px;padding:{}px;background:{}">"#,
+            qr.size,
+            margin * px,
+            css_color(&self.background_color)
+        );
+        for y in 0..qr.size {
+            for (i, &run) in runs(qr, y, 0).iter().enumerate() {
+                if run == 0 {
+                    continue;
+                }
+                out.push_str(&format!(r#"<div style="grid-column:span {run}"#));
+                out.push_str(if i % 2 == 1 { &dark } else { "\"" });
+                out.push_str("></div>");
+            }
+        }
+
+        out.push_str("</div>");
+        out
+    }
+
+    /// Saves the HTML for a qr code to a file
+    ///
+    /// # Errors
+    /// - `io::Error` if the file cannot be written
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn to_file(&self, qr: &QRCode, file: &str) -> io::Result<()> {
+        std::fs::write(file, self.to_str(qr))
+    }
+}
@@ -24,20 +24,85 @@
 
 use std::io;
 
+use crate::analysis::Occlusion;
+use crate::qr::LogoSize;
 use crate::QRCode;
 
-use super::Color;
-use super::{svg::SvgBuilder, Builder, Shape};
+use super::formats;
+use super::frame::Frame;
+use super::svg::{PathMode, SvgBuilder, SvgError};
+use super::{Builder, Color, EyeBallShape, EyeFrameShape, ModuleRenderer, Paint, Shape};
 
 use resvg::tiny_skia::{self, Pixmap};
 use resvg::usvg;
 
+/// Different possible image formats.
+///
+/// PNG, BMP, PBM and PGM are always available, the others need the feature of
+/// the same name (`jpeg`, `webp`, `gif`, `tiff`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// PNG, with transparency
+    Png,
+    /// JPEG, with a quality from 1 to 100, drawn over white
+    Jpeg {
+        /// Quality, from 1 (smallest) to 100 (best)
+        quality: u8,
+    },
+    /// Lossless WebP, with transparency
+    WebP,
+    /// 24 bits BMP, drawn over white
+    Bmp,
+    /// GIF, with transparency
+    Gif,
+    /// Black & white PBM, drawn over white
+    Pbm,
+    /// Grayscale PGM, drawn over white
+    Pgm,
+    /// Uncompressed TIFF, with transparency
+    Tiff,
+}
+
+impl ImageFormat {
+    /// Guesses the format from the extension of a file name, case insensitive.
+    ///
+    /// JPEG files use a quality of 90
+    pub fn from_extension<P: AsRef<std::path::Path>>(file: P) -> Option<Self> {
+        let extension = file.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some(ImageFormat::Png),
+            "jpg" | "jpeg" => Some(ImageFormat::Jpeg { quality: 90 }),
+            "webp" => Some(ImageFormat::WebP),
+            "bmp" => Some(ImageFormat::Bmp),
+            "gif" => Some(ImageFormat::Gif),
+            "pbm" => Some(ImageFormat::Pbm),
+            "pgm" => Some(ImageFormat::Pgm),
+            "tif" | "tiff" => Some(ImageFormat::Tiff),
+            _ => None,
+        }
+    }
+
+    /// Whether the encoder of this format is compiled in, see [`ImageFormat`]
+    #[must_use]
+    pub const fn is_enabled(self) -> bool {
+        match self {
+            ImageFormat::Jpeg { .. } => cfg!(feature = "jpeg"),
+            ImageFormat::WebP => cfg!(feature = "webp"),
+            ImageFormat::Gif => cfg!(feature = "gif"),
+            ImageFormat::Tiff => cfg!(feature = "tiff"),
+            ImageFormat::Png | ImageFormat::Bmp | ImageFormat::Pbm | ImageFormat::Pgm => true,
+        }
+    }
+}
+
 /// [`ImageBuilder`] contains an [`SvgBuilder`] and adds some options \
 /// - fit_height adds a max-height boundary
 /// - fit_width adds a max-width boundary
+/// - format sets the image format
 pub struct ImageBuilder {
     fit_height: Option<u32>,
     fit_width: Option<u32>,
+    format: Option<ImageFormat>,
     svg_builder: SvgBuilder,
 }
 
@@ -52,12 +117,22 @@ pub enum ImageError {
     EncodingError(String),
 }
 
+impl From<SvgError> for ImageError {
+    fn from(err: SvgError) -> Self {
+        match err {
+            SvgError::IoError(io_err) => Self::IoError(io_err),
+            SvgError::SvgError(svg_err) => Self::ImageError(svg_err),
+        }
+    }
+}
+
 /// Creates an ImageBuilder instance, which contains an [`SvgBuilder`]
 impl Default for ImageBuilder {
     fn default() -> Self {
         ImageBuilder {
             fit_height: None,
             fit_width: None,
+            format: None,
             svg_builder: Default::default(),
         }
     }
@@ -69,12 +144,12 @@ impl Builder for ImageBuilder {
         self
     }
 
-    fn module_color<C: Into<Color>>(&mut self, module_color: C) -> &mut Self {
+    fn module_color<P: Into<Paint>>(&mut self, module_color: P) -> &mut Self {
         self.svg_builder.module_color(module_color);
         self
     }
 
-    fn background_color<C: Into<Color>>(&mut self, background_color: C) -> &mut Self {
+    fn background_color<P: Into<Paint>>(&mut self, background_color: P) -> &mut Self {
         self.svg_builder.background_color(background_color);
         self
     }
@@ -84,6 +159,11 @@ impl Builder for ImageBuilder {
         self
     }
 
+    fn renderer<R: ModuleRenderer + Send + Sync + 'static>(&mut self, renderer: R) -> &mut Self {
+        self.svg_builder.renderer(renderer);
+        self
+    }
+
     fn image(&mut self, image: String) -> &mut Self {
         self.svg_builder.image(image);
         self
@@ -114,7 +194,42 @@ impl Builder for ImageBuilder {
         self
     }
 
-    fn shape_color<C: Into<Color>>(&mut self, shape: Shape, color: C) -> &mut Self {
+    fn image_area(&mut self, size: LogoSize) -> &mut Self {
+        self.svg_builder.image_area(size);
+        self
+    }
+
+    fn clear_under_image(&mut self, clear: bool) -> &mut Self {
+        self.svg_builder.clear_under_image(clear);
+        self
+    }
+
+    fn frame(&mut self, frame: Frame) -> &mut Self {
+        self.svg_builder.frame(frame);
+        self
+    }
+
+    fn finder_shape(&mut self, frame: EyeFrameShape, ball: EyeBallShape) -> &mut Self {
+        self.svg_builder.finder_shape(frame, ball);
+        self
+    }
+
+    fn finder_color<P: Into<Paint>>(&mut self, frame: P, ball: P) -> &mut Self {
+        self.svg_builder.finder_color(frame, ball);
+        self
+    }
+
+    fn alignment_shape(&mut self, frame: EyeFrameShape, ball: EyeBallShape) -> &mut Self {
+        self.svg_builder.alignment_shape(frame, ball);
+        self
+    }
+
+    fn alignment_color<P: Into<Paint>>(&mut self, frame: P, ball: P) -> &mut Self {
+        self.svg_builder.alignment_color(frame, ball);
+        self
+    }
+
+    fn shape_color<P: Into<Paint>>(&mut self, shape: Shape, color: P) -> &mut Self {
         self.svg_builder.shape_color(shape, color);
         self
     }
@@ -133,6 +248,86 @@ impl ImageBuilder {
         self
     }
 
+    /// Updates the image format (default: from the file extension in
+    /// [`ImageBuilder::to_file`], PNG otherwise)
+    pub fn format(&mut self, format: ImageFormat) -> &mut Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Updates how square modules are written, see [`SvgBuilder::path_mode`].
+    /// Smaller paths are faster to rasterize.
+    pub fn path_mode(&mut self, path_mode: PathMode) -> &mut Self {
+        self.svg_builder.path_mode(path_mode);
+        self
+    }
+
+    /// Embeds an image from its raw bytes, see [`SvgBuilder::image_bytes`]
+    ///
+    /// # Errors
+    /// - `ImageError::ImageError` if the image format is not supported
+    pub fn image_bytes(&mut self, bytes: &[u8]) -> Result<&mut Self, ImageError> {
+        self.svg_builder.image_bytes(bytes)?;
+        Ok(self)
+    }
+
+    /// Embeds a local image file, see [`SvgBuilder::image_file`]
+    ///
+    /// # Errors
+    /// - `ImageError::IoError` if the file cannot be read
+    /// - `ImageError::ImageError` if the image format is not supported
+    pub fn image_file<P: AsRef<std::path::Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, ImageError> {
+        self.svg_builder.image_file(path)?;
+        Ok(self)
+    }
+
+    /// Embeds a downscaled image, see [`SvgBuilder::image_bytes_downscaled`]
+    ///
+    /// # Errors
+    /// - `ImageError::ImageError` if the image cannot be decoded or re-encoded
+    pub fn image_bytes_downscaled(
+        &mut self,
+        bytes: &[u8],
+        max_size: u32,
+    ) -> Result<&mut Self, ImageError> {
+        self.svg_builder.image_bytes_downscaled(bytes, max_size)?;
+        Ok(self)
+    }
+
+    /// Embeds a downscaled local image file, see [`SvgBuilder::image_file_downscaled`]
+    ///
+    /// # Errors
+    /// - `ImageError::IoError` if the file cannot be read
+    /// - `ImageError::ImageError` if the image cannot be decoded or re-encoded
+    pub fn image_file_downscaled<P: AsRef<std::path::Path>>(
+        &mut self,
+        path: P,
+        max_size: u32,
+    ) -> Result<&mut Self, ImageError> {
+        self.svg_builder.image_file_downscaled(path, max_size)?;
+        Ok(self)
+    }
+
+    /// Checks the `QRCode` stays readable with the embedded image, see
+    /// [`SvgBuilder::check_image`]
+    ///
+    /// # Errors
+    /// - `ImageError::ImageError` if the image hides too many modules for the `QRCode` to be
+    ///   recovered
+    pub fn check_image(&self, qr: &QRCode) -> Result<(), ImageError> {
+        self.svg_builder.check_image(qr)?;
+        Ok(())
+    }
+
+    /// Returns the modules hidden by the embedded image background, see
+    /// [`SvgBuilder::cleared_modules`]
+    pub fn cleared_modules(&self, qr: &QRCode) -> Occlusion {
+        self.svg_builder.cleared_modules(qr)
+    }
+
     // From https://github.com/RazrFalcon/resvg/blob/374a25f/crates/resvg/tests/integration/main.rs
     /// Return a pixmap containing the svg for a QRCode
     pub fn to_pixmap(&self, qr: &QRCode) -> Pixmap {
@@ -168,19 +363,47 @@ impl ImageBuilder {
         pixmap
     }
 
-    /// Saves the image for a QRCode to a file
+    /// Saves the image for a QRCode to a file, the format is the one set with
+    /// [`ImageBuilder::format`], or guessed from the extension, or PNG.
+    ///
+    /// Files whose extension needs a format that is not enabled are written as
+    /// PNG, like before other formats were supported. Use [`ImageBuilder::format`]
+    /// to get an error instead.
+    ///
+    /// # Errors
+    /// - `ImageError::EncodingError` if the format cannot be encoded
+    /// - `ImageError::IoError` if the file cannot be written
     pub fn to_file(&self, qr: &QRCode, file: &str) -> Result<(), ImageError> {
-        use io::{Error, ErrorKind};
-
-        self.to_pixmap(qr)
-            .save_png(file)
-            .map_err(|err| ImageError::IoError(Error::new(ErrorKind::Other, err.to_string())))
+        let format = self
+            .format
+            .or_else(|| ImageFormat::from_extension(file).filter(|format| format.is_enabled()))
+            .unwrap_or(ImageFormat::Png);
+        let bytes = self.encode(qr, format)?;
+        std::fs::write(file, bytes).map_err(ImageError::IoError)
     }
 
-    /// Saves the image for a QRCode in a byte buffer
+    /// Saves the image for a QRCode in a byte buffer, in the format set with
+    /// [`ImageBuilder::format`] or PNG
+    ///
+    /// # Errors
+    /// - `ImageError::EncodingError` if the format cannot be encoded
     pub fn to_bytes(&self, qr: &QRCode) -> Result<Vec<u8>, ImageError> {
-        let out = self.to_pixmap(qr);
-        out.encode_png()
-            .map_err(|err| ImageError::EncodingError(err.to_string()))
+        self.encode(qr, self.format.unwrap_or(ImageFormat::Png))
+    }
+
+    fn encode(&self, qr: &QRCode, format: ImageFormat) -> Result<Vec<u8>, ImageError> {
+        let pixmap = self.to_pixmap(qr);
+        match format {
+            ImageFormat::Png => pixmap
+                .encode_png()
+                .map_err(|err| ImageError::EncodingError(err.to_string())),
+            ImageFormat::Jpeg { quality } => formats::jpeg(&pixmap, quality),
+            ImageFormat::WebP => formats::webp(&pixmap),
+            ImageFormat::Bmp => Ok(formats::bmp(&pixmap)),
+            ImageFormat::Gif => formats::gif(&pixmap),
+            ImageFormat::Pbm => Ok(formats::pbm(&pixmap)),
+            ImageFormat::Pgm => Ok(formats::pgm(&pixmap)),
+            ImageFormat::Tiff => formats::tiff(&pixmap),
+        }
     }
 }
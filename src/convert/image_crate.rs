@@ -0,0 +1,129 @@
+//! Renders [`QRCode`] into `image` crate buffers, [`GrayImage`] or
+//! [`RgbaImage`], without encoding
+//!
+//! Every module is drawn on `scale × scale` pixels. With the `image` feature,
+//! [`from_pixmap`] also converts the output of
+//! [`ImageBuilder::to_pixmap`](super::image::ImageBuilder::to_pixmap).
+//!
+//! The buffers are from `image` 0.25, re-exported as [`image`] so they match
+//! the version used here.
+//!
+//! ```rust
+//! use fast_qr::convert::image_crate::image::Rgba;
+//! use fast_qr::convert::image_crate::ImageCrateBuilder;
+//! use fast_qr::qr::QRBuilder;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .build()
+//!     .unwrap();
+//!
+//! let _img = ImageCrateBuilder::default()
+//!     .scale(10)
+//!     .module_color(Rgba([26, 35, 126, 255]))
+//!     .to_rgba(&qrcode);
+//! ```
+
+use image_crate::{GrayImage, ImageBuffer, Pixel, Rgba, RgbaImage};
+
+/// The `image` crate the buffers come from
+pub use image_crate as image;
+
+use crate::QRCode;
+
+use super::dots;
+
+#[cfg(feature = "image")]
+use resvg::tiny_skia::Pixmap;
+
+/// Builder for `image` crate buffers, can set margin, scale and colors
+pub struct ImageCrateBuilder {
+    /// The margin, in modules, default is 4
+    margin: usize,
+    /// Pixels per module, default is 8
+    scale: u32,
+    /// Color of the modules, default is opaque black
+    module_color: Rgba<u8>,
+    /// Color of the background, default is opaque white
+    background_color: Rgba<u8>,
+}
+
+impl Default for ImageCrateBuilder {
+    fn default() -> Self {
+        ImageCrateBuilder {
+            margin: 4,
+            scale: 8,
+            module_color: Rgba([0, 0, 0, 255]),
+            background_color: Rgba([255, 255, 255, 255]),
+        }
+    }
+}
+
+impl ImageCrateBuilder {
+    /// Updates margin, in modules (default: 4)
+    pub fn margin(&mut self, margin: usize) -> &mut Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Updates the number of pixels per module, at least 1 (default: 8)
+    pub fn scale(&mut self, scale: u32) -> &mut Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Updates the color of the modules (default: opaque black)
+    pub fn module_color(&mut self, module_color: Rgba<u8>) -> &mut Self {
+        self.module_color = module_color;
+        self
+    }
+
+    /// Updates the color of the background (default: opaque white)
+    pub fn background_color(&mut self, background_color: Rgba<u8>) -> &mut Self {
+        self.background_color = background_color;
+        self
+    }
+
+    fn render<P: Pixel>(&self, qr: &QRCode, dark: P, light: P) -> ImageBuffer<P, Vec<P::Subpixel>> {
+        let side = (qr.size + 2 * self.margin) as u32 * self.scale;
+
+        ImageBuffer::from_fn(side, side, |x, y| {
+            let (x, y) = ((x / self.scale) as usize, (y / self.scale) as usize);
+            if dots::dark(qr, self.margin, x, y) {
+                dark
+            } else {
+                light
+            }
+        })
+    }
+
+    /// Return the qr code as a grayscale image, the colors are converted to
+    /// luma and alpha is ignored
+    pub fn to_gray(&self, qr: &QRCode) -> GrayImage {
+        self.render(
+            qr,
+            self.module_color.to_luma(),
+            self.background_color.to_luma(),
+        )
+    }
+
+    /// Return the qr code as an RGBA image
+    pub fn to_rgba(&self, qr: &QRCode) -> RgbaImage {
+        self.render(qr, self.module_color, self.background_color)
+    }
+}
+
+/// Converts a premultiplied [`Pixmap`] to a straight alpha [`RgbaImage`], pixel
+/// for pixel
+#[cfg(feature = "image")]
+pub fn from_pixmap(pixmap: &Pixmap) -> RgbaImage {
+    let data = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    RgbaImage::from_raw(pixmap.width(), pixmap.height(), data)
+        .expect("Pixmap has 4 bytes per pixel")
+}
@@ -0,0 +1,547 @@
+//! Converts [`QRCode`] to a 3D model, binary STL or 3MF, for printed tiles
+//!
+//! Dark modules are raised over a base plate covering the code and its quiet
+//! zone. Distances are in millimeters, `z` goes up and the bottom of the plate
+//! is at `z = 0`.
+//!
+//! ```rust
+//! use fast_qr::convert::mesh::MeshBuilder;
+//! use fast_qr::qr::QRBuilder;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .build()
+//!     .unwrap();
+//!
+//! // 2 mm modules on a 1.5 mm plate, raised by 0.6 mm
+//! let _stl = MeshBuilder::default()
+//!     .module_size(2f64)
+//!     .base_thickness(1.5f64)
+//!     .raised_height(0.6f64)
+//!     .to_stl(&qrcode);
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+#[cfg(not(target_arch = "wasm32"))]
+use std::io;
+
+use crate::QRCode;
+
+use super::crc::crc32;
+use super::{contour, dots, number};
+
+/// File formats of a mesh
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Binary STL, a list of triangles without units
+    Stl,
+    /// 3MF package, an indexed mesh in millimeters
+    ThreeMf,
+}
+
+/// Builder for 3D models, can set margin, sizes and whether modules are merged
+pub struct MeshBuilder {
+    /// The margin, in modules, default is 4
+    margin: usize,
+    /// Width of a module in millimeters, default is 1
+    module_size: f64,
+    /// Thickness of the base plate in millimeters, default is 2
+    base_thickness: f64,
+    /// Height of the dark modules over the plate in millimeters, default is 1
+    raised_height: f64,
+    /// Merges the plate and the dark modules into a single solid, default is true
+    merge: bool,
+}
+
+impl Default for MeshBuilder {
+    fn default() -> Self {
+        MeshBuilder {
+            margin: 4,
+            module_size: 1f64,
+            base_thickness: 2f64,
+            raised_height: 1f64,
+            merge: true,
+        }
+    }
+}
+
+/// Indexed triangles, made of closed shells
+struct Mesh {
+    vertices: Vec<[f64; 3]>,
+    triangles: Vec<[usize; 3]>,
+    /// Index of the vertices by shell, grid position and level
+    indices: HashMap<(usize, usize, usize, usize), usize>,
+    /// `z` of each level
+    levels: Vec<f64>,
+    module_size: f64,
+}
+
+impl Mesh {
+    /// Index of the vertex at the corner `(x, y)` of the grid, shared inside
+    /// of a shell, or between the polygons of a merged solid
+    fn vertex(&mut self, shell: usize, (x, y): (usize, usize), level: usize) -> usize {
+        let (vertices, module_size, z) = (&mut self.vertices, self.module_size, self.levels[level]);
+        *self.indices.entry((shell, x, y, level)).or_insert_with(|| {
+            vertices.push([x as f64 * module_size, y as f64 * module_size, z]);
+            vertices.len() - 1
+        })
+    }
+
+    /// Two triangles, `corners` are counterclockwise seen from outside
+    fn quad(&mut self, shell: usize, corners: [((usize, usize), usize); 4]) {
+        let [a, b, c, d] = corners.map(|(position, level)| self.vertex(shell, position, level));
+        self.triangles.push([a, b, c]);
+        self.triangles.push([a, c, d]);
+    }
+
+    /// Horizontal rectangle facing up, or down if `up` is false
+    fn horizontal(
+        &mut self,
+        shell: usize,
+        (x0, y0): (usize, usize),
+        (x1, y1): (usize, usize),
+        level: usize,
+        up: bool,
+    ) {
+        let mut corners = [
+            ((x0, y0), level),
+            ((x1, y0), level),
+            ((x1, y1), level),
+            ((x0, y1), level),
+        ];
+        if !up {
+            corners.reverse();
+        }
+        self.quad(shell, corners);
+    }
+
+    /// Vertical wall between two corners, facing right of `from` → `to`. It
+    /// is split at every level, so walls meet on shared edges
+    fn wall(
+        &mut self,
+        shell: usize,
+        from: (usize, usize),
+        to: (usize, usize),
+        low: usize,
+        high: usize,
+    ) {
+        for level in low..high {
+            self.quad(
+                shell,
+                [
+                    (from, level),
+                    (to, level),
+                    (to, level + 1),
+                    (from, level + 1),
+                ],
+            );
+        }
+    }
+
+    /// Convex polygon, counterclockwise seen from outside, corners may be in
+    /// the middle of its sides. Ears are clipped so that what remains has an
+    /// area, no triangle is flat
+    fn polygon(&mut self, corners: &[((usize, usize), usize, usize)]) {
+        let points: Vec<[i64; 3]> = corners
+            .iter()
+            .map(|&((x, y), level, _)| [x as i64, y as i64, level as i64])
+            .collect();
+        let indices: Vec<usize> = corners
+            .iter()
+            .map(|&(position, level, shell)| self.vertex(shell, position, level))
+            .collect();
+
+        let cross = |a: [i64; 3], b: [i64; 3]| {
+            [
+                a[1] * b[2] - a[2] * b[1],
+                a[2] * b[0] - a[0] * b[2],
+                a[0] * b[1] - a[1] * b[0],
+            ]
+        };
+        // Newell's normal, the outside of the polygon
+        let mut normal = [0i64; 3];
+        for (i, &point) in points.iter().enumerate() {
+            let next = cross(point, points[(i + 1) % points.len()]);
+            normal = [0, 1, 2].map(|c| normal[c] + next[c]);
+        }
+        // Twice the area of a triangle, negative if it faces inside
+        let area = |a: usize, b: usize, c: usize| {
+            let (u, v) = (
+                [0, 1, 2].map(|i| points[b][i] - points[a][i]),
+                [0, 1, 2].map(|i| points[c][i] - points[a][i]),
+            );
+            let n = cross(u, v);
+            n[0] * normal[0] + n[1] * normal[1] + n[2] * normal[2]
+        };
+
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        let mut total: i64 = (1..order.len() - 1).map(|i| area(0, i, i + 1)).sum();
+        while order.len() > 3 {
+            let len = order.len();
+            let ear = (0..len)
+                .map(|i| [order[(i + len - 1) % len], order[i], order[(i + 1) % len]])
+                .find(|&[a, b, c]| (1..total).contains(&area(a, b, c)));
+            let [a, b, c] = match ear {
+                Some(ear) => ear,
+                None => return,
+            };
+            self.triangles.push([indices[a], indices[b], indices[c]]);
+            total -= area(a, b, c);
+            order.retain(|&i| i != b);
+        }
+        self.triangles
+            .push([indices[order[0]], indices[order[1]], indices[order[2]]]);
+    }
+
+    /// Box over the rectangle, between two levels
+    fn cuboid(
+        &mut self,
+        shell: usize,
+        (x0, y0): (usize, usize),
+        (x1, y1): (usize, usize),
+        low: usize,
+        high: usize,
+    ) {
+        self.horizontal(shell, (x0, y0), (x1, y1), low, false);
+        self.horizontal(shell, (x0, y0), (x1, y1), high, true);
+        let corners = [(x0, y0), (x1, y0), (x1, y1), (x0, y1)];
+        for i in 0..4 {
+            self.wall(shell, corners[i], corners[(i + 1) % 4], low, high);
+        }
+    }
+}
+
+/// Cells merged into rectangles: the runs of each row, grown over the next
+/// rows while they have the same run
+fn rectangles(filled: impl Fn(usize) -> bool, size: usize) -> Vec<[(usize, usize); 2]> {
+    let mut rectangles = Vec::new();
+    // First row of the growing rectangles, by columns
+    let mut growing = BTreeMap::new();
+    for y in 0..=size {
+        let mut runs = BTreeMap::new();
+        let mut x = 0;
+        while y < size && x < size {
+            let start = x;
+            while x < size && filled(y * size + x) {
+                x += 1;
+            }
+            if x > start {
+                let first = growing.remove(&(start, x)).unwrap_or(y);
+                runs.insert((start, x), first);
+            }
+            x += 1;
+        }
+
+        for ((x0, x1), y0) in growing {
+            rectangles.push([(x0, y0), (x1, y)]);
+        }
+        growing = runs;
+    }
+    rectangles
+}
+
+/// Zip archive of uncompressed files
+fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let (mut out, mut directory) = (Vec::new(), Vec::new());
+    for &(name, data) in files {
+        // Version 2.0, no flags, stored, 1980-01-01 00:00
+        let mut header = Vec::new();
+        header.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0x21, 0]);
+        header.extend_from_slice(&crc32(data).to_le_bytes());
+        header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&[0, 0]);
+
+        directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        directory.extend_from_slice(&[20, 0]);
+        directory.extend_from_slice(&header);
+        // No comment, disk 0, no attributes
+        directory.extend_from_slice(&[0; 10]);
+        directory.extend_from_slice(&(out.len() as u32).to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+
+        out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        out.extend_from_slice(&header);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+    }
+
+    let offset = out.len() as u32;
+    out.extend_from_slice(&directory);
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    out.extend_from_slice(&offset.to_le_bytes());
+    out.extend_from_slice(&[0, 0]);
+    out
+}
+
+impl MeshBuilder {
+    /// Updates margin, in modules (default: 4)
+    pub fn margin(&mut self, margin: usize) -> &mut Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Updates the width of a module, in millimeters (default: 1)
+    pub fn module_size(&mut self, millimeters: f64) -> &mut Self {
+        self.module_size = millimeters;
+        self
+    }
+
+    /// Updates the thickness of the base plate, in millimeters, 0 for no
+    /// plate (default: 2)
+    pub fn base_thickness(&mut self, millimeters: f64) -> &mut Self {
+        self.base_thickness = millimeters.max(0f64);
+        self
+    }
+
+    /// Updates the height of the dark modules over the plate, in millimeters
+    /// (default: 1)
+    pub fn raised_height(&mut self, millimeters: f64) -> &mut Self {
+        self.raised_height = millimeters.max(0f64);
+        self
+    }
+
+    /// Merges the plate and the dark modules into a single watertight solid,
+    /// or writes one box per dark module over a separate plate (default: true).
+    /// Dark modules touching by a corner get their own vertices at that
+    /// corner, every edge of the solid is shared by exactly two triangles
+    pub fn merge(&mut self, merge: bool) -> &mut Self {
+        self.merge = merge;
+        self
+    }
+
+    fn mesh(&self, qr: &QRCode) -> Mesh {
+        let size = qr.size + 2 * self.margin;
+        let mut levels = vec![0f64];
+        if self.base_thickness > 0f64 {
+            levels.push(self.base_thickness);
+        }
+        if self.raised_height > 0f64 {
+            levels.push(self.base_thickness + self.raised_height);
+        }
+        let (plate, top) = (usize::from(self.base_thickness > 0f64), levels.len() - 1);
+
+        let mut mesh = Mesh {
+            vertices: Vec::new(),
+            triangles: Vec::new(),
+            indices: HashMap::new(),
+            levels,
+            module_size: self.module_size,
+        };
+
+        // Rows go up, the first row of the code is at the top
+        let dark = |x: usize, y: usize| dots::dark(qr, self.margin, x, size - 1 - y);
+
+        if !self.merge {
+            if plate > 0 {
+                mesh.cuboid(0, (0, 0), (size, size), 0, plate);
+            }
+            if top > plate {
+                for y in 0..size {
+                    for x in 0..size {
+                        if dark(x, y) {
+                            mesh.cuboid(1 + y * size + x, (x, y), (x + 1, y + 1), plate, top);
+                        }
+                    }
+                }
+            }
+            return mesh;
+        }
+
+        // Height field: level of the top of each module, 0 where there is
+        // nothing, and the modules at or over each level. Level 0 is the
+        // bottom, under every module over it
+        let heights: Vec<usize> = (0..size * size)
+            .map(|i| if dark(i % size, i / size) { top } else { plate })
+            .collect();
+        let over: Vec<Vec<bool>> = (0..=top)
+            .map(|level| heights.iter().map(|&h| h >= level.max(1)).collect())
+            .collect();
+        // Corners where the modules at or over a level only touch diagonally.
+        // Each side gets its own vertex, so that the solid stays manifold
+        let pinched = |level: usize, (x, y): (usize, usize)| {
+            let at = |x: usize, y: usize| {
+                x > 0 && y > 0 && x <= size && y <= size && over[level][(y - 1) * size + x - 1]
+            };
+            let (a, b, c, d) = (at(x, y), at(x + 1, y), at(x, y + 1), at(x + 1, y + 1));
+            a == d && b == c && a != b
+        };
+
+        // Polygons, with the module next to each corner. Faces are merged
+        // into rectangles, walls follow the outlines of each level
+        let mut polygons = Vec::new();
+        for level in 0..=top {
+            let faces = rectangles(
+                |i| match level {
+                    0 => heights[i] > 0,
+                    _ => heights[i] == level,
+                },
+                size,
+            );
+            for [(x0, y0), (x1, y1)] in faces {
+                let mut corners = vec![
+                    ((x0, y0), level, (x0, y0)),
+                    ((x1, y0), level, (x1 - 1, y0)),
+                    ((x1, y1), level, (x1 - 1, y1 - 1)),
+                    ((x0, y1), level, (x0, y1 - 1)),
+                ];
+                // The bottom faces down
+                if level == 0 {
+                    corners.reverse();
+                }
+                polygons.push(corners);
+            }
+        }
+        for (level, over) in over.iter().enumerate().skip(1) {
+            for outline in contour::trace(over, size, size) {
+                for (i, &from) in outline.iter().enumerate() {
+                    let to = outline[(i + 1) % outline.len()];
+                    let (dx, dy) = (
+                        (to.0 as isize - from.0 as isize).signum(),
+                        (to.1 as isize - from.1 as isize).signum(),
+                    );
+                    let before = ((to.0 as isize - dx) as usize, (to.1 as isize - dy) as usize);
+                    let (first, last) = (
+                        contour::right_cell(from, to),
+                        contour::right_cell(before, to),
+                    );
+                    polygons.push(vec![
+                        (from, level - 1, first),
+                        (to, level - 1, last),
+                        (to, level, last),
+                        (from, level, first),
+                    ]);
+                }
+            }
+        }
+
+        // Sides are split at the corners of the other polygons on the same
+        // line, so that edges always end at the same vertices
+        let mut lines: HashMap<(bool, usize, usize), Vec<usize>> = HashMap::new();
+        for corners in &polygons {
+            for &((x, y), level, _) in corners {
+                lines.entry((true, level, y)).or_default().push(x);
+                lines.entry((false, level, x)).or_default().push(y);
+            }
+        }
+        for line in lines.values_mut() {
+            line.sort_unstable();
+            line.dedup();
+        }
+
+        for corners in polygons {
+            let mut split = Vec::new();
+            for (i, &(from, level, (x, y))) in corners.iter().enumerate() {
+                let shell = if pinched(level, from) {
+                    1 + y * size + x
+                } else {
+                    0
+                };
+                split.push((from, level, shell));
+
+                let (to, to_level, _) = corners[(i + 1) % corners.len()];
+                if to_level != level {
+                    continue;
+                }
+                let (horizontal, line, start, end) = if from.1 == to.1 {
+                    (true, from.1, from.0, to.0)
+                } else {
+                    (false, from.0, from.1, to.1)
+                };
+                let points = &lines[&(horizontal, level, line)];
+                let low = points.partition_point(|&p| p <= start.min(end));
+                let high = points.partition_point(|&p| p < start.max(end));
+                let inner = points[low..high].iter();
+                let position = |p: usize| if horizontal { (p, line) } else { (line, p) };
+                if start < end {
+                    split.extend(inner.map(|&p| (position(p), level, 0)));
+                } else {
+                    split.extend(inner.rev().map(|&p| (position(p), level, 0)));
+                }
+            }
+            mesh.polygon(&split);
+        }
+        mesh
+    }
+
+    /// Return the binary STL for a qr code, in millimeters
+    pub fn to_stl(&self, qr: &QRCode) -> Vec<u8> {
+        let mesh = self.mesh(qr);
+
+        let mut out = b"fast_qr".to_vec();
+        out.resize(80, 0);
+        out.extend_from_slice(&(mesh.triangles.len() as u32).to_le_bytes());
+        for triangle in &mesh.triangles {
+            let [a, b, c] = triangle.map(|i| mesh.vertices[i]);
+            let (u, v) = (
+                [0, 1, 2].map(|i| b[i] - a[i]),
+                [0, 1, 2].map(|i| c[i] - a[i]),
+            );
+            let normal = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            let length = normal.iter().map(|n| n * n).sum::<f64>().sqrt();
+
+            for n in normal {
+                let n = if length > 0f64 { n / length } else { 0f64 };
+                out.extend_from_slice(&(n as f32).to_le_bytes());
+            }
+            for coordinate in [a, b, c].iter().flatten() {
+                out.extend_from_slice(&(*coordinate as f32).to_le_bytes());
+            }
+            // Attribute byte count
+            out.extend_from_slice(&[0, 0]);
+        }
+        out
+    }
+
+    /// Return the 3MF package for a qr code, in millimeters
+    pub fn to_3mf(&self, qr: &QRCode) -> Vec<u8> {
+        let mesh = self.mesh(qr);
+
+        let mut model = String::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?><model unit="millimeter" xml:lang="en-US" xmlns="http://schemas.microsoft.com/3dmanufacturing/core/2015/02"><resources><object id="1" type="model"><mesh><vertices>"#,
+        );
+        for [x, y, z] in &mesh.vertices {
+            model.push_str(&format!(
+                r#"<vertex x="{}" y="{}" z="{}"/>"#,
+                number(*x),
+                number(*y),
+                number(*z)
+            ));
+        }
+        model.push_str("</vertices><triangles>");
+        for [a, b, c] in &mesh.triangles {
+            model.push_str(&format!(r#"<triangle v1="{a}" v2="{b}" v3="{c}"/>"#));
+        }
+        model.push_str(r#"</triangles></mesh></object></resources><build><item objectid="1"/></build></model>"#);
+
+        let content_types = r#"<?xml version="1.0" encoding="UTF-8"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="model" ContentType="application/vnd.ms-package.3dmanufacturing-3dmodel+xml"/></Types>"#;
+        let relationships = r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Target="/3D/3dmodel.model" Id="rel0" Type="http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel"/></Relationships>"#;
+
+        zip(&[
+            ("[Content_Types].xml", content_types.as_bytes()),
+            ("_rels/.rels", relationships.as_bytes()),
+            ("3D/3dmodel.model", model.as_bytes()),
+        ])
+    }
+
+    /// Saves the model for a qr code to a file
+    ///
+    /// # Errors
+    /// - `io::Error` if the file cannot be written
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn to_file(&self, qr: &QRCode, file: &str, format: Format) -> io::Result<()> {
+        let bytes = match format {
+            Format::Stl => self.to_stl(qr),
+            Format::ThreeMf => self.to_3mf(qr),
+        };
+        std::fs::write(file, bytes)
+    }
+}
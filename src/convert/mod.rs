@@ -8,12 +8,83 @@ use core::ops::Deref;
 #[cfg(feature = "svg")]
 use svg::SvgError;
 
+pub mod contour;
+#[cfg(any(feature = "raster", feature = "mesh"))]
+pub(crate) mod crc;
+pub(crate) mod dots;
+#[cfg(feature = "svg")]
+pub(crate) mod embed;
+#[cfg(feature = "svg")]
+pub(crate) mod font;
+#[cfg(feature = "image")]
+pub(crate) mod formats;
+pub mod frame;
+#[cfg(any(feature = "pdf", feature = "eps"))]
+pub(crate) mod path;
+
 #[cfg(feature = "image")]
 #[cfg_attr(docsrs, doc(cfg(feature = "image")))]
 pub mod image;
 #[cfg(feature = "image")]
 use image::ImageError;
 
+#[cfg(any(feature = "pdf", feature = "eps"))]
+mod named_colors;
+#[cfg(any(feature = "pdf", feature = "eps"))]
+use named_colors::NAMED_COLORS;
+
+#[cfg(any(feature = "raster", feature = "pdf", feature = "eps"))]
+pub(crate) mod png;
+#[cfg(feature = "raster")]
+#[cfg_attr(docsrs, doc(cfg(feature = "raster")))]
+pub mod raster;
+pub mod terminal;
+
+#[cfg(feature = "pdf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "pdf")))]
+pub mod pdf;
+
+#[cfg(feature = "eps")]
+#[cfg_attr(docsrs, doc(cfg(feature = "eps")))]
+pub mod eps;
+
+#[cfg(feature = "html")]
+#[cfg_attr(docsrs, doc(cfg(feature = "html")))]
+pub mod html;
+
+#[cfg(feature = "dxf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dxf")))]
+pub mod dxf;
+
+#[cfg(feature = "mesh")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mesh")))]
+pub mod mesh;
+
+#[cfg(feature = "zpl")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zpl")))]
+pub mod zpl;
+
+#[cfg(feature = "escpos")]
+#[cfg_attr(docsrs, doc(cfg(feature = "escpos")))]
+pub mod escpos;
+
+#[cfg(feature = "source")]
+#[cfg_attr(docsrs, doc(cfg(feature = "source")))]
+pub mod source;
+
+#[cfg(feature = "embedded-graphics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "embedded-graphics")))]
+pub mod embedded;
+
+#[cfg(feature = "image-crate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "image-crate")))]
+pub mod image_crate;
+
+#[cfg(feature = "ratatui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ratatui")))]
+pub mod tui;
+
+use crate::qr::LogoSize;
 use crate::Module;
 
 /// Converts a position to a module svg
@@ -55,6 +126,7 @@ pub enum Shape {
 
 /// Different possible Shapes to represent modules in a [`crate::QRCode`]
 #[cfg(not(target_arch = "wasm32"))]
+#[allow(unpredictable_function_pointer_comparisons)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd)]
 pub enum Shape {
     /// Square Shape
@@ -187,6 +259,462 @@ impl Deref for Shape {
     }
 }
 
+/// A dark module being drawn by a [`ModuleRenderer`], with its neighbourhood
+pub struct ModuleContext<'a> {
+    /// Horizontal position of the module in the svg, margin included
+    pub x: usize,
+    /// Vertical position of the module in the svg, margin included
+    pub y: usize,
+    /// The module being drawn
+    pub module: Module,
+    /// Width of the [`crate::QRCode`], margin excluded
+    pub size: usize,
+    qr: &'a crate::QRCode,
+    row: usize,
+    column: usize,
+}
+
+impl<'a> ModuleContext<'a> {
+    #[cfg(feature = "svg")]
+    pub(crate) fn new(qr: &'a crate::QRCode, row: usize, column: usize, margin: usize) -> Self {
+        ModuleContext {
+            x: column + margin,
+            y: row + margin,
+            module: qr[row][column],
+            size: qr.size,
+            qr,
+            row,
+            column,
+        }
+    }
+
+    /// Position of the module in the [`crate::QRCode`] as `(y, x)`, margin excluded
+    #[must_use]
+    pub const fn position(&self) -> (usize, usize) {
+        (self.row, self.column)
+    }
+
+    /// Module `dy` rows and `dx` columns away, `None` outside of the [`crate::QRCode`]
+    #[must_use]
+    pub fn neighbour(&self, dy: isize, dx: isize) -> Option<Module> {
+        let row = self.row as isize + dy;
+        let column = self.column as isize + dx;
+        let size = self.size as isize;
+        if row < 0 || column < 0 || row >= size || column >= size {
+            return None;
+        }
+
+        Some(self.qr[row as usize][column as usize])
+    }
+
+    /// Whether the module `dy` rows and `dx` columns away is dark, modules
+    /// outside of the [`crate::QRCode`] are light
+    #[must_use]
+    pub fn is_dark(&self, dy: isize, dx: isize) -> bool {
+        self.neighbour(dy, dx).map_or(false, Module::value)
+    }
+}
+
+/// What a [`ModuleRenderer`] draws for a module: a svg path and an optional color
+pub struct ModuleOutput {
+    /// Svg path commands, in svg coordinates
+    pub path: String,
+    /// Color of this module, default is the layer color
+    pub color: Option<Color>,
+}
+
+impl ModuleOutput {
+    /// Path drawn with a specific color
+    pub fn colored<C: Into<Color>>(path: String, color: C) -> Self {
+        ModuleOutput {
+            path,
+            color: Some(color.into()),
+        }
+    }
+}
+
+impl From<String> for ModuleOutput {
+    fn from(path: String) -> Self {
+        ModuleOutput { path, color: None }
+    }
+}
+
+/// Custom module drawing which, unlike [`Shape::Command`], can hold state and
+/// see the neighbouring modules. It is called for each dark module.
+///
+/// Closures taking a [`ModuleContext`] and returning a `String` or a
+/// [`ModuleOutput`] are renderers.
+///
+/// # Example
+/// ```rust
+/// use fast_qr::convert::{ModuleContext, ModuleOutput};
+///
+/// // Captured state
+/// let palette = ["#ff0000", "#00ff00", "#0000ff"];
+/// let renderer = move |module: &ModuleContext| {
+///     let path = format!("M{},{}h1v1h-1", module.x, module.y);
+///     // Isolated modules are drawn in blue
+///     if !module.is_dark(0, -1) && !module.is_dark(0, 1) {
+///         ModuleOutput::colored(path, palette[2])
+///     } else {
+///         ModuleOutput::colored(path, palette[module.y % 2])
+///     }
+/// };
+/// ```
+pub trait ModuleRenderer {
+    /// Draws a dark module
+    fn render(&self, module: &ModuleContext) -> ModuleOutput;
+}
+
+impl<F, O> ModuleRenderer for F
+where
+    F: Fn(&ModuleContext) -> O,
+    O: Into<ModuleOutput>,
+{
+    fn render(&self, module: &ModuleContext) -> ModuleOutput {
+        self(module).into()
+    }
+}
+
+/// Shapes that connect neighbouring dark modules, add them with
+/// [`Builder::renderer`]
+///
+/// ```rust
+/// # #[cfg(feature = "svg")]
+/// # fn main() {
+/// use fast_qr::convert::{svg::SvgBuilder, Builder, ConnectedShape};
+/// use fast_qr::qr::QRBuilder;
+///
+/// let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+/// let _svg = SvgBuilder::default()
+///     .renderer(ConnectedShape::Liquid)
+///     .to_str(&qrcode);
+/// # }
+/// # #[cfg(not(feature = "svg"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectedShape {
+    /// Rounds the outer corners of connected dark regions
+    Rounded,
+    /// Rounds the outer corners and fills inner corners with concave fillets
+    Liquid,
+    /// Joins horizontal runs of dark modules into pills
+    HorizontalPills,
+    /// Joins vertical runs of dark modules into pills
+    VerticalPills,
+}
+
+impl ConnectedShape {
+    /// Draws the module clockwise from the middle of its top side, each corner
+    /// being rounded or not (top right, bottom right, bottom left, top left)
+    fn module(x: usize, y: usize, rounded: [bool; 4]) -> String {
+        const ROUNDED: [&str; 4] = [
+            "a.5,.5 0 0,1 .5,.5",
+            "a.5,.5 0 0,1 -.5,.5",
+            "a.5,.5 0 0,1 -.5,-.5",
+            "a.5,.5 0 0,1 .5,-.5",
+        ];
+        const SQUARE: [&str; 4] = ["h.5v.5", "v.5h-.5", "h-.5v-.5", "v-.5h.5"];
+
+        let mut out = format!("M{x}.5,{y}");
+        for (i, &rounded) in rounded.iter().enumerate() {
+            out.push_str(if rounded { ROUNDED[i] } else { SQUARE[i] });
+        }
+        out.push('z');
+        out
+    }
+
+    /// Concave fillets in the light modules diagonal to this one, where both
+    /// modules in between are dark
+    fn fillets(module: &ModuleContext) -> String {
+        let (x, y) = (module.x, module.y);
+        let (up, down) = (module.is_dark(-1, 0), module.is_dark(1, 0));
+        let (left, right) = (module.is_dark(0, -1), module.is_dark(0, 1));
+
+        let mut out = String::new();
+        if up && right && !module.is_dark(-1, 1) {
+            out.push_str(&format!(
+                "M{},{}.5v.5h.5a.5,.5 0 0,1 -.5,-.5z",
+                x + 1,
+                y - 1
+            ));
+        }
+        if down && right && !module.is_dark(1, 1) {
+            out.push_str(&format!(
+                "M{},{}.5v-.5h.5a.5,.5 0 0,0 -.5,.5z",
+                x + 1,
+                y + 1
+            ));
+        }
+        if down && left && !module.is_dark(1, -1) {
+            out.push_str(&format!("M{x},{}.5v-.5h-.5a.5,.5 0 0,1 .5,.5z", y + 1));
+        }
+        if up && left && !module.is_dark(-1, -1) {
+            out.push_str(&format!("M{x},{}.5v.5h-.5a.5,.5 0 0,0 .5,-.5z", y - 1));
+        }
+        out
+    }
+}
+
+impl ModuleRenderer for ConnectedShape {
+    fn render(&self, module: &ModuleContext) -> ModuleOutput {
+        let (up, down) = (module.is_dark(-1, 0), module.is_dark(1, 0));
+        let (left, right) = (module.is_dark(0, -1), module.is_dark(0, 1));
+
+        let rounded = match self {
+            Self::Rounded | Self::Liquid => {
+                [!up && !right, !down && !right, !down && !left, !up && !left]
+            }
+            Self::HorizontalPills => [!right, !right, !left, !left],
+            Self::VerticalPills => [!up, !down, !down, !up],
+        };
+
+        let mut path = Self::module(module.x, module.y, rounded);
+        if *self == Self::Liquid {
+            path.push_str(&Self::fillets(module));
+        }
+        path.into()
+    }
+}
+
+/// Converts a finder or alignment pattern part to svg, from its top left
+/// corner and its width
+/// # Example
+///
+/// For the square frame of a pattern, the svg is
+///
+/// ```rust
+/// fn square_frame(y: usize, x: usize, size: usize) -> String {
+///     let inner = size - 2;
+///     format!(
+///         "M{x},{y}h{size}v{size}h-{size}zM{},{}v{inner}h{inner}v-{inner}z",
+///         x + 1,
+///         y + 1
+///     )
+/// }
+/// ```
+pub type PatternFunction = fn(usize, usize, usize) -> String;
+
+/// Formats a number with at most `places` decimals, without trailing zeros
+fn decimals(value: f64, places: usize) -> String {
+    let value = format!("{:.*}", places, value);
+    let value = value.trim_end_matches('0').trim_end_matches('.');
+    match value {
+        "-0" => String::from("0"),
+        _ => value.to_string(),
+    }
+}
+
+/// Formats a coordinate with at most 2 decimals
+fn coord(value: f64) -> String {
+    decimals(value, 2)
+}
+
+/// Formats a number with at most 4 decimals
+#[cfg(any(feature = "pdf", feature = "eps", feature = "dxf", feature = "mesh"))]
+fn number(value: f64) -> String {
+    decimals(value, 4)
+}
+
+/// Encodes bytes using the standard base64 alphabet, with padding
+#[cfg(any(feature = "svg", feature = "raster"))]
+pub(crate) fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+/// Square of width `size` with corners of radius `r`, `clockwise` or not.
+/// Drawing a shape inside another one in the opposite direction makes a hole.
+fn rounded_square(x: f64, y: f64, size: f64, r: f64, clockwise: bool) -> String {
+    let side = coord(size - 2f64 * r);
+    let r_str = coord(r);
+    let sweep = u8::from(clockwise);
+    let arc =
+        |dx: f64, dy: f64| format!("a{r_str},{r_str} 0 0,{sweep} {},{}", coord(dx), coord(dy));
+
+    if clockwise {
+        format!(
+            "M{},{}h{side}{}v{side}{}h-{side}{}v-{side}{}z",
+            coord(x + r),
+            coord(y),
+            arc(r, r),
+            arc(-r, r),
+            arc(-r, -r),
+            arc(r, -r),
+        )
+    } else {
+        format!(
+            "M{},{}{}v{side}{}h{side}{}v-{side}{}h-{side}z",
+            coord(x + r),
+            coord(y),
+            arc(-r, r),
+            arc(r, r),
+            arc(r, -r),
+            arc(-r, -r),
+        )
+    }
+}
+
+/// Circle centered on `(cx, cy)`, `clockwise` or not
+fn circle(cx: f64, cy: f64, r: f64, clockwise: bool) -> String {
+    let sweep = u8::from(clockwise);
+    let r_str = coord(r);
+    format!(
+        "M{},{}a{r_str},{r_str} 0 1,{sweep} {},0a{r_str},{r_str} 0 1,{sweep} -{},0z",
+        coord(cx - r),
+        coord(cy),
+        coord(2f64 * r),
+        coord(2f64 * r),
+    )
+}
+
+/// Shapes for the outer frame of finder and alignment patterns ("eyes")
+#[derive(Debug, Clone, Copy)]
+pub enum EyeFrameShape {
+    /// Square frame
+    Square,
+    /// Square frame with rounded corners
+    RoundedSquare,
+    /// Circle frame
+    Circle,
+    /// Custom frame with a function, see [`PatternFunction`]
+    Command(PatternFunction),
+}
+
+impl EyeFrameShape {
+    fn square(y: usize, x: usize, size: usize) -> String {
+        let inner = size - 2;
+        format!(
+            "M{x},{y}h{size}v{size}h-{size}zM{},{}v{inner}h{inner}v-{inner}z",
+            x + 1,
+            y + 1
+        )
+    }
+
+    fn rounded_square(y: usize, x: usize, size: usize) -> String {
+        let (x, y, size) = (x as f64, y as f64, size as f64);
+        let r = size / 3.5;
+
+        let mut out = rounded_square(x, y, size, r, true);
+        out.push_str(&rounded_square(
+            x + 1f64,
+            y + 1f64,
+            size - 2f64,
+            r - 1f64,
+            false,
+        ));
+        out
+    }
+
+    fn circle(y: usize, x: usize, size: usize) -> String {
+        let r = size as f64 / 2f64;
+        let (cx, cy) = (x as f64 + r, y as f64 + r);
+
+        let mut out = circle(cx, cy, r, false);
+        out.push_str(&circle(cx, cy, r - 1f64, true));
+        out
+    }
+
+    const FUNCTIONS: [PatternFunction; 3] = [
+        EyeFrameShape::square,
+        EyeFrameShape::rounded_square,
+        EyeFrameShape::circle,
+    ];
+}
+
+impl Deref for EyeFrameShape {
+    type Target = PatternFunction;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Square => &Self::FUNCTIONS[0],
+            Self::RoundedSquare => &Self::FUNCTIONS[1],
+            Self::Circle => &Self::FUNCTIONS[2],
+            Self::Command(func) => func,
+        }
+    }
+}
+
+/// Shapes for the inner ball of finder and alignment patterns ("eyes")
+#[derive(Debug, Clone, Copy)]
+pub enum EyeBallShape {
+    /// Square ball
+    Square,
+    /// Square ball with rounded corners
+    RoundedSquare,
+    /// Circle ball
+    Circle,
+    /// Diamond ball
+    Diamond,
+    /// Custom ball with a function, see [`PatternFunction`]
+    Command(PatternFunction),
+}
+
+impl EyeBallShape {
+    fn square(y: usize, x: usize, size: usize) -> String {
+        format!("M{x},{y}h{size}v{size}h-{size}z")
+    }
+
+    fn rounded_square(y: usize, x: usize, size: usize) -> String {
+        let size = size as f64;
+        rounded_square(x as f64, y as f64, size, size / 4f64, true)
+    }
+
+    fn circle(y: usize, x: usize, size: usize) -> String {
+        let r = size as f64 / 2f64;
+        circle(x as f64 + r, y as f64 + r, r, true)
+    }
+
+    fn diamond(y: usize, x: usize, size: usize) -> String {
+        let half = coord(size as f64 / 2f64);
+        format!(
+            "M{},{y}l{half},{half}l-{half},{half}l-{half},-{half}z",
+            coord(x as f64 + size as f64 / 2f64)
+        )
+    }
+
+    const FUNCTIONS: [PatternFunction; 4] = [
+        EyeBallShape::square,
+        EyeBallShape::rounded_square,
+        EyeBallShape::circle,
+        EyeBallShape::diamond,
+    ];
+}
+
+impl Deref for EyeBallShape {
+    type Target = PatternFunction;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Square => &Self::FUNCTIONS[0],
+            Self::RoundedSquare => &Self::FUNCTIONS[1],
+            Self::Circle => &Self::FUNCTIONS[2],
+            Self::Diamond => &Self::FUNCTIONS[3],
+            Self::Command(func) => func,
+        }
+    }
+}
+
 /// Different possible image background shapes
 #[cfg_attr(target_arch = "wasm32", repr(C))]
 #[cfg_attr(all(target_arch = "wasm32", feature = "wasm-bindgen"), wasm_bindgen)]
@@ -262,6 +790,7 @@ pub fn rgba2hex(color: [u8; 4]) -> String {
 }
 
 /// Allows to take String, string slices, arrays or slices of u8 (3 or 4) to create a [Color]
+#[derive(Debug, Clone)]
 pub struct Color(pub String);
 
 impl Color {
@@ -270,6 +799,59 @@ impl Color {
     pub fn to_str(&self) -> &str {
         &self.0
     }
+
+    /// Red, green, blue and alpha of a hexadecimal, `rgb()`, `rgba()` or
+    /// CSS named color
+    #[cfg(any(feature = "pdf", feature = "eps"))]
+    pub(crate) fn rgba(&self) -> Option<[u8; 4]> {
+        let color = self.0.trim().to_ascii_lowercase();
+        if let Some(hex) = color.strip_prefix('#') {
+            let digit = |i: usize| u8::from_str_radix(hex.get(i..=i)?, 16).ok();
+            let pair = |i: usize| Some(digit(i)? * 16 + digit(i + 1)?);
+            return match hex.len() {
+                3 | 4 => {
+                    let alpha = if hex.len() == 4 { digit(3)? * 17 } else { 255 };
+                    Some([digit(0)? * 17, digit(1)? * 17, digit(2)? * 17, alpha])
+                }
+                6 | 8 => {
+                    let alpha = if hex.len() == 8 { pair(6)? } else { 255 };
+                    Some([pair(0)?, pair(2)?, pair(4)?, alpha])
+                }
+                _ => None,
+            };
+        }
+
+        if let Some(arguments) = color
+            .strip_prefix("rgba(")
+            .or_else(|| color.strip_prefix("rgb("))
+        {
+            let values: Vec<f64> = arguments
+                .trim_end_matches(')')
+                .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+                .filter(|value| !value.is_empty())
+                .map(|value| match value.strip_suffix('%') {
+                    Some(percent) => percent.parse::<f64>().map(|p| p * 2.55f64),
+                    None => value.parse::<f64>(),
+                })
+                .collect::<Result<_, _>>()
+                .ok()?;
+            let channel = |value: f64| value.round().clamp(0f64, 255f64) as u8;
+            return match values[..] {
+                [r, g, b] => Some([channel(r), channel(g), channel(b), 255]),
+                [r, g, b, a] => {
+                    // Alpha is a fraction, or a percentage already scaled
+                    let a = if a <= 1f64 { a * 255f64 } else { a };
+                    Some([channel(r), channel(g), channel(b), channel(a)])
+                }
+                _ => None,
+            };
+        }
+
+        NAMED_COLORS
+            .binary_search_by(|(name, _)| (*name).cmp(color.as_str()))
+            .ok()
+            .map(|i| NAMED_COLORS[i].1)
+    }
 }
 
 impl From<String> for Color {
@@ -314,22 +896,237 @@ impl From<Vec<u8>> for Color {
     }
 }
 
-/// Trait for `SvgBuilder` and `ImageBuilder`
+/// Fill of the modules or the background: a plain [Color], a gradient or a pattern.
+///
+/// Anything that converts to a [Color] also converts to a [Paint].
+#[derive(Debug, Clone)]
+pub enum Paint {
+    /// Plain color
+    Color(Color),
+    /// Linear gradient, see [`LinearGradient`]
+    LinearGradient(LinearGradient),
+    /// Radial gradient, see [`RadialGradient`]
+    RadialGradient(RadialGradient),
+    /// Repeated svg tile, see [`Pattern`]
+    Pattern(Pattern),
+}
+
+impl<C: Into<Color>> From<C> for Paint {
+    fn from(color: C) -> Self {
+        Paint::Color(color.into())
+    }
+}
+
+impl From<LinearGradient> for Paint {
+    fn from(gradient: LinearGradient) -> Self {
+        Paint::LinearGradient(gradient)
+    }
+}
+
+impl From<RadialGradient> for Paint {
+    fn from(gradient: RadialGradient) -> Self {
+        Paint::RadialGradient(gradient)
+    }
+}
+
+impl From<Pattern> for Paint {
+    fn from(pattern: Pattern) -> Self {
+        Paint::Pattern(pattern)
+    }
+}
+
+impl Paint {
+    /// Returns the `<defs>` element for this paint with the given `id`,
+    /// `size` is the width of the svg. Plain colors need no definition.
+    #[cfg(feature = "svg")]
+    pub(crate) fn definition(&self, id: &str, size: usize) -> Option<String> {
+        let size = size as f64;
+        let stops = |stops: &[(f64, Color)]| {
+            stops
+                .iter()
+                .map(|(offset, color)| {
+                    format!(
+                        r#"<stop offset="{}" stop-color="{}"/>"#,
+                        coord(*offset),
+                        color.to_str()
+                    )
+                })
+                .collect::<String>()
+        };
+
+        match self {
+            Paint::Color(_) => None,
+            Paint::LinearGradient(gradient) => {
+                // Spans the whole svg, whatever the angle
+                let (sin, cos) = gradient.angle.to_radians().sin_cos();
+                let half = size / 2f64 * (sin.abs() + cos.abs());
+                let center = size / 2f64;
+                Some(format!(
+                    r#"<linearGradient id="{id}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}">{}</linearGradient>"#,
+                    coord(center - cos * half),
+                    coord(center - sin * half),
+                    coord(center + cos * half),
+                    coord(center + sin * half),
+                    stops(&gradient.stops)
+                ))
+            }
+            Paint::RadialGradient(gradient) => Some(format!(
+                r#"<radialGradient id="{id}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" r="{}">{}</radialGradient>"#,
+                coord(gradient.cx * size),
+                coord(gradient.cy * size),
+                coord(gradient.r * size),
+                stops(&gradient.stops)
+            )),
+            Paint::Pattern(pattern) => Some(format!(
+                r#"<pattern id="{id}" patternUnits="userSpaceOnUse" width="{}" height="{}">{}</pattern>"#,
+                coord(pattern.width),
+                coord(pattern.height),
+                pattern.content
+            )),
+        }
+    }
+}
+
+/// Linear gradient going through the whole svg
+///
+/// ```rust
+/// use fast_qr::convert::LinearGradient;
+///
+/// // Top left to bottom right, from red to blue
+/// let gradient = LinearGradient::new(45f64)
+///     .stop(0f64, "#ff0000")
+///     .stop(1f64, "#0000ff");
+/// ```
+#[derive(Debug, Clone)]
+pub struct LinearGradient {
+    /// Angle in degrees, 0 goes from left to right and 90 from top to bottom
+    pub angle: f64,
+    /// Offsets, between 0 and 1, and their colors
+    pub stops: Vec<(f64, Color)>,
+}
+
+impl LinearGradient {
+    /// Creates a gradient without stops, going along `angle` (in degrees)
+    #[must_use]
+    pub fn new(angle: f64) -> Self {
+        LinearGradient {
+            angle,
+            stops: Vec::new(),
+        }
+    }
+
+    /// Adds a color at `offset`, between 0 and 1
+    #[must_use]
+    pub fn stop<C: Into<Color>>(mut self, offset: f64, color: C) -> Self {
+        self.stops.push((offset, color.into()));
+        self
+    }
+}
+
+/// Radial gradient, coordinates are fractions of the svg width
+///
+/// ```rust
+/// use fast_qr::convert::RadialGradient;
+///
+/// // Centered, from red to blue
+/// let gradient = RadialGradient::new(0.5f64, 0.5f64, 0.5f64)
+///     .stop(0f64, "#ff0000")
+///     .stop(1f64, "#0000ff");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RadialGradient {
+    /// Horizontal position of the center, 0 is the left and 1 the right
+    pub cx: f64,
+    /// Vertical position of the center, 0 is the top and 1 the bottom
+    pub cy: f64,
+    /// Radius, 1 is the svg width
+    pub r: f64,
+    /// Offsets, between 0 and 1, and their colors
+    pub stops: Vec<(f64, Color)>,
+}
+
+impl RadialGradient {
+    /// Creates a gradient without stops, centered on `(cx, cy)`
+    #[must_use]
+    pub fn new(cx: f64, cy: f64, r: f64) -> Self {
+        RadialGradient {
+            cx,
+            cy,
+            r,
+            stops: Vec::new(),
+        }
+    }
+
+    /// Adds a color at `offset`, between 0 and 1
+    #[must_use]
+    pub fn stop<C: Into<Color>>(mut self, offset: f64, color: C) -> Self {
+        self.stops.push((offset, color.into()));
+        self
+    }
+}
+
+/// Svg content repeated as a tile, sizes are in modules
+///
+/// ```rust
+/// use fast_qr::convert::Pattern;
+///
+/// // Black & red stripes
+/// let pattern = Pattern::new(
+///     1f64,
+///     1f64,
+///     r##"<rect width="1" height="0.5" fill="#000"/><rect y="0.5" width="1" height="0.5" fill="#f00"/>"##,
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    /// Tile width
+    pub width: f64,
+    /// Tile height
+    pub height: f64,
+    /// Svg elements drawn on each tile
+    pub content: String,
+}
+
+impl Pattern {
+    /// Creates a pattern repeating `content` every `width` x `height` modules
+    #[must_use]
+    pub fn new<S: Into<String>>(width: f64, height: f64, content: S) -> Self {
+        Pattern {
+            width,
+            height,
+            content: content.into(),
+        }
+    }
+}
+
+/// Trait for the builders sharing the svg styling: `SvgBuilder`, `ImageBuilder`, `PdfBuilder`
+/// and `EpsBuilder`
+///
+/// `HtmlBuilder` is standalone: table cells cannot draw shapes, images or frames, so it only
+/// has its own margin, colors, module size and layout.
 pub trait Builder {
     /// Updates margin (default: 4)
     fn margin(&mut self, margin: usize) -> &mut Self;
-    /// Updates module color (default: #000000)
-    fn module_color<C: Into<Color>>(&mut self, module_color: C) -> &mut Self;
-    /// Updates background color (default: #FFFFFF)
-    fn background_color<C: Into<Color>>(&mut self, background_color: C) -> &mut Self;
+    /// Updates module color, gradient or pattern (default: #000000)
+    fn module_color<P: Into<Paint>>(&mut self, module_color: P) -> &mut Self;
+    /// Updates background color, gradient or pattern (default: #FFFFFF)
+    fn background_color<P: Into<Paint>>(&mut self, background_color: P) -> &mut Self;
     /// Adds a shape to the shapes list
     fn shape(&mut self, shape: Shape) -> &mut Self;
     /// Add a shape to the shapes list with a specific color
-    fn shape_color<C: Into<Color>>(&mut self, shape: Shape, color: C) -> &mut Self;
+    fn shape_color<P: Into<Paint>>(&mut self, shape: Shape, color: P) -> &mut Self;
+    /// Adds a custom renderer, drawn with the module color unless it returns
+    /// its own, see [`ModuleRenderer`]. Renderers are `Send + Sync` so builders can be
+    /// shared between threads.
+    fn renderer<R: ModuleRenderer + Send + Sync + 'static>(&mut self, renderer: R) -> &mut Self;
 
     // Manages the image part
 
     /// Provides the image path or an base64 encoded image
+    ///
+    /// The string is written as is in svg. PDF and EPS only embed PNG and JPEG
+    /// `data:` URIs, other images make their output fail, and never read the
+    /// filesystem: use their `image_file` to embed a local file.
     fn image(&mut self, image: String) -> &mut Self;
     /// Updates the image background color (default: #FFFFFF)
     fn image_background_color<C: Into<Color>>(&mut self, image_background_color: C) -> &mut Self;
@@ -341,4 +1138,27 @@ pub trait Builder {
     fn image_size(&mut self, image_size: f64, gap: f64) -> &mut Self;
     /// Updates the image position, anchor is the center of the image. Default is the center of the [`crate::QRCode`]
     fn image_position(&mut self, x: f64, y: f64) -> &mut Self;
+    /// Reserves an area for the image, in modules or as a percentage of the [`crate::QRCode`]
+    /// (default: sized from the version). Ignored when the [`crate::QRCode`] was built with
+    /// [`crate::QRBuilder::logo`], which already reserves one.
+    fn image_area(&mut self, size: LogoSize) -> &mut Self;
+    /// Removes the data modules under the image background instead of painting over them,
+    /// function patterns are kept (default: false)
+    fn clear_under_image(&mut self, clear: bool) -> &mut Self;
+
+    // Manages finder and alignment patterns
+
+    /// Draws each finder pattern as a whole, with a frame and a ball shape
+    /// (default: drawn module by module like other modules)
+    fn finder_shape(&mut self, frame: EyeFrameShape, ball: EyeBallShape) -> &mut Self;
+    /// Updates finder patterns frame & ball colors (default: module color)
+    fn finder_color<P: Into<Paint>>(&mut self, frame: P, ball: P) -> &mut Self;
+    /// Draws each alignment pattern as a whole, with a frame and a ball shape
+    /// (default: drawn module by module like other modules)
+    fn alignment_shape(&mut self, frame: EyeFrameShape, ball: EyeBallShape) -> &mut Self;
+    /// Updates alignment patterns frame & ball colors (default: module color)
+    fn alignment_color<P: Into<Paint>>(&mut self, frame: P, ball: P) -> &mut Self;
+
+    /// Draws a frame, with an optional caption, around the code (default: none)
+    fn frame(&mut self, frame: frame::Frame) -> &mut Self;
 }
@@ -0,0 +1,394 @@
+//! Parses svg path data into absolute segments, for the backends writing
+//! other vector formats. Arcs and quadratic curves become cubic curves.
+//!
+//! Also writes the operands PDF & PostScript have in common.
+
+use core::f64::consts::FRAC_PI_2;
+
+use super::{coord, number, Color, Paint};
+
+/// Absolute path segment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Segment {
+    /// Starts a subpath
+    Move(f64, f64),
+    /// Straight line
+    Line(f64, f64),
+    /// Cubic Bézier curve: two control points, then the end
+    Cubic(f64, f64, f64, f64, f64, f64),
+    /// Closes the subpath
+    Close,
+}
+
+/// Reads the numbers, flags and commands of path data
+struct Tokens<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn skip_separators(&mut self) {
+        while self
+            .bytes
+            .get(self.position)
+            .map_or(false, |c| c.is_ascii_whitespace() || *c == b',')
+        {
+            self.position += 1;
+        }
+    }
+
+    /// Command letter, if it is the next token
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        let c = *self.bytes.get(self.position)?;
+        if c.is_ascii_alphabetic() {
+            self.position += 1;
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    fn is_empty(&mut self) -> bool {
+        self.skip_separators();
+        self.position >= self.bytes.len()
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        self.skip_separators();
+        let start = self.position;
+        let digits = |tokens: &mut Self| {
+            while tokens
+                .bytes
+                .get(tokens.position)
+                .map_or(false, u8::is_ascii_digit)
+            {
+                tokens.position += 1;
+            }
+        };
+
+        if matches!(self.bytes.get(self.position), Some(b'+' | b'-')) {
+            self.position += 1;
+        }
+        digits(self);
+        // A second dot starts the next number, like in `.5.5`
+        if self.bytes.get(self.position) == Some(&b'.') {
+            self.position += 1;
+            digits(self);
+        }
+        if matches!(self.bytes.get(self.position), Some(b'e' | b'E')) {
+            self.position += 1;
+            if matches!(self.bytes.get(self.position), Some(b'+' | b'-')) {
+                self.position += 1;
+            }
+            digits(self);
+        }
+
+        core::str::from_utf8(&self.bytes[start..self.position])
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    /// Arc flags are a single digit, they may not be separated
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.bytes.get(self.position)? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.position += 1;
+        Some(flag)
+    }
+}
+
+/// Parses svg path data, malformed data stops the path where the error is
+pub(crate) fn parse(data: &str) -> Vec<Segment> {
+    let mut tokens = Tokens {
+        bytes: data.as_bytes(),
+        position: 0,
+    };
+    let mut segments = Vec::new();
+
+    let mut current = (0f64, 0f64);
+    let mut start = (0f64, 0f64);
+    // Control point reflected by the smooth curves
+    let mut last_cubic: Option<(f64, f64)> = None;
+    let mut last_quadratic: Option<(f64, f64)> = None;
+    let mut command = 0u8;
+
+    while !tokens.is_empty() {
+        if let Some(c) = tokens.command() {
+            command = c;
+        } else if command == 0 {
+            break;
+        }
+
+        let relative = command.is_ascii_lowercase();
+        let offset = if relative { current } else { (0f64, 0f64) };
+        let point = |tokens: &mut Tokens| -> Option<(f64, f64)> {
+            Some((tokens.number()? + offset.0, tokens.number()? + offset.1))
+        };
+
+        let (mut cubic, mut quadratic) = (None, None);
+        let parsed = match command.to_ascii_uppercase() {
+            b'M' => point(&mut tokens).map(|p| {
+                segments.push(Segment::Move(p.0, p.1));
+                start = p;
+                current = p;
+                // Following pairs are lines
+                command = if relative { b'l' } else { b'L' };
+            }),
+            b'L' => point(&mut tokens).map(|p| {
+                segments.push(Segment::Line(p.0, p.1));
+                current = p;
+            }),
+            b'H' => tokens.number().map(|x| {
+                current.0 = x + offset.0;
+                segments.push(Segment::Line(current.0, current.1));
+            }),
+            b'V' => tokens.number().map(|y| {
+                current.1 = y + offset.1;
+                segments.push(Segment::Line(current.0, current.1));
+            }),
+            b'C' | b'S' => {
+                let first = if command.eq_ignore_ascii_case(&b'C') {
+                    point(&mut tokens)
+                } else {
+                    Some(reflect(current, last_cubic))
+                };
+                first
+                    .and_then(|c1| Some((c1, point(&mut tokens)?, point(&mut tokens)?)))
+                    .map(|(c1, c2, p)| {
+                        segments.push(Segment::Cubic(c1.0, c1.1, c2.0, c2.1, p.0, p.1));
+                        cubic = Some(c2);
+                        current = p;
+                    })
+            }
+            b'Q' | b'T' => {
+                let control = if command.eq_ignore_ascii_case(&b'Q') {
+                    point(&mut tokens)
+                } else {
+                    Some(reflect(current, last_quadratic))
+                };
+                control
+                    .and_then(|c| Some((c, point(&mut tokens)?)))
+                    .map(|(c, p)| {
+                        let third = |from: f64, to: f64| from + (to - from) * 2f64 / 3f64;
+                        segments.push(Segment::Cubic(
+                            third(current.0, c.0),
+                            third(current.1, c.1),
+                            third(p.0, c.0),
+                            third(p.1, c.1),
+                            p.0,
+                            p.1,
+                        ));
+                        quadratic = Some(c);
+                        current = p;
+                    })
+            }
+            b'A' => (|| {
+                let (rx, ry, angle) = (tokens.number()?, tokens.number()?, tokens.number()?);
+                let (large, sweep) = (tokens.flag()?, tokens.flag()?);
+                let p = point(&mut tokens)?;
+                arc(current, (rx, ry), angle, large, sweep, p, &mut segments);
+                current = p;
+                Some(())
+            })(),
+            b'Z' => {
+                segments.push(Segment::Close);
+                current = start;
+                Some(())
+            }
+            _ => None,
+        };
+
+        if parsed.is_none() {
+            break;
+        }
+        last_cubic = cubic;
+        last_quadratic = quadratic;
+    }
+
+    segments
+}
+
+/// Reflection of the previous control point around the current point
+fn reflect(current: (f64, f64), control: Option<(f64, f64)>) -> (f64, f64) {
+    control.map_or(current, |(x, y)| {
+        (2f64 * current.0 - x, 2f64 * current.1 - y)
+    })
+}
+
+/// Converts an elliptical arc to cubic curves of at most 90°
+fn arc(
+    from: (f64, f64),
+    radii: (f64, f64),
+    angle: f64,
+    large: bool,
+    sweep: bool,
+    to: (f64, f64),
+    segments: &mut Vec<Segment>,
+) {
+    if from == to {
+        return;
+    }
+    let (mut rx, mut ry) = (radii.0.abs(), radii.1.abs());
+    if rx == 0f64 || ry == 0f64 {
+        segments.push(Segment::Line(to.0, to.1));
+        return;
+    }
+
+    // Center parameterization, from the svg specification
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (dx, dy) = ((from.0 - to.0) / 2f64, (from.1 - to.1) / 2f64);
+    let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+
+    // Radii too small to join the points are scaled up
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1f64 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coefficient = (numerator / denominator).max(0f64).sqrt();
+    if large == sweep {
+        coefficient = -coefficient;
+    }
+    let (cx1, cy1) = (coefficient * rx * y1 / ry, -coefficient * ry * x1 / rx);
+    let center = (
+        cos * cx1 - sin * cy1 + (from.0 + to.0) / 2f64,
+        sin * cx1 + cos * cy1 + (from.1 + to.1) / 2f64,
+    );
+
+    let start = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+    let end = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx);
+    let mut delta = end - start;
+    if sweep && delta < 0f64 {
+        delta += 4f64 * FRAC_PI_2;
+    } else if !sweep && delta > 0f64 {
+        delta -= 4f64 * FRAC_PI_2;
+    }
+
+    let count = (delta.abs() / FRAC_PI_2 - 1e-9).ceil().max(1f64) as usize;
+    let step = delta / count as f64;
+    // Length of the tangents of a cubic approximating an arc of `step`
+    let k = 4f64 / 3f64 * (step / 4f64).tan();
+    let map = |u: f64, v: f64| {
+        (
+            center.0 + cos * rx * u - sin * ry * v,
+            center.1 + sin * rx * u + cos * ry * v,
+        )
+    };
+
+    for i in 0..count {
+        let (t1, t2) = (start + step * i as f64, start + step * (i + 1) as f64);
+        let (s1, c1) = t1.sin_cos();
+        let (s2, c2) = t2.sin_cos();
+        let p1 = map(c1 - k * s1, s1 + k * c1);
+        let p2 = map(c2 + k * s2, s2 - k * c2);
+        let p = if i + 1 == count { to } else { map(c2, s2) };
+        segments.push(Segment::Cubic(p1.0, p1.1, p2.0, p2.1, p.0, p.1));
+    }
+}
+
+/// Path construction operators `m`, `l`, `c` and `h` of svg path data
+pub(crate) fn operators(data: &str) -> String {
+    let mut out = String::with_capacity(data.len() * 2);
+    for segment in parse(data) {
+        match segment {
+            Segment::Move(x, y) => out.push_str(&format!("{} {} m ", coord(x), coord(y))),
+            Segment::Line(x, y) => out.push_str(&format!("{} {} l ", coord(x), coord(y))),
+            Segment::Cubic(x1, y1, x2, y2, x, y) => out.push_str(&format!(
+                "{} {} {} {} {} {} c ",
+                coord(x1),
+                coord(y1),
+                coord(x2),
+                coord(y2),
+                coord(x),
+                coord(y)
+            )),
+            Segment::Close => out.push_str("h "),
+        }
+    }
+    out
+}
+
+/// Shading dictionary of a gradient spanning a code `size` modules wide,
+/// `components` writes a color in `color_space`. `None` for other paints.
+pub(crate) fn shading(
+    paint: &Paint,
+    size: f64,
+    color_space: &str,
+    components: &dyn Fn(&Color) -> String,
+) -> Option<String> {
+    match paint {
+        Paint::LinearGradient(gradient) => {
+            // Spans the whole code, like in svg
+            let (sin, cos) = gradient.angle.to_radians().sin_cos();
+            let half = size / 2f64 * (sin.abs() + cos.abs());
+            let center = size / 2f64;
+            Some(format!(
+                "<< /ShadingType 2 /ColorSpace /{color_space} /Coords [{} {} {} {}] /Function {} /Extend [true true] >>",
+                number(center - cos * half),
+                number(center - sin * half),
+                number(center + cos * half),
+                number(center + sin * half),
+                function(&gradient.stops, components)
+            ))
+        }
+        Paint::RadialGradient(gradient) => {
+            let (cx, cy) = (number(gradient.cx * size), number(gradient.cy * size));
+            Some(format!(
+                "<< /ShadingType 3 /ColorSpace /{color_space} /Coords [{cx} {cy} 0 {cx} {cy} {}] /Function {} /Extend [true true] >>",
+                number(gradient.r * size),
+                function(&gradient.stops, components)
+            ))
+        }
+        Paint::Color(_) | Paint::Pattern(_) => None,
+    }
+}
+
+/// Function interpolating the colors of gradient stops, in order
+fn function(stops: &[(f64, Color)], components: &dyn Fn(&Color) -> String) -> String {
+    // Like in svg, offsets are clamped and never go back
+    let mut points: Vec<(f64, String)> = Vec::new();
+    for (offset, color) in stops {
+        let previous = points.last().map_or(0f64, |(offset, _)| *offset);
+        points.push((offset.clamp(0f64, 1f64).max(previous), components(color)));
+    }
+    if points.is_empty() {
+        points.push((0f64, components(&Color::from("#000000"))));
+    }
+    if points[0].0 > 0f64 {
+        points.insert(0, (0f64, points[0].1.clone()));
+    }
+    if points[points.len() - 1].0 < 1f64 {
+        points.push((1f64, points[points.len() - 1].1.clone()));
+    }
+
+    let interpolation = |from: &str, to: &str| {
+        format!("<< /FunctionType 2 /Domain [0 1] /C0 [{from}] /C1 [{to}] /N 1 >>")
+    };
+    if points.len() == 2 {
+        return interpolation(&points[0].1, &points[1].1);
+    }
+
+    let functions: Vec<String> = points
+        .windows(2)
+        .map(|pair| interpolation(&pair[0].1, &pair[1].1))
+        .collect();
+    let bounds: Vec<String> = points[1..points.len() - 1]
+        .iter()
+        .map(|(offset, _)| number(*offset))
+        .collect();
+    format!(
+        "<< /FunctionType 3 /Domain [0 1] /Functions [{}] /Bounds [{}] /Encode [{}] >>",
+        functions.join(" "),
+        bounds.join(" "),
+        vec!["0 1"; functions.len()].join(" ")
+    )
+}
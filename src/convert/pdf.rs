@@ -0,0 +1,598 @@
+//! Converts [`QRCode`] to a vector PDF
+//!
+//! ```rust
+//! use fast_qr::convert::pdf::{PageSize, PdfBuilder};
+//! use fast_qr::convert::{Builder, Shape};
+//! use fast_qr::qr::QRBuilder;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .build()
+//!     .unwrap();
+//!
+//! // Modules of exactly 0.5 mm, centered on an A4 page
+//! let _pdf = PdfBuilder::default()
+//!     .shape(Shape::RoundedSquare)
+//!     .page_size(PageSize::A4)
+//!     .module_size(0.5f64)
+//!     .to_bytes(&qrcode);
+//! ```
+
+use crate::qr::LogoSize;
+use crate::QRCode;
+
+use super::embed::{self, MimeType};
+use super::frame::Frame;
+use super::path;
+use super::svg::{Layer, PathMode, SvgBuilder, SvgError};
+use super::{
+    coord, number, png, Builder, Color, EyeBallShape, EyeFrameShape, ImageBackgroundShape,
+    ModuleRenderer, Paint, Shape,
+};
+
+/// Points per millimeter
+const POINTS: f64 = 72f64 / 25.4f64;
+
+/// Different possible page sizes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PageSize {
+    /// 210 x 297 mm
+    A4,
+    /// 148 x 210 mm
+    A5,
+    /// 8.5 x 11 inches
+    Letter,
+    /// Width & height, in millimeters
+    Custom(f64, f64),
+}
+
+impl PageSize {
+    /// Width & height, in millimeters
+    #[must_use]
+    pub const fn millimeters(self) -> (f64, f64) {
+        match self {
+            PageSize::A4 => (210f64, 297f64),
+            PageSize::A5 => (148f64, 210f64),
+            PageSize::Letter => (215.9f64, 279.4f64),
+            PageSize::Custom(width, height) => (width, height),
+        }
+    }
+}
+
+/// Builder for PDF, contains an [`SvgBuilder`] and adds page & module sizes.
+///
+/// Everything is drawn as vector paths, square modules are merged with
+/// [`PathMode::Outline`] by default. PNG and JPEG logos are embedded, from a
+/// base64 `data:` URI or a local file given to [`PdfBuilder::image_file`].
+///
+/// [`Paint::Pattern`] has no equivalent: patterns are filled in black and
+/// gradients ignore the alpha of their stops.
+pub struct PdfBuilder {
+    /// Page size, default is the size of the code
+    page_size: Option<PageSize>,
+    /// Width of a module in millimeters, default is 1, or as large as the
+    /// page allows
+    module_size: Option<f64>,
+    svg_builder: SvgBuilder,
+}
+
+/// Creates a PdfBuilder instance, which contains an [`SvgBuilder`]
+impl Default for PdfBuilder {
+    fn default() -> Self {
+        let mut svg_builder = SvgBuilder::default();
+        svg_builder.path_mode(PathMode::Outline);
+
+        PdfBuilder {
+            page_size: None,
+            module_size: None,
+            svg_builder,
+        }
+    }
+}
+
+impl Builder for PdfBuilder {
+    fn margin(&mut self, margin: usize) -> &mut Self {
+        self.svg_builder.margin(margin);
+        self
+    }
+
+    fn module_color<P: Into<Paint>>(&mut self, module_color: P) -> &mut Self {
+        self.svg_builder.module_color(module_color);
+        self
+    }
+
+    fn background_color<P: Into<Paint>>(&mut self, background_color: P) -> &mut Self {
+        self.svg_builder.background_color(background_color);
+        self
+    }
+
+    fn shape(&mut self, shape: Shape) -> &mut Self {
+        self.svg_builder.shape(shape);
+        self
+    }
+
+    fn renderer<R: ModuleRenderer + Send + Sync + 'static>(&mut self, renderer: R) -> &mut Self {
+        self.svg_builder.renderer(renderer);
+        self
+    }
+
+    fn image(&mut self, image: String) -> &mut Self {
+        self.svg_builder.image(image);
+        self
+    }
+
+    fn image_background_color<C: Into<Color>>(&mut self, image_background_color: C) -> &mut Self {
+        self.svg_builder
+            .image_background_color(image_background_color);
+        self
+    }
+
+    fn image_background_shape(
+        &mut self,
+        image_background_shape: ImageBackgroundShape,
+    ) -> &mut Self {
+        self.svg_builder
+            .image_background_shape(image_background_shape);
+        self
+    }
+
+    fn image_size(&mut self, image_size: f64, gap: f64) -> &mut Self {
+        self.svg_builder.image_size(image_size, gap);
+        self
+    }
+
+    fn image_position(&mut self, x: f64, y: f64) -> &mut Self {
+        self.svg_builder.image_position(x, y);
+        self
+    }
+
+    fn image_area(&mut self, size: LogoSize) -> &mut Self {
+        self.svg_builder.image_area(size);
+        self
+    }
+
+    fn clear_under_image(&mut self, clear: bool) -> &mut Self {
+        self.svg_builder.clear_under_image(clear);
+        self
+    }
+
+    fn frame(&mut self, frame: Frame) -> &mut Self {
+        self.svg_builder.frame(frame);
+        self
+    }
+
+    fn finder_shape(&mut self, frame: EyeFrameShape, ball: EyeBallShape) -> &mut Self {
+        self.svg_builder.finder_shape(frame, ball);
+        self
+    }
+
+    fn finder_color<P: Into<Paint>>(&mut self, frame: P, ball: P) -> &mut Self {
+        self.svg_builder.finder_color(frame, ball);
+        self
+    }
+
+    fn alignment_shape(&mut self, frame: EyeFrameShape, ball: EyeBallShape) -> &mut Self {
+        self.svg_builder.alignment_shape(frame, ball);
+        self
+    }
+
+    fn alignment_color<P: Into<Paint>>(&mut self, frame: P, ball: P) -> &mut Self {
+        self.svg_builder.alignment_color(frame, ball);
+        self
+    }
+
+    fn shape_color<P: Into<Paint>>(&mut self, shape: Shape, color: P) -> &mut Self {
+        self.svg_builder.shape_color(shape, color);
+        self
+    }
+}
+
+/// Graphics states & patterns used by the page
+struct Resources {
+    /// Module space to page space, patterns ignore the current transformation
+    matrix: String,
+    /// Width of the code, margin included, in modules
+    size: f64,
+    /// Alpha of each `/GS{n}` graphics state
+    alphas: Vec<u8>,
+    /// Dictionary of each `/P{n}` pattern
+    patterns: Vec<String>,
+}
+
+impl Resources {
+    /// Operators selecting `paint` to fill or to stroke, `None` if it is
+    /// transparent
+    fn paint(&mut self, paint: &Paint, stroke: bool) -> Option<String> {
+        let (color_space, color) = if stroke { ("CS", "SCN") } else { ("cs", "scn") };
+
+        let shading = match paint {
+            Paint::Color(color) => return self.color(color, stroke),
+            Paint::Pattern(_) => return self.color(&Color::from("#000000"), stroke),
+            _ => path::shading(paint, self.size, "DeviceRGB", &rgb)?,
+        };
+
+        let pattern = format!(
+            "<< /PatternType 2 /Shading {shading} /Matrix [{}] >>",
+            self.matrix
+        );
+        let index = match self.patterns.iter().position(|p| *p == pattern) {
+            Some(index) => index,
+            None => {
+                self.patterns.push(pattern);
+                self.patterns.len() - 1
+            }
+        };
+
+        Some(format!("/Pattern {color_space} /P{index} {color} "))
+    }
+
+    fn color(&mut self, color: &Color, stroke: bool) -> Option<String> {
+        let a = color.rgba().map_or(255, |[_, _, _, a]| a);
+        if a == 0 {
+            return None;
+        }
+
+        let operator = if stroke { "RG" } else { "rg" };
+        let mut out = format!("{} {operator} ", rgb(color));
+        if a < 255 {
+            let index = match self.alphas.iter().position(|&alpha| alpha == a) {
+                Some(index) => index,
+                None => {
+                    self.alphas.push(a);
+                    self.alphas.len() - 1
+                }
+            };
+            out.push_str(&format!("/GS{index} gs "));
+        }
+
+        Some(out)
+    }
+
+    fn to_str(&self, image: Option<usize>) -> String {
+        let mut out = String::from("<<");
+        if !self.alphas.is_empty() {
+            out.push_str(" /ExtGState <<");
+            for (i, &alpha) in self.alphas.iter().enumerate() {
+                let alpha = number(f64::from(alpha) / 255f64);
+                out.push_str(&format!(" /GS{i} << /ca {alpha} /CA {alpha} >>"));
+            }
+            out.push_str(" >>");
+        }
+        if !self.patterns.is_empty() {
+            out.push_str(" /Pattern <<");
+            for (i, pattern) in self.patterns.iter().enumerate() {
+                out.push_str(&format!(" /P{i} {pattern}"));
+            }
+            out.push_str(" >>");
+        }
+        if let Some(object) = image {
+            out.push_str(&format!(" /XObject << /Im0 {object} 0 R >>"));
+        }
+        out.push_str(" >>");
+        out
+    }
+}
+
+/// Red, green and blue components of a color, opaque black if it is invalid
+fn rgb(color: &Color) -> String {
+    let [r, g, b, _] = color.rgba().unwrap_or([0, 0, 0, 255]);
+    format!(
+        "{} {} {}",
+        number(f64::from(r) / 255f64),
+        number(f64::from(g) / 255f64),
+        number(f64::from(b) / 255f64)
+    )
+}
+
+/// Embedded image, as an image XObject and its soft mask
+struct Image {
+    width: usize,
+    height: usize,
+    /// Color space & filter
+    dictionary: String,
+    data: Vec<u8>,
+    /// Compressed alpha, if the image is not opaque
+    mask: Option<Vec<u8>>,
+}
+
+impl Image {
+    /// JPEG images are embedded as is, PNG images are decoded
+    fn new(bytes: &[u8]) -> Option<Self> {
+        match MimeType::sniff(bytes)? {
+            MimeType::Jpeg => {
+                let (width, height, components) = embed::jpeg_header(bytes)?;
+                let color_space = match components {
+                    1 => "/DeviceGray",
+                    3 => "/DeviceRGB",
+                    4 => "/DeviceCMYK",
+                    _ => return None,
+                };
+                Some(Image {
+                    width: width as usize,
+                    height: height as usize,
+                    dictionary: format!(
+                        "/ColorSpace {color_space} /BitsPerComponent 8 /Filter /DCTDecode "
+                    ),
+                    data: bytes.to_vec(),
+                    mask: None,
+                })
+            }
+            MimeType::Png => {
+                let image = png::decode(bytes)?;
+                let (mut rgb, mut alpha) = (Vec::new(), Vec::new());
+                for pixel in image.pixels.chunks(4) {
+                    rgb.extend_from_slice(&pixel[..3]);
+                    alpha.push(pixel[3]);
+                }
+                let opaque = alpha.iter().all(|&a| a == 255);
+
+                Some(Image {
+                    width: image.width,
+                    height: image.height,
+                    dictionary: String::from(
+                        "/ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /FlateDecode ",
+                    ),
+                    data: png::zlib(&rgb),
+                    mask: if opaque {
+                        None
+                    } else {
+                        Some(png::zlib(&alpha))
+                    },
+                })
+            }
+            MimeType::Svg | MimeType::Webp => None,
+        }
+    }
+}
+
+/// Stream object
+fn stream(dictionary: &str, data: &[u8]) -> Vec<u8> {
+    let mut out = format!("<< {dictionary}/Length {} >>\nstream\n", data.len()).into_bytes();
+    out.extend_from_slice(data);
+    out.extend_from_slice(b"\nendstream");
+    out
+}
+
+impl PdfBuilder {
+    /// Centers the code on a page of this size (default: the size of the code)
+    pub fn page_size(&mut self, page_size: PageSize) -> &mut Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    /// Updates the width of a module, in millimeters (default: 1, or as large
+    /// as the page allows)
+    pub fn module_size(&mut self, millimeters: f64) -> &mut Self {
+        self.module_size = Some(millimeters);
+        self
+    }
+
+    /// Embeds an image from its bytes, see [`SvgBuilder::image_bytes`]
+    ///
+    /// # Errors
+    /// - `SvgError::SvgError` if the image format is not supported
+    pub fn image_bytes(&mut self, bytes: &[u8]) -> Result<&mut Self, SvgError> {
+        self.svg_builder.image_bytes(bytes)?;
+        Ok(self)
+    }
+
+    /// Embeds a local image file, see [`SvgBuilder::image_file`]
+    ///
+    /// # Errors
+    /// - `SvgError::IoError` if the file cannot be read
+    /// - `SvgError::SvgError` if the image format is not supported
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn image_file<P: AsRef<std::path::Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, SvgError> {
+        self.svg_builder.image_file(path)?;
+        Ok(self)
+    }
+
+    /// Checks the `QRCode` stays readable with the embedded image, see
+    /// [`SvgBuilder::check_image`]
+    ///
+    /// # Errors
+    /// - `SvgError::SvgError` if the image hides too many modules for the `QRCode` to be
+    ///   recovered
+    pub fn check_image(&self, qr: &QRCode) -> Result<(), SvgError> {
+        self.svg_builder.check_image(qr)
+    }
+
+    /// Updates how square modules are written, see [`SvgBuilder::path_mode`]
+    /// (default: `PathMode::Outline`)
+    pub fn path_mode(&mut self, path_mode: PathMode) -> &mut Self {
+        self.svg_builder.path_mode(path_mode);
+        self
+    }
+
+    /// Draws a layer, alone in its graphics state
+    fn draw(layer: &Layer, resources: &mut Resources, out: &mut String) {
+        let fill = match resources.paint(&layer.paint, false) {
+            Some(fill) => fill,
+            None => return,
+        };
+        let path = path::operators(&layer.path);
+        if path.is_empty() {
+            return;
+        }
+
+        out.push_str("q ");
+        out.push_str(&fill);
+        if layer.stroke {
+            out.push_str(&resources.paint(&layer.paint, true).unwrap_or_default());
+            out.push_str(".3 w 1 j ");
+        }
+        out.push_str(&path);
+        out.push_str(if layer.stroke { "B Q\n" } else { "f Q\n" });
+    }
+
+    /// Return the bytes of a single page PDF containing the qr code
+    ///
+    /// # Errors
+    /// - `SvgError::SvgError` if the image is not a PNG or JPEG image in a base64
+    ///   `data:` URI, it would leave a hole in the code
+    pub fn to_bytes(&self, qr: &QRCode) -> Result<Vec<u8>, SvgError> {
+        let size = (self.svg_builder.margin_size() * 2 + qr.size) as f64;
+        let frame = self.svg_builder.frame_layers(size);
+        // Size of the drawing and position of the code in it, in modules
+        let (drawing, offset) = match &frame {
+            Some((layout, _)) => ((layout.width, layout.height), layout.offset),
+            None => ((size, size), (0f64, 0f64)),
+        };
+        let (module, page) = match (self.module_size, self.page_size) {
+            (Some(module), Some(page)) => (module, page.millimeters()),
+            (Some(module), None) => (module, (drawing.0 * module, drawing.1 * module)),
+            (None, Some(page)) => {
+                let (width, height) = page.millimeters();
+                ((width / drawing.0).min(height / drawing.1), (width, height))
+            }
+            (None, None) => (1f64, drawing),
+        };
+
+        let (scale, width, height) = (module * POINTS, page.0 * POINTS, page.1 * POINTS);
+        let left = (width - drawing.0 * scale) / 2f64 + offset.0 * scale;
+        let top = (height + drawing.1 * scale) / 2f64 - offset.1 * scale;
+        // Module space, going down, to page space, going up
+        let matrix = format!(
+            "{} 0 0 {} {} {}",
+            number(scale),
+            number(-scale),
+            number(left),
+            number(top)
+        );
+
+        let mut resources = Resources {
+            matrix: matrix.clone(),
+            size,
+            alphas: Vec::new(),
+            patterns: Vec::new(),
+        };
+        let mut content = format!("q {matrix} cm\n");
+
+        if let Some((_, layers)) = &frame {
+            // The frame starts at the top left of the drawing
+            content.push_str(&format!(
+                "q 1 0 0 1 {} {} cm\n",
+                number(-offset.0),
+                number(-offset.1)
+            ));
+            for layer in layers {
+                Self::draw(layer, &mut resources, &mut content);
+            }
+            content.push_str("Q\n");
+        }
+
+        let background = Layer {
+            path: format!("M0,0h{0}v{0}h-{0}z", coord(size)),
+            paint: self.svg_builder.background().clone(),
+            stroke: false,
+        };
+        Self::draw(&background, &mut resources, &mut content);
+        for layer in self.svg_builder.layers(qr) {
+            Self::draw(&layer, &mut resources, &mut content);
+        }
+
+        let mut image = None;
+        if let Some(placed) = self.svg_builder.placed_image(qr) {
+            for layer in &placed.backgrounds {
+                Self::draw(layer, &mut resources, &mut content);
+            }
+
+            let (x, y, image_size) = (placed.x, placed.y, placed.size);
+            let embedded = embed::source_bytes(placed.source)
+                .and_then(|bytes| Image::new(&bytes))
+                .ok_or_else(embed::unsupported_image)?;
+
+            // Fits in the square, keeping the aspect ratio
+            let ratio = image_size / embedded.width.max(embedded.height).max(1) as f64;
+            let (w, h) = (
+                embedded.width as f64 * ratio,
+                embedded.height as f64 * ratio,
+            );
+            let (x, y) = (x + (image_size - w) / 2f64, y + (image_size - h) / 2f64);
+            content.push_str(&format!(
+                "q {} 0 0 {} {} {} cm /Im0 Do Q\n",
+                number(w),
+                number(-h),
+                number(x),
+                number(y + h)
+            ));
+            image = Some(embedded);
+        }
+        content.push('Q');
+
+        // Catalog, pages, page, content, then the image and its mask
+        let mut objects = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources {} /Contents 4 0 R >>",
+                number(width),
+                number(height),
+                resources.to_str(image.as_ref().map(|_| 5))
+            )
+            .into_bytes(),
+            stream("", content.as_bytes()),
+        ];
+        if let Some(image) = image {
+            let dimensions = format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} ",
+                image.width, image.height
+            );
+            let mask = match image.mask {
+                Some(_) => String::from("/SMask 6 0 R "),
+                None => String::new(),
+            };
+            objects.push(stream(
+                &format!("{dimensions}{}{mask}", image.dictionary),
+                &image.data,
+            ));
+            if let Some(mask) = image.mask {
+                objects.push(stream(
+                    &format!(
+                        "{dimensions}/ColorSpace /DeviceGray /BitsPerComponent 8 /Filter /FlateDecode "
+                    ),
+                    &mask,
+                ));
+            }
+        }
+
+        let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            out.extend_from_slice(object);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+
+        // Entries of the cross-reference table are 20 bytes long
+        let xref = out.len();
+        out.extend_from_slice(
+            format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+        );
+        for offset in offsets {
+            out.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+        }
+        out.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+                objects.len() + 1
+            )
+            .as_bytes(),
+        );
+
+        Ok(out)
+    }
+
+    /// Saves the PDF for a qr code to a file
+    ///
+    /// # Errors
+    /// - `SvgError::IoError` if the file cannot be written
+    /// - `SvgError::SvgError` if the image cannot be embedded, see [`Self::to_bytes`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn to_file(&self, qr: &QRCode, file: &str) -> Result<(), SvgError> {
+        std::fs::write(file, self.to_bytes(qr)?).map_err(SvgError::IoError)
+    }
+}
@@ -0,0 +1,528 @@
+//! Minimal PNG codec, without dependencies
+//!
+//! Grayscale rows are filtered with `Up` so repeated rows become zeros, then
+//! compressed with run-length matches using the fixed Huffman codes of deflate.
+//! Decoding handles any non interlaced PNG, to embed logos.
+
+#[cfg(feature = "raster")]
+use super::crc::crc32;
+
+/// Magic bytes every PNG starts with
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Writes bits least significant first, as deflate expects
+#[cfg(any(feature = "raster", feature = "pdf"))]
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u32,
+    len: u8,
+}
+
+#[cfg(any(feature = "raster", feature = "pdf"))]
+impl BitWriter {
+    fn new() -> Self {
+        BitWriter {
+            bytes: Vec::new(),
+            current: 0,
+            len: 0,
+        }
+    }
+
+    /// Pushes the `len` lowest bits of `bits`, least significant first
+    fn push(&mut self, bits: u32, len: u8) {
+        self.current |= bits << self.len;
+        self.len += len;
+        while self.len >= 8 {
+            self.bytes.push(self.current as u8);
+            self.current >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    /// Pushes a Huffman code, most significant bit first
+    fn push_code(&mut self, code: u32, len: u8) {
+        let reversed = code.reverse_bits() >> (32 - u32::from(len));
+        self.push(reversed, len);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.current as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Fixed Huffman code of a literal / length symbol
+#[cfg(any(feature = "raster", feature = "pdf"))]
+fn push_symbol(writer: &mut BitWriter, symbol: u16) {
+    let symbol = u32::from(symbol);
+    match symbol {
+        0..=143 => writer.push_code(0x30 + symbol, 8),
+        144..=255 => writer.push_code(0x190 + symbol - 144, 9),
+        256..=279 => writer.push_code(symbol - 256, 7),
+        _ => writer.push_code(0xc0 + symbol - 280, 8),
+    }
+}
+
+/// Smallest length of each length symbol, from 257
+const LENGTH_BASES: [usize; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+/// Extra bits of each length symbol, from 257
+const LENGTH_EXTRA_BITS: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// Pushes a match of `len` bytes (3 to 258) repeating the previous byte
+#[cfg(any(feature = "raster", feature = "pdf"))]
+fn push_repeat(writer: &mut BitWriter, len: usize) {
+    let index = LENGTH_BASES.iter().rposition(|&base| base <= len).unwrap();
+    push_symbol(writer, 257 + index as u16);
+    writer.push((len - LENGTH_BASES[index]) as u32, LENGTH_EXTRA_BITS[index]);
+    // Distance 1 is the 5 bits code 0, without extra bits
+    writer.push_code(0, 5);
+}
+
+/// Compresses `data` as a single fixed Huffman deflate block
+#[cfg(any(feature = "raster", feature = "pdf"))]
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+    // Last block, fixed Huffman codes
+    writer.push(1, 1);
+    writer.push(1, 2);
+
+    let mut i = 0;
+    while i < data.len() {
+        let run = if i == 0 {
+            0
+        } else {
+            data[i..]
+                .iter()
+                .take(258)
+                .take_while(|&&byte| byte == data[i - 1])
+                .count()
+        };
+
+        if run >= 3 {
+            push_repeat(&mut writer, run);
+            i += run;
+        } else {
+            push_symbol(&mut writer, u16::from(data[i]));
+            i += 1;
+        }
+    }
+
+    // End of block
+    push_symbol(&mut writer, 256);
+    writer.finish()
+}
+
+#[cfg(any(feature = "raster", feature = "pdf"))]
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+/// zlib stream of `data`
+#[cfg(any(feature = "raster", feature = "pdf"))]
+pub(crate) fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    out.extend_from_slice(&deflate(data));
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+#[cfg(feature = "raster")]
+fn push_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Encodes a grayscale image, `rows` are already packed to `bit_depth` bits
+/// per pixel (1 or 8)
+#[cfg(feature = "raster")]
+pub(crate) fn encode_gray(width: u32, height: u32, bit_depth: u8, rows: &[Vec<u8>]) -> Vec<u8> {
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // Bit depth, grayscale, deflate, adaptive filtering, no interlace
+    header.extend_from_slice(&[bit_depth, 0, 0, 0, 0]);
+
+    let mut filtered = Vec::with_capacity(rows.iter().map(|row| row.len() + 1).sum());
+    let mut previous: &[u8] = &[];
+    for row in rows {
+        // Filter type `Up`
+        filtered.push(2);
+        for (i, &byte) in row.iter().enumerate() {
+            let above = previous.get(i).copied().unwrap_or(0);
+            filtered.push(byte.wrapping_sub(above));
+        }
+        previous = row;
+    }
+
+    let mut out = SIGNATURE.to_vec();
+    push_chunk(&mut out, b"IHDR", &header);
+    push_chunk(&mut out, b"IDAT", &zlib(&filtered));
+    push_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+/// Reads bits least significant first
+#[cfg(any(feature = "pdf", feature = "eps"))]
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    /// Position in bits
+    position: usize,
+}
+
+#[cfg(any(feature = "pdf", feature = "eps"))]
+impl<'a> BitReader<'a> {
+    fn bits(&mut self, len: u8) -> Option<u32> {
+        let mut value = 0;
+        for i in 0..len {
+            let byte = self.bytes.get(self.position / 8)?;
+            value |= u32::from((byte >> (self.position % 8)) & 1) << i;
+            self.position += 1;
+        }
+        Some(value)
+    }
+
+    fn align(&mut self) {
+        self.position = (self.position + 7) / 8 * 8;
+    }
+}
+
+/// Canonical Huffman code, from the lengths of the codes of each symbol
+#[cfg(any(feature = "pdf", feature = "eps"))]
+struct Huffman {
+    /// Number of codes of each length
+    counts: [u16; 16],
+    /// Symbols sorted by code
+    symbols: Vec<u16>,
+}
+
+#[cfg(any(feature = "pdf", feature = "eps"))]
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0usize; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len] as usize;
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize]] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Option<u16> {
+        // First code and first symbol index of the current length
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = i32::from(self.counts[len]);
+            if code - count < first {
+                return self.symbols.get((index + code - first) as usize).copied();
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        None
+    }
+}
+
+/// Decompresses a zlib stream, `None` if it holds more than `limit` bytes
+#[cfg(any(feature = "pdf", feature = "eps"))]
+pub(crate) fn inflate(data: &[u8], limit: usize) -> Option<Vec<u8>> {
+    const DISTANCE_BASES: [usize; 30] = [
+        1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+        2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+    ];
+    const DISTANCE_EXTRA_BITS: [u8; 30] = [
+        0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12,
+        13, 13,
+    ];
+    /// Order of the code length code lengths
+    const ORDER: [usize; 19] = [
+        16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+    ];
+
+    let mut reader = BitReader {
+        bytes: data.get(2..)?,
+        position: 0,
+    };
+    let mut out = Vec::new();
+
+    loop {
+        let last = reader.bits(1)? == 1;
+        let (literals, distances) = match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let len = reader.bits(16)? as usize;
+                reader.bits(16)?;
+                let start = reader.position / 8;
+                if out.len() + len > limit {
+                    return None;
+                }
+                out.extend_from_slice(reader.bytes.get(start..start + len)?);
+                reader.position += len * 8;
+                if last {
+                    return Some(out);
+                }
+                continue;
+            }
+            1 => {
+                let mut lengths = [8u8; 288];
+                lengths[144..256].iter_mut().for_each(|len| *len = 9);
+                lengths[256..280].iter_mut().for_each(|len| *len = 7);
+                (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+            }
+            2 => {
+                let literal_count = reader.bits(5)? as usize + 257;
+                let distance_count = reader.bits(5)? as usize + 1;
+                let code_count = reader.bits(4)? as usize + 4;
+
+                let mut code_lengths = [0u8; 19];
+                for &i in &ORDER[..code_count] {
+                    code_lengths[i] = reader.bits(3)? as u8;
+                }
+                let codes = Huffman::new(&code_lengths);
+
+                let mut lengths = Vec::with_capacity(literal_count + distance_count);
+                while lengths.len() < literal_count + distance_count {
+                    let (len, repeat) = match codes.decode(&mut reader)? {
+                        symbol @ 0..=15 => (symbol as u8, 1),
+                        16 => (*lengths.last()?, 3 + reader.bits(2)?),
+                        17 => (0, 3 + reader.bits(3)?),
+                        _ => (0, 11 + reader.bits(7)?),
+                    };
+                    lengths.extend(std::iter::repeat(len).take(repeat as usize));
+                }
+                (
+                    Huffman::new(&lengths[..literal_count]),
+                    Huffman::new(lengths.get(literal_count..literal_count + distance_count)?),
+                )
+            }
+            _ => return None,
+        };
+
+        loop {
+            if out.len() > limit {
+                return None;
+            }
+            let symbol = literals.decode(&mut reader)? as usize;
+            if symbol < 256 {
+                out.push(symbol as u8);
+                continue;
+            }
+            if symbol == 256 {
+                break;
+            }
+
+            let index = symbol - 257;
+            let len =
+                LENGTH_BASES.get(index)? + reader.bits(*LENGTH_EXTRA_BITS.get(index)?)? as usize;
+            let index = distances.decode(&mut reader)? as usize;
+            let distance = DISTANCE_BASES.get(index)?
+                + reader.bits(*DISTANCE_EXTRA_BITS.get(index)?)? as usize;
+            let start = out.len().checked_sub(distance)?;
+            for i in 0..len {
+                out.push(out[start + i]);
+            }
+        }
+
+        if last {
+            return Some(out);
+        }
+    }
+}
+
+/// Decoded image, 4 bytes per pixel
+#[cfg(any(feature = "pdf", feature = "eps"))]
+pub(crate) struct Rgba {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) pixels: Vec<u8>,
+}
+
+/// Decodes a non interlaced PNG, `None` if it is malformed
+#[cfg(any(feature = "pdf", feature = "eps"))]
+pub(crate) fn decode(bytes: &[u8]) -> Option<Rgba> {
+    let be_u32 = |b: &[u8]| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize;
+
+    if !bytes.starts_with(SIGNATURE) {
+        return None;
+    }
+
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut data = Vec::new();
+
+    let mut i = SIGNATURE.len();
+    while i + 8 <= bytes.len() {
+        let len = be_u32(&bytes[i..]);
+        let end = (i + 8).checked_add(len)?;
+        let chunk = bytes.get(i + 8..end)?;
+        match &bytes[i + 4..i + 8] {
+            b"IHDR" if len >= 13 => header = Some(chunk),
+            b"PLTE" => palette = chunk,
+            b"tRNS" => transparency = chunk,
+            b"IDAT" => data.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => (),
+        }
+        // Skips the CRC
+        i = end + 4;
+    }
+
+    let header = header?;
+    let (width, height) = (be_u32(header), be_u32(&header[4..]));
+    let (depth, color_type) = (header[8] as usize, header[9]);
+    // Compression, filter & interlace methods
+    if width == 0 || height == 0 || header[10..13] != [0, 0, 0] {
+        return None;
+    }
+    let (channels, depths): (usize, &[usize]) = match color_type {
+        0 => (1, &[1, 2, 4, 8, 16]),
+        2 => (3, &[8, 16]),
+        3 => (1, &[1, 2, 4, 8]),
+        4 => (2, &[8, 16]),
+        6 => (4, &[8, 16]),
+        _ => return None,
+    };
+    if !depths.contains(&depth) {
+        return None;
+    }
+
+    // Unfilters the rows, each one starts with its filter type
+    let stride = width.checked_mul(channels * depth)?.checked_add(7)? / 8;
+    let size = stride.checked_add(1)?.checked_mul(height)?;
+    let bpp = (channels * depth / 8).max(1);
+    let raw = inflate(&data, size)?;
+    if raw.len() < size {
+        return None;
+    }
+    let mut rows = vec![0u8; stride * height];
+    for y in 0..height {
+        let line = raw.get(y * (stride + 1)..(y + 1) * (stride + 1))?;
+        let (filter, line) = (line[0], &line[1..]);
+        for x in 0..stride {
+            let left = if x >= bpp {
+                rows[y * stride + x - bpp]
+            } else {
+                0
+            };
+            let up = if y > 0 { rows[(y - 1) * stride + x] } else { 0 };
+            let up_left = if x >= bpp && y > 0 {
+                rows[(y - 1) * stride + x - bpp]
+            } else {
+                0
+            };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                4 => {
+                    let p = i16::from(left) + i16::from(up) - i16::from(up_left);
+                    let (pa, pb, pc) = (
+                        (p - i16::from(left)).abs(),
+                        (p - i16::from(up)).abs(),
+                        (p - i16::from(up_left)).abs(),
+                    );
+                    if pa <= pb && pa <= pc {
+                        left
+                    } else if pb <= pc {
+                        up
+                    } else {
+                        up_left
+                    }
+                }
+                _ => return None,
+            };
+            rows[y * stride + x] = line[x].wrapping_add(predicted);
+        }
+    }
+
+    // Raw samples, only the most significant byte of 16 bits samples
+    let max = (1usize << depth.min(8)) - 1;
+    let sample = |y: usize, index: usize| -> usize {
+        let row = &rows[y * stride..(y + 1) * stride];
+        match depth {
+            16 => row[index * 2] as usize,
+            8 => row[index] as usize,
+            _ => {
+                let bit = index * depth;
+                (row[bit / 8] as usize >> (8 - depth - bit % 8)) & max
+            }
+        }
+    };
+    let scale = |value: usize| (value * 255 / max) as u8;
+    // Gray level drawn transparent
+    let gray_key = match transparency {
+        [high, low] if color_type == 0 => {
+            let key = usize::from(*high) << 8 | usize::from(*low);
+            Some(if depth == 16 { key >> 8 } else { key })
+        }
+        _ => None,
+    };
+
+    let mut pixels = Vec::with_capacity(width.checked_mul(height)?.checked_mul(4)?);
+    for y in 0..height {
+        for x in 0..width {
+            let s = |c: usize| sample(y, x * channels + c);
+            let pixel = match color_type {
+                0 => {
+                    let gray = scale(s(0));
+                    let transparent = gray_key.map_or(false, |key| key == s(0));
+                    [gray, gray, gray, if transparent { 0 } else { 255 }]
+                }
+                2 => [scale(s(0)), scale(s(1)), scale(s(2)), 255],
+                3 => {
+                    let index = s(0);
+                    let color = palette.get(index * 3..index * 3 + 3)?;
+                    let alpha = transparency.get(index).copied().unwrap_or(255);
+                    [color[0], color[1], color[2], alpha]
+                }
+                4 => {
+                    let gray = scale(s(0));
+                    [gray, gray, gray, scale(s(1))]
+                }
+                _ => [scale(s(0)), scale(s(1)), scale(s(2)), scale(s(3))],
+            };
+            pixels.extend_from_slice(&pixel);
+        }
+    }
+
+    Some(Rgba {
+        width,
+        height,
+        pixels,
+    })
+}
@@ -0,0 +1,218 @@
+//! Converts [`QRCode`] to a grayscale bitmap or PNG, without going through svg
+//!
+//! Every module is drawn on a whole number of pixels, so edges are sharp and
+//! there is no grey seam between modules. Unlike [`image`](super::image), it
+//! does not depend on resvg.
+//!
+//! ```rust
+//! use fast_qr::convert::raster::{BitDepth, RasterBuilder};
+//! use fast_qr::convert::Shape;
+//! use fast_qr::qr::QRBuilder;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .build()
+//!     .unwrap();
+//!
+//! let png = RasterBuilder::default()
+//!     .shape(Shape::Circle)
+//!     .scale(10)
+//!     .to_png(&qrcode, BitDepth::One);
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::io;
+
+use crate::QRCode;
+
+use super::{png, Shape};
+
+/// Bits per pixel of a grayscale PNG
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitDepth {
+    /// Black & white, smallest files
+    One,
+    /// 256 shades of grey
+    Eight,
+}
+
+/// Grayscale image, one byte per pixel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitmap {
+    /// Width in pixels
+    pub width: usize,
+    /// Height in pixels
+    pub height: usize,
+    /// Pixels, row by row, 0 is black and 255 is white
+    pub pixels: Vec<u8>,
+}
+
+impl Bitmap {
+    /// Gray level of the pixel at `(x, y)`
+    #[must_use]
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Rows packed to `bit_depth` bits per pixel
+    fn packed_rows(&self, bit_depth: BitDepth) -> Vec<Vec<u8>> {
+        self.pixels
+            .chunks(self.width)
+            .map(|row| match bit_depth {
+                BitDepth::Eight => row.to_vec(),
+                BitDepth::One => row
+                    .chunks(8)
+                    .map(|pixels| {
+                        pixels.iter().enumerate().fold(0u8, |byte, (i, &pixel)| {
+                            byte | (u8::from(pixel >= 128) << (7 - i))
+                        })
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Encodes the bitmap as a grayscale PNG
+    #[must_use]
+    pub fn to_png(&self, bit_depth: BitDepth) -> Vec<u8> {
+        let depth = match bit_depth {
+            BitDepth::One => 1,
+            BitDepth::Eight => 8,
+        };
+
+        png::encode_gray(
+            self.width as u32,
+            self.height as u32,
+            depth,
+            &self.packed_rows(bit_depth),
+        )
+    }
+}
+
+/// Builder for grayscale bitmaps and PNG, can set shape, margin, scale and colors
+pub struct RasterBuilder {
+    /// Shape of the modules, default is square.
+    /// [`Shape::Command`] cannot be rasterized and is drawn as a square
+    shape: Shape,
+    /// The margin, in modules, default is 4
+    margin: usize,
+    /// Pixels per module, default is 8
+    scale: usize,
+    /// Gray level of the modules, default is 0 (black)
+    module_color: u8,
+    /// Gray level of the background, default is 255 (white)
+    background_color: u8,
+}
+
+impl Default for RasterBuilder {
+    fn default() -> Self {
+        RasterBuilder {
+            shape: Shape::Square,
+            margin: 4,
+            scale: 8,
+            module_color: 0,
+            background_color: 255,
+        }
+    }
+}
+
+impl RasterBuilder {
+    /// Updates the shape of the modules (default: Square)
+    pub fn shape(&mut self, shape: Shape) -> &mut Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Updates margin, in modules (default: 4)
+    pub fn margin(&mut self, margin: usize) -> &mut Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Updates the number of pixels per module, at least 1 (default: 8)
+    pub fn scale(&mut self, scale: usize) -> &mut Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Updates the gray level of the modules (default: 0)
+    pub fn module_color(&mut self, module_color: u8) -> &mut Self {
+        self.module_color = module_color;
+        self
+    }
+
+    /// Updates the gray level of the background (default: 255)
+    pub fn background_color(&mut self, background_color: u8) -> &mut Self {
+        self.background_color = background_color;
+        self
+    }
+
+    /// Whether the point `(u, v)` of a module, between 0 and 1, is covered
+    fn covers(&self, u: f64, v: f64) -> bool {
+        let (du, dv) = ((u - 0.5f64).abs(), (v - 0.5f64).abs());
+        match self.shape {
+            Shape::Circle => du * du + dv * dv <= 0.25f64,
+            // Square from .2 to .8 with a .3 wide rounded stroke
+            Shape::RoundedSquare => {
+                let (ou, ov) = ((du - 0.3f64).max(0f64), (dv - 0.3f64).max(0f64));
+                ou * ou + ov * ov <= 0.15f64 * 0.15f64
+            }
+            Shape::Vertical => du <= 0.4f64,
+            Shape::Horizontal => dv <= 0.4f64,
+            Shape::Diamond => du + dv <= 0.5f64,
+            _ => true,
+        }
+    }
+
+    /// Draws the [`QRCode`] on a grayscale bitmap
+    #[must_use]
+    pub fn to_bitmap(&self, qr: &QRCode) -> Bitmap {
+        let scale = self.scale;
+        let side = (qr.size + 2 * self.margin) * scale;
+        let mut pixels = vec![self.background_color; side * side];
+
+        // Which pixels of a module are covered, sampled at their center
+        let mask: Vec<bool> = (0..scale * scale)
+            .map(|i| {
+                let u = ((i % scale) as f64 + 0.5f64) / scale as f64;
+                let v = ((i / scale) as f64 + 0.5f64) / scale as f64;
+                self.covers(u, v)
+            })
+            .collect();
+
+        for y in 0..qr.size {
+            for (x, cell) in qr[y].iter().enumerate() {
+                if !cell.value() {
+                    continue;
+                }
+
+                let top = (y + self.margin) * scale;
+                let left = (x + self.margin) * scale;
+                for (i, _) in mask.iter().enumerate().filter(|(_, &covered)| covered) {
+                    let (dx, dy) = (i % scale, i / scale);
+                    pixels[(top + dy) * side + left + dx] = self.module_color;
+                }
+            }
+        }
+
+        Bitmap {
+            width: side,
+            height: side,
+            pixels,
+        }
+    }
+
+    /// Encodes the [`QRCode`] as a grayscale PNG
+    #[must_use]
+    pub fn to_png(&self, qr: &QRCode, bit_depth: BitDepth) -> Vec<u8> {
+        self.to_bitmap(qr).to_png(bit_depth)
+    }
+
+    /// Saves the [`QRCode`] as a grayscale PNG
+    ///
+    /// # Errors
+    /// - `io::Error` if the file cannot be written
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn to_file(&self, qr: &QRCode, file: &str, bit_depth: BitDepth) -> io::Result<()> {
+        std::fs::write(file, self.to_png(qr, bit_depth))
+    }
+}
@@ -0,0 +1,174 @@
+//! Converts [`QRCode`] to source code, for firmware drawing precomputed
+//! bitmaps
+//!
+//! Modules are packed row-major, 1 bit per module and 1 for dark, each row
+//! padded to a whole number of bytes.
+//!
+//! ```rust
+//! use fast_qr::convert::source::{BitOrder, Language, SourceBuilder};
+//! use fast_qr::qr::QRBuilder;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .build()
+//!     .unwrap();
+//!
+//! let _header = SourceBuilder::default()
+//!     .name("login_qr")
+//!     .margin(0)
+//!     .bit_order(BitOrder::LsbFirst)
+//!     .to_str(&qrcode, Language::C);
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::io;
+
+use crate::QRCode;
+
+use super::dots;
+
+/// Languages the bitmap can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    /// C header with a `static const uint8_t` array and size macros
+    C,
+    /// X BitMap, always least significant bit first
+    Xbm,
+    /// Rust `const` array and size constants
+    Rust,
+}
+
+/// Order of the modules in a byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOrder {
+    /// The leftmost module is the most significant bit (default)
+    MsbFirst,
+    /// The leftmost module is the least significant bit
+    LsbFirst,
+}
+
+/// Builder for source code, can set name, margin and bit order
+pub struct SourceBuilder {
+    /// Name of the array, default is `qr`
+    name: String,
+    /// The margin, in modules, default is 4
+    margin: usize,
+    /// Bit order, default is `BitOrder::MsbFirst`
+    bit_order: BitOrder,
+}
+
+impl Default for SourceBuilder {
+    fn default() -> Self {
+        SourceBuilder {
+            name: String::from("qr"),
+            margin: 4,
+            bit_order: BitOrder::MsbFirst,
+        }
+    }
+}
+
+/// Bytes as hexadecimal literals, 12 per line
+fn array(bytes: &[u8], indent: &str) -> String {
+    bytes
+        .chunks(12)
+        .map(|line| {
+            let line: Vec<String> = line.iter().map(|byte| format!("0x{byte:02x},")).collect();
+            format!("{indent}{}\n", line.join(" "))
+        })
+        .collect()
+}
+
+impl SourceBuilder {
+    /// Updates the name of the array, other characters than ASCII letters,
+    /// digits and `_` become `_` (default: `qr`)
+    pub fn name(&mut self, name: &str) -> &mut Self {
+        let mut name: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            name.insert(0, '_');
+        }
+        self.name = name;
+        self
+    }
+
+    /// Updates margin, in modules, 0 for no quiet zone (default: 4)
+    pub fn margin(&mut self, margin: usize) -> &mut Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Updates the order of the modules in a byte (default: `BitOrder::MsbFirst`)
+    pub fn bit_order(&mut self, bit_order: BitOrder) -> &mut Self {
+        self.bit_order = bit_order;
+        self
+    }
+
+    /// Return the packed bitmap, row-major
+    pub fn to_bytes(&self, qr: &QRCode) -> Vec<u8> {
+        self.packed(qr, self.bit_order)
+    }
+
+    fn packed(&self, qr: &QRCode, bit_order: BitOrder) -> Vec<u8> {
+        let bytes = dots::rows(qr, self.margin, 1).concat();
+        match bit_order {
+            BitOrder::MsbFirst => bytes,
+            BitOrder::LsbFirst => bytes.iter().map(|byte| byte.reverse_bits()).collect(),
+        }
+    }
+
+    /// Return the source code of the bitmap
+    pub fn to_str(&self, qr: &QRCode, language: Language) -> String {
+        let side = qr.size + 2 * self.margin;
+        let row = (side + 7) / 8;
+        let (name, upper) = (&self.name, self.name.to_ascii_uppercase());
+        let order = match self.bit_order {
+            BitOrder::MsbFirst => "most",
+            BitOrder::LsbFirst => "least",
+        };
+
+        match language {
+            Language::C => {
+                let bytes = self.to_bytes(qr);
+                format!(
+                    "#ifndef {upper}_H\n#define {upper}_H\n\n#include <stdint.h>\n\n\
+                     #define {upper}_WIDTH {side}\n#define {upper}_HEIGHT {side}\n\
+                     #define {upper}_BYTES_PER_ROW {row}\n\n\
+                     /* 1 bit per module, 1 is dark, {order} significant bit first */\n\
+                     static const uint8_t {name}[{}] = {{\n{}}};\n\n#endif /* {upper}_H */\n",
+                    bytes.len(),
+                    array(&bytes, "    ")
+                )
+            }
+            Language::Xbm => {
+                let bytes = self.packed(qr, BitOrder::LsbFirst);
+                format!(
+                    "#define {name}_width {side}\n#define {name}_height {side}\n\
+                     static unsigned char {name}_bits[] = {{\n{}}};\n",
+                    array(&bytes, "   ")
+                )
+            }
+            Language::Rust => {
+                let bytes = self.to_bytes(qr);
+                format!(
+                    "/// Width of `{upper}`, in modules\npub const {upper}_WIDTH: usize = {side};\n\
+                     /// Height of `{upper}`, in modules\npub const {upper}_HEIGHT: usize = {side};\n\
+                     /// Bytes of each row of `{upper}`\npub const {upper}_BYTES_PER_ROW: usize = {row};\n\n\
+                     /// 1 bit per module, 1 is dark, {order} significant bit first\n\
+                     pub const {upper}: [u8; {}] = [\n{}];\n",
+                    bytes.len(),
+                    array(&bytes, "    ")
+                )
+            }
+        }
+    }
+
+    /// Saves the source code of the bitmap to a file
+    ///
+    /// # Errors
+    /// - `io::Error` if the file cannot be written
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn to_file(&self, qr: &QRCode, file: &str, language: Language) -> io::Result<()> {
+        std::fs::write(file, self.to_str(qr, language))
+    }
+}
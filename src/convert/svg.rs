@@ -21,9 +21,154 @@
 //! # }
 //! ```
 
-use crate::{QRCode, Version};
+use std::collections::{HashMap, HashSet};
 
-use super::{Builder, Color, ImageBackgroundShape, ModuleFunction, Shape};
+use crate::analysis::{DamageReport, Occlusion};
+use crate::qr::LogoSize;
+use crate::{ModuleType, QRCode, Version};
+
+#[cfg(any(feature = "pdf", feature = "eps"))]
+use super::frame::FrameLayout;
+use super::{
+    contour, coord, embed, frame::Frame, Builder, Color, EyeBallShape, EyeFrameShape,
+    ImageBackgroundShape, ModuleContext, ModuleFunction, ModuleRenderer, Paint, Shape,
+};
+
+/// Shapes and colors of finder or alignment patterns drawn as a whole
+struct EyeStyle {
+    frame: EyeFrameShape,
+    ball: EyeBallShape,
+    /// Frame color, default is `dot_color`
+    frame_color: Option<Paint>,
+    /// Ball color, default is `dot_color`
+    ball_color: Option<Paint>,
+}
+
+/// Gradients and patterns used by a svg, written in its `<defs>`
+struct Defs {
+    /// Width of the svg
+    size: usize,
+    /// Prefix of the ids
+    prefix: String,
+    /// Definitions, the id of the nth one is `{prefix}paint-n`
+    definitions: Vec<String>,
+}
+
+impl Defs {
+    fn new(size: usize, prefix: &str) -> Self {
+        Defs {
+            size,
+            prefix: prefix.to_string(),
+            definitions: Vec::new(),
+        }
+    }
+
+    /// Returns the `fill` value of a paint, adding its definition if needed
+    fn fill(&mut self, paint: &Paint) -> String {
+        if let Paint::Color(color) = paint {
+            return color.to_str().to_string();
+        }
+
+        // Reuses identical definitions
+        for (i, definition) in self.definitions.iter().enumerate() {
+            let id = format!("{}paint-{i}", self.prefix);
+            if paint.definition(&id, self.size).as_ref() == Some(definition) {
+                return format!("url(#{id})");
+            }
+        }
+
+        let id = format!("{}paint-{}", self.prefix, self.definitions.len());
+        if let Some(definition) = paint.definition(&id, self.size) {
+            self.definitions.push(definition);
+        }
+        format!("url(#{id})")
+    }
+
+    fn to_str(&self) -> String {
+        if self.definitions.is_empty() {
+            return String::new();
+        }
+
+        format!("<defs>{}</defs>", self.definitions.join(""))
+    }
+}
+
+impl Default for EyeStyle {
+    fn default() -> Self {
+        EyeStyle {
+            frame: EyeFrameShape::Square,
+            ball: EyeBallShape::Square,
+            frame_color: None,
+            ball_color: None,
+        }
+    }
+}
+
+/// Module types and their group class, with [`SvgBuilder::group_by_type`]
+const GROUPS: [(ModuleType, &str); 7] = [
+    (ModuleType::FinderPattern, "finder"),
+    (ModuleType::Alignment, "alignment"),
+    (ModuleType::Timing, "timing"),
+    (ModuleType::Format, "format"),
+    (ModuleType::Version, "version"),
+    (ModuleType::DarkModule, "dark-module"),
+    (ModuleType::Data, "data"),
+];
+
+/// Removes the characters not allowed in ids and classes, so prefixes can't
+/// break out of attributes or selectors
+fn prefix(mut prefix: String) -> String {
+    prefix.retain(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    prefix
+}
+
+/// Path filled with a single paint
+pub(crate) struct Layer {
+    /// Svg path data, in modules, margin included
+    pub(crate) path: String,
+    pub(crate) paint: Paint,
+    /// Whether the path is also stroked, .3 wide with round joins, to round
+    /// its corners
+    pub(crate) stroke: bool,
+}
+
+impl Layer {
+    fn to_str(&self, defs: &mut Defs, class: &str) -> String {
+        let fill = defs.fill(&self.paint);
+        let stroke = if self.stroke {
+            format!(r#" stroke-width=".3" stroke-linejoin="round" stroke="{fill}""#)
+        } else {
+            String::new()
+        };
+
+        format!(r#"<path d="{}"{stroke}{class} fill="{fill}"/>"#, self.path)
+    }
+}
+
+/// Embedded image, drawn over its backgrounds
+#[cfg(any(feature = "pdf", feature = "eps"))]
+pub(crate) struct PlacedImage<'a> {
+    pub(crate) backgrounds: Vec<Layer>,
+    /// Path, url or data URI of the image
+    pub(crate) source: &'a str,
+    /// Left of the image, in modules
+    pub(crate) x: f64,
+    /// Top of the image, in modules
+    pub(crate) y: f64,
+    /// Width & height of the square the image fits in, in modules
+    pub(crate) size: f64,
+}
+
+/// How [`Shape::Square`] modules are written in the svg path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathMode {
+    /// One subpath per module, `M{x},{y}h1v1h-1` (default)
+    Modules,
+    /// Merges horizontal runs of modules into rectangles, using relative commands
+    Runs,
+    /// Traces the outlines of dark regions into polygons, smallest output
+    Outline,
+}
 
 /// Builder for svg, can set shape, margin, background_color, dot_color
 pub struct SvgBuilder {
@@ -33,16 +178,20 @@ pub struct SvgBuilder {
     /// Commands can also have a custom color
     /// The default is `dot_color`, commands with specific colors can be
     /// added using `.shape_color()`
-    command_colors: Vec<Option<Color>>,
+    command_colors: Vec<Option<Paint>>,
+    /// Custom renderers, drawn after the commands, added using `.renderer()`
+    renderers: Vec<Box<dyn ModuleRenderer + Send + Sync>>,
     /// The margin for the svg, default is 4
     margin: usize,
+    /// How square modules are written, default is one subpath per module
+    path_mode: PathMode,
     /// The background color for the svg, default is #FFFFFF
-    background_color: Color,
+    background_color: Paint,
     /// The color for each module, default is #000000
-    dot_color: Color,
+    dot_color: Paint,
 
     // Image Embedding
-    /// Image to embed in the svg, can be a path or a base64 string
+    /// Image to embed in the svg, can be a path, an url or a data URI
     image: Option<String>,
     /// Background color for the image, default is #FFFFFF
     image_background_color: Color,
@@ -52,6 +201,34 @@ pub struct SvgBuilder {
     image_size: Option<(f64, f64)>,
     /// Position of the image, default is center
     image_position: Option<(f64, f64)>,
+    /// Area reserved for the image, default is sized from the version
+    image_area: Option<LogoSize>,
+    /// Removes the modules under the image instead of hiding them, default is false
+    clear_under_image: bool,
+
+    // Finder & alignment patterns
+    /// Finder patterns style, default is drawn like other modules
+    finder: Option<EyeStyle>,
+    /// Alignment patterns style, default is drawn like other modules
+    alignment: Option<EyeStyle>,
+
+    // Semantic structure
+    /// Groups modules by type in `<g>` elements, default is false
+    group_by_type: bool,
+    /// Accessible title, default is none
+    title: Option<String>,
+    /// Accessible description, default is none
+    description: Option<String>,
+    /// Prefix of the generated ids, default is `fqr-`
+    id_prefix: String,
+    /// Prefix of the generated classes, default is `fqr-`
+    class_prefix: String,
+    /// Module & background colors when the user prefers a dark color scheme,
+    /// default is none
+    dark_mode: Option<(Color, Color)>,
+
+    /// Frame around the code, default is none
+    frame: Option<Frame>,
 }
 
 #[derive(Debug)]
@@ -71,8 +248,10 @@ impl Default for SvgBuilder {
             background_color: [255; 4].into(),
             dot_color: [0, 0, 0, 255].into(),
             margin: 4,
+            path_mode: PathMode::Modules,
             commands: Vec::new(),
             command_colors: Vec::new(),
+            renderers: Vec::new(),
 
             // Image Embedding
             image: None,
@@ -80,6 +259,20 @@ impl Default for SvgBuilder {
             image_background_shape: ImageBackgroundShape::Square,
             image_size: None,
             image_position: None,
+            image_area: None,
+            clear_under_image: false,
+
+            finder: None,
+            alignment: None,
+
+            group_by_type: false,
+            title: None,
+            description: None,
+            id_prefix: String::from("fqr-"),
+            class_prefix: String::from("fqr-"),
+            dark_mode: None,
+
+            frame: None,
         }
     }
 }
@@ -90,12 +283,12 @@ impl Builder for SvgBuilder {
         self
     }
 
-    fn module_color<C: Into<Color>>(&mut self, dot_color: C) -> &mut Self {
+    fn module_color<P: Into<Paint>>(&mut self, dot_color: P) -> &mut Self {
         self.dot_color = dot_color.into();
         self
     }
 
-    fn background_color<C: Into<Color>>(&mut self, background_color: C) -> &mut Self {
+    fn background_color<P: Into<Paint>>(&mut self, background_color: P) -> &mut Self {
         self.background_color = background_color.into();
         self
     }
@@ -106,12 +299,17 @@ impl Builder for SvgBuilder {
         self
     }
 
-    fn shape_color<C: Into<Color>>(&mut self, shape: Shape, color: C) -> &mut Self {
+    fn shape_color<P: Into<Paint>>(&mut self, shape: Shape, color: P) -> &mut Self {
         self.commands.push(*shape);
         self.command_colors.push(Some(color.into()));
         self
     }
 
+    fn renderer<R: ModuleRenderer + Send + Sync + 'static>(&mut self, renderer: R) -> &mut Self {
+        self.renderers.push(Box::new(renderer));
+        self
+    }
+
     fn image(&mut self, image: String) -> &mut Self {
         self.image = Some(image);
         self
@@ -139,6 +337,49 @@ impl Builder for SvgBuilder {
         self.image_position = Some((x, y));
         self
     }
+
+    fn image_area(&mut self, size: LogoSize) -> &mut Self {
+        self.image_area = Some(size);
+        self
+    }
+
+    fn clear_under_image(&mut self, clear: bool) -> &mut Self {
+        self.clear_under_image = clear;
+        self
+    }
+
+    fn frame(&mut self, frame: Frame) -> &mut Self {
+        self.frame = Some(frame);
+        self
+    }
+
+    fn finder_shape(&mut self, frame: EyeFrameShape, ball: EyeBallShape) -> &mut Self {
+        let finder = self.finder.get_or_insert_with(EyeStyle::default);
+        finder.frame = frame;
+        finder.ball = ball;
+        self
+    }
+
+    fn finder_color<P: Into<Paint>>(&mut self, frame: P, ball: P) -> &mut Self {
+        let finder = self.finder.get_or_insert_with(EyeStyle::default);
+        finder.frame_color = Some(frame.into());
+        finder.ball_color = Some(ball.into());
+        self
+    }
+
+    fn alignment_shape(&mut self, frame: EyeFrameShape, ball: EyeBallShape) -> &mut Self {
+        let alignment = self.alignment.get_or_insert_with(EyeStyle::default);
+        alignment.frame = frame;
+        alignment.ball = ball;
+        self
+    }
+
+    fn alignment_color<P: Into<Paint>>(&mut self, frame: P, ball: P) -> &mut Self {
+        let alignment = self.alignment.get_or_insert_with(EyeStyle::default);
+        alignment.frame_color = Some(frame.into());
+        alignment.ball_color = Some(ball.into());
+        self
+    }
 }
 
 impl SvgBuilder {
@@ -185,17 +426,27 @@ impl SvgBuilder {
         (border_size, placed_coord, border_size - gap)
     }
 
-    fn image(&self, n: usize) -> String {
-        if self.image.is_none() {
-            return String::new();
-        }
-
-        let image = self.image.as_ref().unwrap();
-        let mut out = String::with_capacity(image.len() + 100);
+    /// Returns `(border_size, placed_coord, image_size)` of the embedded image, if any
+    fn image_bounds(&self, qr: &QRCode) -> Option<(f64, (f64, f64), f64)> {
+        self.image.as_ref()?;
 
+        let n = qr.size;
         let (mut border_size, mut placed_coord, mut image_size) =
             Self::image_placement(self.image_background_shape, self.margin, n);
 
+        // Uses the area reserved by `QRBuilder::logo`, then `Builder::image_area`
+        if let Some(&Occlusion::Rectangle { x, y, width, .. }) = qr.logo() {
+            border_size = width as f64;
+            placed_coord = ((x + self.margin) as f64, (y + self.margin) as f64);
+            image_size = (border_size - 2f64).max(border_size / 2f64);
+        } else if let Some(size) = self.image_area {
+            let side = size.side(n).min(n);
+            border_size = side as f64;
+            let placed = ((n - side) / 2 + self.margin) as f64;
+            placed_coord = (placed, placed);
+            image_size = (border_size - 2f64).max(border_size / 2f64);
+        }
+
         if let Some((override_size, gap)) = self.image_size {
             border_size = override_size + gap * 2f64;
             let mut placed_coord_x = (self.margin * 2 + n) as f64 - border_size;
@@ -208,13 +459,100 @@ impl SvgBuilder {
             placed_coord = (x - border_size / 2f64, y - border_size / 2f64);
         }
 
-        out.push_str(&format!(
-            r#"<rect x="{0:.2}" y="{1:.2}" width="{2:.2}" height="{2:.2}" fill="{3}"/>"#,
-            placed_coord.0,
-            placed_coord.1,
-            border_size,
-            self.background_color.to_str()
-        ));
+        Some((border_size, placed_coord, image_size))
+    }
+
+    /// Returns the modules hidden by the embedded image background.
+    ///
+    /// Function patterns are never included, they are kept even when
+    /// [`Builder::clear_under_image`] is set. The result can be given to
+    /// [`crate::analysis::DamageReport`] to check the `QRCode` is still readable.
+    pub fn cleared_modules(&self, qr: &QRCode) -> Occlusion {
+        let mut modules = HashSet::new();
+
+        let (border_size, placed_coord, _) = match self.image_bounds(qr) {
+            Some(area) => area,
+            None => return Occlusion::Modules(modules),
+        };
+
+        let radius = border_size / 2f64;
+        let center = (placed_coord.0 + radius, placed_coord.1 + radius);
+
+        for y in 0..qr.size {
+            for x in 0..qr.size {
+                if qr[y][x].module_type() != ModuleType::Data {
+                    continue;
+                }
+
+                let left = (x + self.margin) as f64;
+                let top = (y + self.margin) as f64;
+
+                let covered = match self.image_background_shape {
+                    ImageBackgroundShape::Square | ImageBackgroundShape::RoundedSquare => {
+                        left + 1f64 > placed_coord.0
+                            && left < placed_coord.0 + border_size
+                            && top + 1f64 > placed_coord.1
+                            && top < placed_coord.1 + border_size
+                    }
+                    ImageBackgroundShape::Circle => {
+                        // Closest point of the module to the center
+                        let dx = center.0 - center.0.clamp(left, left + 1f64);
+                        let dy = center.1 - center.1.clamp(top, top + 1f64);
+                        dx * dx + dy * dy < radius * radius
+                    }
+                };
+
+                if covered {
+                    modules.insert((y, x));
+                }
+            }
+        }
+
+        Occlusion::Modules(modules)
+    }
+
+    /// Checks the `QRCode` stays readable with the modules hidden by the embedded image.
+    ///
+    /// Areas reserved by [`crate::QRBuilder::logo`] are already checked when building the
+    /// `QRCode`. Other images are only checked when calling this, writing the svg does not
+    /// check them.
+    ///
+    /// # Errors
+    /// - `SvgError::SvgError` if the image hides too many modules for the `QRCode` to be recovered
+    pub fn check_image(&self, qr: &QRCode) -> Result<(), SvgError> {
+        if qr.logo().is_some() {
+            return Ok(());
+        }
+
+        let report = DamageReport::new(qr, &self.cleared_modules(qr));
+        if report.is_recoverable() {
+            Ok(())
+        } else {
+            Err(SvgError::SvgError(String::from(
+                "Image hides too many modules for the QRCode to stay readable",
+            )))
+        }
+    }
+
+    fn image(&self, qr: &QRCode, defs: &mut Defs) -> String {
+        let (border_size, placed_coord, image_size) = match self.image_bounds(qr) {
+            Some(area) => area,
+            None => return String::new(),
+        };
+
+        let image = self.image.as_ref().unwrap();
+        let mut out = String::with_capacity(image.len() + 100);
+
+        // Modules are already removed, no need to hide them
+        if !self.clear_under_image {
+            out.push_str(&format!(
+                r#"<rect x="{0:.2}" y="{1:.2}" width="{2:.2}" height="{2:.2}" fill="{3}"/>"#,
+                placed_coord.0,
+                placed_coord.1,
+                border_size,
+                defs.fill(&self.background_color)
+            ));
+        }
 
         let format = match self.image_background_shape {
             ImageBackgroundShape::Square => {
@@ -232,7 +570,7 @@ impl SvgBuilder {
             .replace("{0}", &placed_coord.0.to_string())
             .replace("{1}", &placed_coord.1.to_string())
             .replace("{2}", &border_size.to_string())
-            .replace("{3}", &self.image_background_color.to_str());
+            .replace("{3}", self.image_background_color.to_str());
 
         out.push_str(&format);
 
@@ -247,81 +585,672 @@ impl SvgBuilder {
         out
     }
 
-    fn path(&self, qr: &QRCode) -> String {
+    /// Modules, one layer per shape then per renderer color. With `grouped`,
+    /// one list of layers per group of [`GROUPS`], otherwise a single list.
+    /// Empty layers are skipped.
+    fn module_layers(&self, qr: &QRCode, grouped: bool) -> Vec<Vec<Layer>> {
         const DEFAULT_COMMAND: [ModuleFunction; 1] = [Shape::square];
-        const DEFAULT_COMMAND_COLOR: [Option<Color>; 1] = [None];
+        const DEFAULT_COMMAND_COLOR: [Option<Paint>; 1] = [None];
 
         // TODO: cleanup this basic logic
-        let command_colors: &[Option<Color>] = if !self.commands.is_empty() {
+        let is_default = self.commands.is_empty() && self.renderers.is_empty();
+        let command_colors: &[Option<Paint>] = if !is_default {
             &self.command_colors
         } else {
             &DEFAULT_COMMAND_COLOR
         };
-        let commands: &[ModuleFunction] = if !self.commands.is_empty() {
+        let commands: &[ModuleFunction] = if !is_default {
             &self.commands
         } else {
             &DEFAULT_COMMAND
         };
 
-        let mut paths = vec![String::with_capacity(10 * qr.size * qr.size); commands.len()];
-        for path in paths.iter_mut() {
-            path.push_str(r#"<path d=""#);
+        let group_count = if grouped { GROUPS.len() } else { 1 };
+        let group_of = |module_type: ModuleType| {
+            if grouped {
+                GROUPS
+                    .iter()
+                    .position(|&(group, _)| group == module_type)
+                    .unwrap_or(0)
+            } else {
+                0
+            }
+        };
+
+        let mut paths = vec![vec![String::new(); commands.len()]; group_count];
+
+        // For each group and renderer, the path in the module color then paths
+        // per color, in the order colors first appear
+        let mut rendered =
+            vec![
+                vec![(String::new(), Vec::<(String, String)>::new()); self.renderers.len()];
+                group_count
+            ];
+        let mut color_indices =
+            vec![vec![HashMap::<String, usize>::new(); self.renderers.len()]; group_count];
+
+        // Square modules are written all at once, except in `PathMode::Modules`
+        let merged = |command: ModuleFunction| {
+            self.path_mode != PathMode::Modules
+                && command as usize == Shape::square as ModuleFunction as usize
+        };
+        let mut drawn = vec![vec![false; qr.size * qr.size]; group_count];
+
+        let mut cleared = vec![false; qr.size * qr.size];
+        if self.clear_under_image {
+            if let Occlusion::Modules(modules) = self.cleared_modules(qr) {
+                for (y, x) in modules {
+                    cleared[y * qr.size + x] = true;
+                }
+            }
         }
 
         for y in 0..qr.size {
             let line = &qr[y];
             for (x, &cell) in line.iter().enumerate() {
-                if !cell.value() {
+                if !cell.value() || cleared[y * qr.size + x] {
                     continue;
                 }
 
-                for (i, command) in commands.iter().enumerate() {
-                    paths[i].push_str(&command(x + self.margin, y + self.margin, cell));
+                // Drawn as a whole by `patterns`
+                match cell.module_type() {
+                    ModuleType::FinderPattern if self.finder.is_some() => continue,
+                    ModuleType::Alignment if self.alignment.is_some() => continue,
+                    _ => (),
+                }
+
+                let group = group_of(cell.module_type());
+                drawn[group][y * qr.size + x] = true;
+                for (i, &command) in commands.iter().enumerate() {
+                    if merged(command) {
+                        continue;
+                    }
+                    paths[group][i].push_str(&command(y + self.margin, x + self.margin, cell));
+                }
+
+                let context = ModuleContext::new(qr, y, x, self.margin);
+                for ((renderer, (path, colored)), indices) in self
+                    .renderers
+                    .iter()
+                    .zip(rendered[group].iter_mut())
+                    .zip(color_indices[group].iter_mut())
+                {
+                    let output = renderer.render(&context);
+                    let color = match output.color {
+                        Some(color) => color,
+                        None => {
+                            path.push_str(&output.path);
+                            continue;
+                        }
+                    };
+
+                    match indices.get(color.to_str()) {
+                        Some(&i) => colored[i].1.push_str(&output.path),
+                        None => {
+                            indices.insert(color.0.clone(), colored.len());
+                            colored.push((color.0, output.path));
+                        }
+                    }
                 }
             }
         }
 
-        for (i, &command) in commands.iter().enumerate() {
-            let command_color = command_colors[i].as_ref().unwrap_or(&self.dot_color);
-            // Allows to compare if two function pointers are the same
-            // This works because there is no notion of Generics for `rounded_square`
-            if command as usize == Shape::rounded_square as usize {
-                paths[i].push_str(&format!(
-                    r##"" stroke-width=".3" stroke-linejoin="round" stroke="{}"##,
-                    command_color.to_str()
-                ));
+        paths
+            .into_iter()
+            .zip(rendered)
+            .zip(&drawn)
+            .map(|((mut paths, rendered), drawn)| {
+                if commands.iter().any(|&command| merged(command)) {
+                    let merged_path = match self.path_mode {
+                        PathMode::Runs => self.runs(drawn, qr.size),
+                        _ => self.outline(drawn, qr.size),
+                    };
+                    for (i, &command) in commands.iter().enumerate() {
+                        if merged(command) {
+                            paths[i].push_str(&merged_path);
+                        }
+                    }
+                }
+
+                let mut layers: Vec<Layer> = commands
+                    .iter()
+                    .zip(paths)
+                    .enumerate()
+                    .map(|(i, (&command, path))| Layer {
+                        path,
+                        paint: command_colors[i]
+                            .as_ref()
+                            .unwrap_or(&self.dot_color)
+                            .clone(),
+                        // Allows to compare if two function pointers are the same
+                        // This works because there is no notion of Generics for `rounded_square`
+                        stroke: command as usize
+                            == Shape::rounded_square as ModuleFunction as usize,
+                    })
+                    .collect();
+
+                for (path, colored) in rendered {
+                    layers.push(Layer {
+                        path,
+                        paint: self.dot_color.clone(),
+                        stroke: false,
+                    });
+                    for (color, path) in colored {
+                        layers.push(Layer {
+                            path,
+                            paint: Paint::Color(Color(color)),
+                            stroke: false,
+                        });
+                    }
+                }
+
+                layers.retain(|layer| !layer.path.is_empty());
+                layers
+            })
+            .collect()
+    }
+
+    /// Layers as `<path>` elements, with the module classes
+    fn layers_to_str<'a>(
+        &self,
+        layers: impl Iterator<Item = &'a Layer>,
+        defs: &mut Defs,
+    ) -> String {
+        // Only stroked layers get a stroke in dark mode
+        let (class, stroked) = (self.class("module"), self.class("module stroked"));
+        let mut out = String::new();
+        for layer in layers {
+            let class = if layer.stroke { &stroked } else { &class };
+            out.push_str(&layer.to_str(defs, class));
+        }
+        out
+    }
+
+    /// Horizontal runs of drawn modules as rectangles, after the first one
+    /// each rectangle starts relative to the previous one
+    fn runs(&self, drawn: &[bool], size: usize) -> String {
+        let mut out = String::new();
+        let mut previous: Option<(usize, usize)> = None;
+
+        for y in 0..size {
+            let mut x = 0;
+            while x < size {
+                if !drawn[y * size + x] {
+                    x += 1;
+                    continue;
+                }
+
+                let start = x;
+                while x < size && drawn[y * size + x] {
+                    x += 1;
+                }
+                let len = x - start;
+
+                match previous {
+                    None => out.push_str(&format!("M{},{}", start + self.margin, y + self.margin)),
+                    Some((px, py)) => {
+                        out.push_str(&format!("m{},{}", start as isize - px as isize, y - py))
+                    }
+                }
+                out.push_str(&format!("h{len}v1h-{len}z"));
+                previous = Some((start, y));
+            }
+        }
+
+        out
+    }
+
+    /// Outlines of the drawn modules, with relative commands
+    fn outline(&self, drawn: &[bool], size: usize) -> String {
+        let mut out = String::new();
+        let mut previous: Option<(usize, usize)> = None;
+
+        for polygon in contour::trace(drawn, size, size) {
+            let (x, y) = polygon[0];
+            match previous {
+                None => out.push_str(&format!("M{},{}", x + self.margin, y + self.margin)),
+                Some((px, py)) => out.push_str(&format!(
+                    "m{},{}",
+                    x as isize - px as isize,
+                    y as isize - py as isize
+                )),
+            }
+            previous = Some((x, y));
+
+            // The last side is drawn by `z`
+            for window in polygon.windows(2) {
+                let ((x1, y1), (x2, y2)) = (window[0], window[1]);
+                if y1 == y2 {
+                    out.push_str(&format!("h{}", x2 as isize - x1 as isize));
+                } else {
+                    out.push_str(&format!("v{}", y2 as isize - y1 as isize));
+                }
+            }
+            out.push('z');
+        }
+
+        out
+    }
+
+    /// Top left corners of the alignment patterns
+    fn alignment_positions(qr: &QRCode) -> Vec<(usize, usize)> {
+        let is_alignment = |y: usize, x: usize| qr[y][x].module_type() == ModuleType::Alignment;
+
+        let mut positions = Vec::new();
+        for y in 0..qr.size {
+            for x in 0..qr.size {
+                let top = y == 0 || !is_alignment(y - 1, x);
+                let left = x == 0 || !is_alignment(y, x - 1);
+                if is_alignment(y, x) && top && left {
+                    positions.push((y, x));
+                }
+            }
+        }
+
+        positions
+    }
+
+    /// Finder and alignment patterns drawn as a whole, if they are styled,
+    /// a layer for their frames then one for their balls, with their type
+    fn pattern_layers(&self, qr: &QRCode) -> Vec<(ModuleType, Layer)> {
+        let n = qr.size;
+        let finders = vec![(0, 0), (0, n - 7), (n - 7, 0)];
+        let alignments = Self::alignment_positions(qr);
+        let mut layers = Vec::new();
+
+        for (style, positions, size, module_type) in [
+            (&self.finder, finders, 7, ModuleType::FinderPattern),
+            (&self.alignment, alignments, 5, ModuleType::Alignment),
+        ] {
+            let style = match style {
+                Some(style) if !positions.is_empty() => style,
+                _ => continue,
+            };
+
+            let mut frames = String::new();
+            let mut balls = String::new();
+            for (y, x) in positions {
+                let (y, x) = (y + self.margin, x + self.margin);
+                frames.push_str(&(style.frame)(y, x, size));
+                balls.push_str(&(style.ball)(y + 2, x + 2, size - 4));
             }
 
-            paths[i].push_str(&format!(r#"" fill="{}"/>"#, command_color.to_str()));
+            for (path, color) in [(frames, &style.frame_color), (balls, &style.ball_color)] {
+                let layer = Layer {
+                    path,
+                    paint: color.as_ref().unwrap_or(&self.dot_color).clone(),
+                    stroke: false,
+                };
+                layers.push((module_type, layer));
+            }
         }
 
-        paths.join("")
+        layers
+    }
+
+    /// Margin, in modules
+    #[cfg(any(feature = "pdf", feature = "eps"))]
+    pub(crate) const fn margin_size(&self) -> usize {
+        self.margin
+    }
+
+    /// Paint of the background
+    #[cfg(any(feature = "pdf", feature = "eps"))]
+    pub(crate) const fn background(&self) -> &Paint {
+        &self.background_color
+    }
+
+    /// Frame around a code of width `size`, margin included, and its layers
+    #[cfg(any(feature = "pdf", feature = "eps"))]
+    pub(crate) fn frame_layers(&self, size: f64) -> Option<(FrameLayout, Vec<Layer>)> {
+        let layout = self.frame.as_ref()?.layout(size);
+        let mut layers = vec![Layer {
+            path: format!(
+                "M0,0h{}v{}h-{}z",
+                coord(layout.width),
+                coord(layout.height),
+                coord(layout.width)
+            ),
+            paint: self.background_color.clone(),
+            stroke: false,
+        }];
+        for (path, on_background) in &layout.paths {
+            let paint = if *on_background {
+                &self.background_color
+            } else {
+                &self.dot_color
+            };
+            layers.push(Layer {
+                path: path.clone(),
+                paint: paint.clone(),
+                stroke: false,
+            });
+        }
+        Some((layout, layers))
+    }
+
+    /// Modules then styled finder & alignment patterns, as they are drawn over
+    /// the background
+    #[cfg(any(feature = "pdf", feature = "eps"))]
+    pub(crate) fn layers(&self, qr: &QRCode) -> Vec<Layer> {
+        let mut layers = self.module_layers(qr, false).remove(0);
+        layers.extend(self.pattern_layers(qr).into_iter().map(|(_, layer)| layer));
+        layers
+    }
+
+    /// Embedded image and its backgrounds
+    #[cfg(any(feature = "pdf", feature = "eps"))]
+    pub(crate) fn placed_image(&self, qr: &QRCode) -> Option<PlacedImage<'_>> {
+        let (border_size, (x, y), image_size) = self.image_bounds(qr)?;
+        let mut layers = Vec::new();
+
+        let square = format!(
+            "M{},{}h{2}v{2}h-{2}z",
+            coord(x),
+            coord(y),
+            coord(border_size)
+        );
+        if !self.clear_under_image {
+            layers.push(Layer {
+                path: square.clone(),
+                paint: self.background_color.clone(),
+                stroke: false,
+            });
+        }
+
+        let path = match self.image_background_shape {
+            ImageBackgroundShape::Square => square,
+            ImageBackgroundShape::RoundedSquare => {
+                super::rounded_square(x, y, border_size, 1f64, true)
+            }
+            ImageBackgroundShape::Circle => {
+                let r = border_size / 2f64;
+                super::circle(x + r, y + r, r, true)
+            }
+        };
+        layers.push(Layer {
+            path,
+            paint: Paint::Color(self.image_background_color.clone()),
+            stroke: false,
+        });
+
+        let offset = (border_size - image_size) / 2f64;
+        Some(PlacedImage {
+            backgrounds: layers,
+            source: self.image.as_deref()?,
+            x: x + offset,
+            y: y + offset,
+            size: image_size,
+        })
+    }
+
+    /// Updates how square modules are written, [`PathMode::Runs`] and
+    /// [`PathMode::Outline`] give much smaller svgs (default: `PathMode::Modules`)
+    pub fn path_mode(&mut self, path_mode: PathMode) -> &mut Self {
+        self.path_mode = path_mode;
+        self
     }
 
     /// Return a string containing the svg for a qr code
     pub fn to_str(&self, qr: &QRCode) -> String {
         let n = qr.size;
 
-        let mut out = String::with_capacity(11 * n * n / 2);
-        out.push_str(&format!(
-            r#"<svg viewBox="0 0 {0} {0}" xmlns="http://www.w3.org/2000/svg">"#,
-            self.margin * 2 + n
-        ));
+        let mut defs = Defs::new(self.margin * 2 + n, &self.id_prefix);
 
-        out.push_str(&format!(
-            r#"<rect width="{0}px" height="{0}px" fill="{1}"/>"#,
+        let mut body = String::with_capacity(11 * n * n / 2);
+        body.push_str(&format!(
+            r#"<rect width="{0}px" height="{0}px"{1} fill="{2}"/>"#,
             self.margin * 2 + n,
-            self.background_color.to_str()
+            self.class("background"),
+            defs.fill(&self.background_color)
         ));
 
-        out.push_str(&self.path(qr));
-        out.push_str(&self.image(n));
+        let patterns = self.pattern_layers(qr);
+        if self.group_by_type {
+            let groups = self.module_layers(qr, true);
+            for ((module_type, name), layers) in GROUPS.iter().zip(&groups) {
+                let patterns = patterns
+                    .iter()
+                    .filter(|(pattern_type, _)| pattern_type == module_type)
+                    .map(|(_, layer)| layer);
+                let elements = self.layers_to_str(layers.iter().chain(patterns), &mut defs);
+                if elements.is_empty() {
+                    continue;
+                }
+
+                body.push_str(&format!(r#"<g class="{}{name}">"#, self.class_prefix));
+                body.push_str(&elements);
+                body.push_str("</g>");
+            }
+        } else {
+            let layers = self.module_layers(qr, false).remove(0);
+            let patterns = patterns.iter().map(|(_, layer)| layer);
+            body.push_str(&self.layers_to_str(layers.iter().chain(patterns), &mut defs));
+        }
+        body.push_str(&self.image(qr, &mut defs));
+
+        let (width, height) = match &self.frame {
+            Some(frame) => {
+                let module = format!(
+                    r#"{} fill="{}""#,
+                    self.class("module"),
+                    defs.fill(&self.dot_color)
+                );
+                let background = format!(
+                    r#"{} fill="{}""#,
+                    self.class("background"),
+                    defs.fill(&self.background_color)
+                );
+                let layout = frame.layout((self.margin * 2 + n) as f64);
+                let elements: String = layout
+                    .paths
+                    .iter()
+                    .map(|(path, on_background)| {
+                        let fill = if *on_background { &background } else { &module };
+                        format!(r#"<path d="{path}"{fill}/>"#)
+                    })
+                    .collect();
+
+                body = format!(
+                    r#"<rect width="{}" height="{}"{background}/>{elements}<g transform="translate({},{})">{body}</g>"#,
+                    coord(layout.width),
+                    coord(layout.height),
+                    coord(layout.offset.0),
+                    coord(layout.offset.1),
+                );
+                (coord(layout.width), coord(layout.height))
+            }
+            None => {
+                let size = (self.margin * 2 + n).to_string();
+                (size.clone(), size)
+            }
+        };
+
+        let mut out = String::with_capacity(body.len() + 200);
+        out.push_str(&format!(
+            r#"<svg viewBox="0 0 {width} {height}" xmlns="http://www.w3.org/2000/svg"{}>"#,
+            self.accessibility_attributes()
+        ));
+        out.push_str(&self.accessibility_elements());
+        out.push_str(&self.style());
+        out.push_str(&defs.to_str());
+        out.push_str(&body);
 
         out.push_str("</svg>");
         out
     }
 
+    /// ` class="..."` attribute, only if classes are needed to group or style.
+    /// `names` are separated by spaces, each one is prefixed
+    fn class(&self, names: &str) -> String {
+        if !self.group_by_type && self.dark_mode.is_none() {
+            return String::new();
+        }
+
+        let names: Vec<String> = names
+            .split(' ')
+            .map(|name| format!("{}{name}", self.class_prefix))
+            .collect();
+        format!(r#" class="{}""#, names.join(" "))
+    }
+
+    /// `role` & `aria-labelledby` attributes of the svg element
+    fn accessibility_attributes(&self) -> String {
+        let mut ids = Vec::new();
+        if self.title.is_some() {
+            ids.push(format!("{}title", self.id_prefix));
+        }
+        if self.description.is_some() {
+            ids.push(format!("{}desc", self.id_prefix));
+        }
+        if ids.is_empty() {
+            return String::new();
+        }
+
+        format!(r#" role="img" aria-labelledby="{}""#, ids.join(" "))
+    }
+
+    /// `<title>` & `<desc>` elements
+    fn accessibility_elements(&self) -> String {
+        let mut out = String::new();
+        if let Some(title) = &self.title {
+            out.push_str(&format!(
+                r#"<title id="{}title">{}</title>"#,
+                self.id_prefix,
+                escape(title)
+            ));
+        }
+        if let Some(description) = &self.description {
+            out.push_str(&format!(
+                r#"<desc id="{}desc">{}</desc>"#,
+                self.id_prefix,
+                escape(description)
+            ));
+        }
+        out
+    }
+
+    /// `<style>` restyling the svg for dark color schemes
+    fn style(&self) -> String {
+        let (module_color, background_color) = match &self.dark_mode {
+            Some(colors) => colors,
+            None => return String::new(),
+        };
+
+        format!(
+            "<style>@media (prefers-color-scheme: dark) {{ .{0}module {{ fill: {1}; }} .{0}stroked {{ stroke: {1}; }} .{0}background {{ fill: {2}; }} }}</style>",
+            self.class_prefix,
+            module_color.to_str(),
+            background_color.to_str()
+        )
+    }
+
+    /// Groups modules by [`ModuleType`] in `<g class="{prefix}finder">`,
+    /// `timing`, `data`, ... (default: false)
+    pub fn group_by_type(&mut self, group_by_type: bool) -> &mut Self {
+        self.group_by_type = group_by_type;
+        self
+    }
+
+    /// Adds an accessible `<title>`, the svg gets the `img` role
+    pub fn title<S: Into<String>>(&mut self, title: S) -> &mut Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Adds an accessible `<desc>`, the svg gets the `img` role
+    pub fn description<S: Into<String>>(&mut self, description: S) -> &mut Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Updates the prefix of generated ids, useful when several svgs are
+    /// inlined in the same page (default: `fqr-`). Only ASCII letters, digits,
+    /// `-` and `_` are kept.
+    pub fn id_prefix<S: Into<String>>(&mut self, id_prefix: S) -> &mut Self {
+        self.id_prefix = prefix(id_prefix.into());
+        self
+    }
+
+    /// Updates the prefix of generated classes (default: `fqr-`). Only ASCII
+    /// letters, digits, `-` and `_` are kept.
+    pub fn class_prefix<S: Into<String>>(&mut self, class_prefix: S) -> &mut Self {
+        self.class_prefix = prefix(class_prefix.into());
+        self
+    }
+
+    /// Embeds a `<style>` using these colors when the user prefers a dark
+    /// color scheme. Modules get the `{prefix}module` class, rounded squares
+    /// also the `{prefix}stroked` class, and the background the
+    /// `{prefix}background` class, so pages can restyle them too.
+    pub fn dark_mode<C: Into<Color>>(&mut self, module_color: C, background_color: C) -> &mut Self {
+        self.dark_mode = Some((module_color.into(), background_color.into()));
+        self
+    }
+
+    /// Embeds an image from its raw bytes as a `data:` URI.
+    ///
+    /// PNG, JPEG, SVG and WebP images are supported, the format is detected
+    /// from the content.
+    ///
+    /// # Errors
+    /// - `SvgError::SvgError` if the image format is not supported
+    pub fn image_bytes(&mut self, bytes: &[u8]) -> Result<&mut Self, SvgError> {
+        self.image = Some(embed::data_uri(bytes)?);
+        Ok(self)
+    }
+
+    /// Embeds a local image file as a `data:` URI, so the svg does not depend
+    /// on the file anymore, see [`SvgBuilder::image_bytes`].
+    ///
+    /// # Errors
+    /// - `SvgError::IoError` if the file cannot be read
+    /// - `SvgError::SvgError` if the image format is not supported
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn image_file<P: AsRef<std::path::Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<&mut Self, SvgError> {
+        let bytes = std::fs::read(path).map_err(SvgError::IoError)?;
+        self.image_bytes(&bytes)
+    }
+
+    /// Embeds an image from its raw bytes, PNG and JPEG images are re-encoded
+    /// as PNG no larger than `max_size` pixels. SVG images are kept as is.
+    ///
+    /// # Errors
+    /// - `SvgError::SvgError` if the image cannot be decoded or re-encoded
+    #[cfg(feature = "image")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+    pub fn image_bytes_downscaled(
+        &mut self,
+        bytes: &[u8],
+        max_size: u32,
+    ) -> Result<&mut Self, SvgError> {
+        let bytes = embed::downscale(bytes, max_size)?;
+        self.image_bytes(&bytes)
+    }
+
+    /// Embeds a local image file, downscaled, see [`SvgBuilder::image_bytes_downscaled`]
+    ///
+    /// # Errors
+    /// - `SvgError::IoError` if the file cannot be read
+    /// - `SvgError::SvgError` if the image cannot be decoded or re-encoded
+    #[cfg(all(feature = "image", not(target_arch = "wasm32")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+    pub fn image_file_downscaled<P: AsRef<std::path::Path>>(
+        &mut self,
+        path: P,
+        max_size: u32,
+    ) -> Result<&mut Self, SvgError> {
+        let bytes = std::fs::read(path).map_err(SvgError::IoError)?;
+        self.image_bytes_downscaled(&bytes, max_size)
+    }
+
     /// Saves the svg for a qr code to a file
+    ///
+    /// # Errors
+    /// - `SvgError::IoError` if the file cannot be written
     #[cfg(not(target_arch = "wasm32"))]
     pub fn to_file(&self, qr: &QRCode, file: &str) -> Result<(), SvgError> {
         use std::fs::File;
@@ -335,3 +1264,10 @@ impl SvgBuilder {
         Ok(())
     }
 }
+
+/// Escapes text for xml content
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,457 @@
+//! Converts [`QRCode`] to text for terminals, with block characters or ASCII
+//!
+//! ```rust
+//! use fast_qr::convert::terminal::{AnsiColor, Charset, Polarity, TerminalBuilder};
+//! use fast_qr::qr::QRBuilder;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .build()
+//!     .unwrap();
+//!
+//! // Readable on any theme: black modules on a white background
+//! let text = TerminalBuilder::default()
+//!     .margin(2)
+//!     .polarity(Polarity::Normal)
+//!     .charset(Charset::HalfBlocks)
+//!     .foreground(AnsiColor::Ansi16(0))
+//!     .background(AnsiColor::Ansi16(15))
+//!     .to_str(&qrcode);
+//! ```
+
+use crate::QRCode;
+
+#[cfg(feature = "raster")]
+use super::base64;
+use super::dots;
+#[cfg(feature = "raster")]
+use super::raster::{BitDepth, Bitmap, RasterBuilder};
+
+/// Characters modules are drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    /// `█`, two characters per module so modules are about square
+    FullBlocks,
+    /// `▀`, `▄` and `█`, one character for two modules on top of each other
+    /// (default)
+    HalfBlocks,
+    /// `▘`, `▚`, `▙`..., one character for 2x2 modules
+    Quadrants,
+    /// Sextants of Unicode 13, one character for 2x3 modules. Smallest
+    /// output, but not every font has them
+    Sextants,
+    /// `#`, two characters per module, for terminals without Unicode
+    Ascii,
+}
+
+impl Charset {
+    /// Width & height of a character, in modules
+    const fn cell(self) -> (usize, usize) {
+        match self {
+            Charset::FullBlocks | Charset::Ascii => (1, 1),
+            Charset::HalfBlocks => (1, 2),
+            Charset::Quadrants => (2, 2),
+            Charset::Sextants => (2, 3),
+        }
+    }
+
+    /// Characters of a cell, bit `y * width + x` is set if the module at
+    /// `(x, y)` of the cell is drawn
+    fn push_glyph(self, bits: u8, out: &mut String) {
+        const HALF_BLOCKS: [char; 4] = [' ', '▀', '▄', '█'];
+        const QUADRANTS: [char; 16] = [
+            ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+        ];
+
+        match self {
+            Charset::FullBlocks => out.push_str(if bits == 1 { "██" } else { "  " }),
+            Charset::Ascii => out.push_str(if bits == 1 { "##" } else { "  " }),
+            Charset::HalfBlocks => out.push(HALF_BLOCKS[usize::from(bits)]),
+            Charset::Quadrants => out.push(QUADRANTS[usize::from(bits)]),
+            Charset::Sextants => out.push(match bits {
+                0 => ' ',
+                // Left & right halves are the existing half blocks
+                0b01_0101 => '▌',
+                0b10_1010 => '▐',
+                0b11_1111 => '█',
+                _ => {
+                    let skipped = u32::from(bits > 0b01_0101) + u32::from(bits > 0b10_1010);
+                    char::from_u32(0x1fb00 + u32::from(bits) - 1 - skipped).unwrap_or(' ')
+                }
+            }),
+        }
+    }
+}
+
+/// Which modules are drawn with characters, the others are left to the
+/// background
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Polarity {
+    /// Dark modules are drawn, for terminals with a light background
+    Normal,
+    /// Light modules are drawn, for terminals with a dark background (default)
+    Inverted,
+}
+
+/// Color of an ANSI escape sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnsiColor {
+    /// One of the 16 basic colors, 8 to 15 are the bright ones
+    Ansi16(u8),
+    /// One of the 256 colors of the extended palette
+    Ansi256(u8),
+    /// 24 bits color, not supported by every terminal
+    TrueColor(u8, u8, u8),
+}
+
+impl AnsiColor {
+    /// Parameters of the Select Graphic Rendition sequence
+    fn parameters(self, background: bool) -> String {
+        match self {
+            AnsiColor::Ansi16(color) => {
+                let color = color.min(15);
+                let base = match (background, color < 8) {
+                    (false, true) => 30,
+                    (false, false) => 90 - 8,
+                    (true, true) => 40,
+                    (true, false) => 100 - 8,
+                };
+                (base + color).to_string()
+            }
+            AnsiColor::Ansi256(color) => format!("{};5;{color}", 38 + 10 * u8::from(background)),
+            AnsiColor::TrueColor(r, g, b) => {
+                format!("{};2;{r};{g};{b}", 38 + 10 * u8::from(background))
+            }
+        }
+    }
+}
+
+/// How the qr code is shown, bitmaps need a terminal supporting the protocol
+#[cfg(feature = "raster")]
+#[cfg_attr(docsrs, doc(cfg(feature = "raster")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// Sixel graphics, supported by xterm, foot, mlterm, WezTerm...
+    Sixel,
+    /// Kitty graphics protocol, supported by kitty, WezTerm, Ghostty...
+    Kitty,
+    /// iTerm2 inline images, supported by iTerm2, WezTerm...
+    ITerm2,
+    /// Characters of the [`Charset`], supported everywhere
+    Text,
+}
+
+#[cfg(feature = "raster")]
+impl Protocol {
+    /// Guesses if the terminal supports the protocol from environment
+    /// variables, terminals cannot all be detected without querying them
+    #[must_use]
+    pub fn is_supported(self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let var = |name: &str| std::env::var(name).unwrap_or_default();
+            let (term, program) = (var("TERM"), var("TERM_PROGRAM"));
+            match self {
+                Protocol::Sixel => {
+                    term.contains("sixel")
+                        || ["foot", "mlterm", "contour"]
+                            .iter()
+                            .any(|name| term.starts_with(name))
+                        || program == "WezTerm"
+                }
+                Protocol::Kitty => {
+                    term == "xterm-kitty"
+                        || term == "xterm-ghostty"
+                        || std::env::var_os("KITTY_WINDOW_ID").is_some()
+                        || program == "WezTerm"
+                }
+                Protocol::ITerm2 => {
+                    program == "iTerm.app" || program == "WezTerm" || var("LC_TERMINAL") == "iTerm2"
+                }
+                Protocol::Text => true,
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            self == Protocol::Text
+        }
+    }
+}
+
+/// Builder for terminal output, can set margin, polarity, charset and colors
+///
+/// The default is what [`QRCode::to_str`] prints: half blocks, a margin of 1
+/// and light modules drawn for dark terminals.
+pub struct TerminalBuilder {
+    /// The margin, in modules, default is 1
+    margin: usize,
+    polarity: Polarity,
+    charset: Charset,
+    /// Color of the characters, default is the one of the terminal
+    foreground: Option<AnsiColor>,
+    /// Color behind the characters, default is the one of the terminal
+    background: Option<AnsiColor>,
+    /// Protocols tried in order, default is text only
+    #[cfg(feature = "raster")]
+    protocols: Vec<Protocol>,
+    /// Requested width of bitmaps in pixels, default is 4 pixels per module
+    #[cfg(feature = "raster")]
+    image_width: Option<usize>,
+}
+
+impl Default for TerminalBuilder {
+    fn default() -> Self {
+        TerminalBuilder {
+            margin: 1,
+            polarity: Polarity::Inverted,
+            charset: Charset::HalfBlocks,
+            foreground: None,
+            background: None,
+            #[cfg(feature = "raster")]
+            protocols: vec![Protocol::Text],
+            #[cfg(feature = "raster")]
+            image_width: None,
+        }
+    }
+}
+
+impl TerminalBuilder {
+    /// Updates margin, in modules (default: 1)
+    pub fn margin(&mut self, margin: usize) -> &mut Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Updates which modules are drawn (default: `Polarity::Inverted`)
+    pub fn polarity(&mut self, polarity: Polarity) -> &mut Self {
+        self.polarity = polarity;
+        self
+    }
+
+    /// Updates the characters modules are drawn with (default: `Charset::HalfBlocks`)
+    pub fn charset(&mut self, charset: Charset) -> &mut Self {
+        self.charset = charset;
+        self
+    }
+
+    /// Updates the color of the characters (default: the terminal's)
+    pub fn foreground(&mut self, foreground: AnsiColor) -> &mut Self {
+        self.foreground = Some(foreground);
+        self
+    }
+
+    /// Updates the color behind the characters (default: the terminal's)
+    pub fn background(&mut self, background: AnsiColor) -> &mut Self {
+        self.background = Some(background);
+        self
+    }
+
+    /// Updates the protocols tried in order, the first one the terminal
+    /// supports is used, [`Protocol::Text`] if none is (default: text only)
+    #[cfg(feature = "raster")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "raster")))]
+    pub fn protocols(&mut self, protocols: &[Protocol]) -> &mut Self {
+        self.protocols = protocols.to_vec();
+        self
+    }
+
+    /// Updates the requested width of bitmaps in pixels. Modules are a whole
+    /// number of pixels, so the bitmap may be a bit smaller (default: 4
+    /// pixels per module)
+    #[cfg(feature = "raster")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "raster")))]
+    pub fn image_width(&mut self, pixels: usize) -> &mut Self {
+        self.image_width = Some(pixels);
+        self
+    }
+
+    /// Return a string containing the qr code, with the first supported
+    /// protocol. As text, lines are separated by `\n` and colored lines are
+    /// reset at their end
+    #[must_use]
+    pub fn to_str(&self, qr: &QRCode) -> String {
+        #[cfg(feature = "raster")]
+        {
+            let protocol = self
+                .protocols
+                .iter()
+                .copied()
+                .find(|protocol| protocol.is_supported())
+                .unwrap_or(Protocol::Text);
+            self.to_protocol(qr, protocol)
+        }
+        #[cfg(not(feature = "raster"))]
+        {
+            self.text(qr)
+        }
+    }
+
+    /// Return a string containing the qr code with `protocol`, whether the
+    /// terminal supports it or not. Bitmaps are black on white, polarity and
+    /// colors only apply to text
+    #[cfg(feature = "raster")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "raster")))]
+    #[must_use]
+    pub fn to_protocol(&self, qr: &QRCode, protocol: Protocol) -> String {
+        if protocol == Protocol::Text {
+            return self.text(qr);
+        }
+
+        let modules = qr.size + 2 * self.margin;
+        let scale = self.image_width.map_or(4, |width| (width / modules).max(1));
+        let bitmap = RasterBuilder::default()
+            .margin(self.margin)
+            .scale(scale)
+            .to_bitmap(qr);
+
+        match protocol {
+            Protocol::Sixel => sixel(&bitmap),
+            Protocol::Kitty => kitty(&bitmap.to_png(BitDepth::One)),
+            Protocol::ITerm2 => iterm2(&bitmap),
+            Protocol::Text => unreachable!(),
+        }
+    }
+
+    /// Width & height of the text, in terminal cells
+    #[cfg(feature = "ratatui")]
+    pub(crate) fn text_size(&self, qr: &QRCode) -> (usize, usize) {
+        let (cell_width, cell_height) = self.charset.cell();
+        let size = qr.size + 2 * self.margin;
+        let characters = match self.charset {
+            Charset::FullBlocks | Charset::Ascii => 2,
+            _ => 1,
+        };
+        (
+            (size + cell_width - 1) / cell_width * characters,
+            (size + cell_height - 1) / cell_height,
+        )
+    }
+
+    /// Characters of the charset
+    pub(crate) fn text(&self, qr: &QRCode) -> String {
+        let (cell_width, cell_height) = self.charset.cell();
+        let size = qr.size + 2 * self.margin;
+        // Rows are padded at the top and columns at the right to whole
+        // characters, padding is left to the background
+        let top = (cell_height - size % cell_height) % cell_height;
+        let columns = (size + cell_width - 1) / cell_width;
+        let rows = (size + top) / cell_height;
+
+        let drawn = |x: usize, y: usize| {
+            if y < top || x >= size {
+                return false;
+            }
+            dots::dark(qr, self.margin, x, y - top) == (self.polarity == Polarity::Normal)
+        };
+
+        let mut sgr = Vec::new();
+        if let Some(color) = self.foreground {
+            sgr.push(color.parameters(false));
+        }
+        if let Some(color) = self.background {
+            sgr.push(color.parameters(true));
+        }
+        let (prefix, suffix) = if sgr.is_empty() {
+            (String::new(), "")
+        } else {
+            (format!("\x1b[{}m", sgr.join(";")), "\x1b[0m")
+        };
+
+        let mut lines = Vec::with_capacity(rows);
+        for row in 0..rows {
+            let mut line = prefix.clone();
+            for column in 0..columns {
+                let mut bits = 0u8;
+                for dy in 0..cell_height {
+                    for dx in 0..cell_width {
+                        let (x, y) = (column * cell_width + dx, row * cell_height + dy);
+                        bits |= u8::from(drawn(x, y)) << (dy * cell_width + dx);
+                    }
+                }
+                self.charset.push_glyph(bits, &mut line);
+            }
+            line.push_str(suffix);
+            lines.push(line);
+        }
+
+        lines.join("\n")
+    }
+
+    /// Prints the qr code to the standard output
+    pub fn print(&self, qr: &QRCode) {
+        println!("{}", self.to_str(qr));
+    }
+}
+
+/// Sixel image with two color registers, 0 for light and 1 for dark pixels
+#[cfg(feature = "raster")]
+fn sixel(bitmap: &Bitmap) -> String {
+    // Raster attributes give the size, colors are in percents
+    let mut out = format!(
+        "\x1bPq\"1;1;{};{}#0;2;100;100;100#1;2;0;0;0",
+        bitmap.width, bitmap.height
+    );
+
+    // Each character is a column of 6 pixels, repeated with `!{count}`
+    let push_run = |out: &mut String, c: char, count: usize| match count {
+        0 => (),
+        1..=3 => out.extend(core::iter::repeat(c).take(count)),
+        _ => out.push_str(&format!("!{count}{c}")),
+    };
+
+    for band in (0..bitmap.height).step_by(6) {
+        for (register, dark) in [(0, false), (1, true)] {
+            out.push_str(&format!("#{register}"));
+            let (mut previous, mut count) = ('?', 0);
+            for x in 0..bitmap.width {
+                let bits = (0..6)
+                    .filter(|dy| band + dy < bitmap.height)
+                    .filter(|dy| (bitmap.get(x, band + dy) < 128) == dark)
+                    .fold(0u8, |bits, dy| bits | 1 << dy);
+                let c = char::from(0x3f + bits);
+                if c != previous {
+                    push_run(&mut out, previous, count);
+                    previous = c;
+                    count = 0;
+                }
+                count += 1;
+            }
+            push_run(&mut out, previous, count);
+            // Back to the start of the band for the next color
+            out.push(if dark { '-' } else { '$' });
+        }
+    }
+
+    out.push_str("\x1b\\");
+    out
+}
+
+/// Kitty graphics command showing a PNG, in chunks of 4096 bytes
+#[cfg(feature = "raster")]
+fn kitty(png: &[u8]) -> String {
+    let data = base64(png);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+
+    let mut out = String::with_capacity(data.len() + chunks.len() * 16 + 16);
+    for (i, chunk) in chunks.iter().enumerate() {
+        // Transmits & displays the image, without responses
+        let keys = if i == 0 { "a=T,f=100,q=2," } else { "" };
+        let more = u8::from(i + 1 < chunks.len());
+        out.push_str(&format!("\x1b_G{keys}m={more};"));
+        out.push_str(core::str::from_utf8(chunk).unwrap_or_default());
+        out.push_str("\x1b\\");
+    }
+    out
+}
+
+/// iTerm2 inline image, shown at its size in pixels
+#[cfg(feature = "raster")]
+fn iterm2(bitmap: &Bitmap) -> String {
+    let png = bitmap.to_png(BitDepth::One);
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={}px;height={}px;preserveAspectRatio=1:{}\x07",
+        png.len(),
+        bitmap.width,
+        bitmap.height,
+        base64(&png)
+    )
+}
@@ -0,0 +1,143 @@
+//! Renders [`QRCode`] in `ratatui` layouts
+//!
+//! [`QrWidget`] uses the first of its charsets the area fits, densest first by
+//! default, centers the code and keeps its margin. When no charset fits, a
+//! short message is drawn instead, see [`QrWidget::charset_for`].
+//!
+//! ```rust
+//! use fast_qr::convert::tui::QrWidget;
+//! use fast_qr::qr::QRBuilder;
+//! use ratatui::buffer::Buffer;
+//! use ratatui::layout::Rect;
+//! use ratatui::widgets::Widget;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .build()
+//!     .unwrap();
+//!
+//! let area = Rect::new(0, 0, 40, 20);
+//! let mut buffer = Buffer::empty(area);
+//! QrWidget::new(&qrcode).render(area, &mut buffer);
+//! ```
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::Widget;
+
+use crate::QRCode;
+
+use super::terminal::{Charset, Polarity, TerminalBuilder};
+
+/// Drawn when the code does not fit, cut to the width of the area
+const TOO_SMALL: &str = "Area too small for the QR code";
+
+/// Widget drawing a [`QRCode`] with block characters
+pub struct QrWidget<'a> {
+    qr: &'a QRCode,
+    /// The margin, in modules, default is 1
+    margin: usize,
+    /// Charsets tried in order, default is sextants, quadrants, half blocks
+    /// then full blocks
+    charsets: Vec<Charset>,
+    polarity: Polarity,
+    /// Style of the code, default is black on white
+    style: Style,
+}
+
+impl<'a> QrWidget<'a> {
+    /// Creates a widget for `qr`, black on white with a margin of 1
+    pub fn new(qr: &'a QRCode) -> Self {
+        QrWidget {
+            qr,
+            margin: 1,
+            charsets: vec![
+                Charset::Sextants,
+                Charset::Quadrants,
+                Charset::HalfBlocks,
+                Charset::FullBlocks,
+            ],
+            polarity: Polarity::Normal,
+            style: Style::new().fg(Color::Black).bg(Color::White),
+        }
+    }
+
+    /// Updates margin, in modules, it is kept whatever the area (default: 1)
+    pub fn margin(&mut self, margin: usize) -> &mut Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Updates the charsets tried in order, the first one the area fits is
+    /// used (default: sextants, quadrants, half blocks then full blocks).
+    /// Fonts without sextants or quadrants can start with larger blocks.
+    pub fn charsets(&mut self, charsets: &[Charset]) -> &mut Self {
+        self.charsets = charsets.to_vec();
+        self
+    }
+
+    /// Updates which modules are drawn with the foreground of the style
+    /// (default: `Polarity::Normal`)
+    pub fn polarity(&mut self, polarity: Polarity) -> &mut Self {
+        self.polarity = polarity;
+        self
+    }
+
+    /// Updates the style of the code (default: black on white)
+    pub fn style(&mut self, style: Style) -> &mut Self {
+        self.style = style;
+        self
+    }
+
+    fn terminal(&self, charset: Charset) -> TerminalBuilder {
+        let mut terminal = TerminalBuilder::default();
+        terminal
+            .margin(self.margin)
+            .charset(charset)
+            .polarity(self.polarity);
+        terminal
+    }
+
+    /// Returns the charset the code is drawn with in `area` and its size in
+    /// cells, or `None` if the area is too small for every charset
+    pub fn charset_for(&self, area: Rect) -> Option<(Charset, u16, u16)> {
+        self.charsets.iter().find_map(|&charset| {
+            let (width, height) = self.terminal(charset).text_size(self.qr);
+            let fits = width <= usize::from(area.width) && height <= usize::from(area.height);
+            fits.then(|| (charset, width as u16, height as u16))
+        })
+    }
+}
+
+impl Widget for &QrWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (charset, width, height) = match self.charset_for(area) {
+            Some(fit) => fit,
+            None => {
+                if area.height > 0 {
+                    buf.set_stringn(
+                        area.x,
+                        area.y,
+                        TOO_SMALL,
+                        usize::from(area.width),
+                        Style::new(),
+                    );
+                }
+                return;
+            }
+        };
+
+        let x = area.x + (area.width - width) / 2;
+        let y = area.y + (area.height - height) / 2;
+        let text = self.terminal(charset).text(self.qr);
+        for (i, line) in text.lines().enumerate() {
+            buf.set_string(x, y + i as u16, line, self.style);
+        }
+    }
+}
+
+impl Widget for QrWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        (&self).render(area, buf);
+    }
+}
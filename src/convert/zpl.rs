@@ -0,0 +1,205 @@
+//! Converts [`QRCode`] to ZPL, for Zebra label printers
+//!
+//! The code is either drawn as a `^GF` graphic field, dot for dot, or left
+//! to the printer with its own `^BQ` barcode command.
+//!
+//! ```rust
+//! use fast_qr::convert::zpl::ZplBuilder;
+//! use fast_qr::qr::QRBuilder;
+//!
+//! let qrcode = QRBuilder::new("https://example.com/")
+//!     .build()
+//!     .unwrap();
+//!
+//! // 6 dots per module, 50 dots from the top left of the label
+//! let _zpl = ZplBuilder::default()
+//!     .dots_per_module(6)
+//!     .origin(50, 50)
+//!     .to_str(&qrcode);
+//!
+//! // Same payload and ECL, encoded by the printer
+//! let _zpl = ZplBuilder::default().to_native(&qrcode, b"https://example.com/");
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::io;
+
+use crate::encode::Mode;
+use crate::{QRCode, ECL};
+
+use super::dots;
+
+/// Builder for ZPL labels, can set margin, dots per module, origin and
+/// compression
+pub struct ZplBuilder {
+    /// The margin, in modules, default is 4
+    margin: usize,
+    /// Dots per module, default is 4
+    dots_per_module: usize,
+    /// Field origin in dots, default is `(0, 0)`
+    origin: (usize, usize),
+    /// Compresses the graphic field, default is true
+    compression: bool,
+}
+
+impl Default for ZplBuilder {
+    fn default() -> Self {
+        ZplBuilder {
+            margin: 4,
+            dots_per_module: 4,
+            origin: (0, 0),
+            compression: true,
+        }
+    }
+}
+
+/// Repeat count of the ZPL ASCII compression, `G` to `Y` count 1 to 19 and
+/// `g` to `z` count 20 to 400
+fn repeat(out: &mut String, mut count: usize) {
+    while count >= 20 {
+        let twenties = (count / 20).min(20);
+        out.push((b'g' + twenties as u8 - 1) as char);
+        count -= twenties * 20;
+    }
+    if count > 0 {
+        out.push((b'G' + count as u8 - 1) as char);
+    }
+}
+
+/// ZPL ASCII compression of hexadecimal rows: runs of a digit get a repeat
+/// count, `,` fills the rest of a row with zeros and `:` repeats the
+/// previous row
+fn compress(rows: &[Vec<u8>]) -> String {
+    let mut out = String::new();
+    for (y, row) in rows.iter().enumerate() {
+        if y > 0 && rows[y - 1] == *row {
+            out.push(':');
+            continue;
+        }
+
+        let hex: Vec<u8> = row
+            .iter()
+            .flat_map(|byte| format!("{byte:02X}").into_bytes())
+            .collect();
+        let end = hex
+            .iter()
+            .rposition(|&digit| digit != b'0')
+            .map_or(0, |i| i + 1);
+
+        let mut i = 0;
+        while i < end {
+            let run = hex[i..end]
+                .iter()
+                .take_while(|&&digit| digit == hex[i])
+                .count();
+            if run > 1 {
+                repeat(&mut out, run);
+            }
+            out.push(hex[i] as char);
+            i += run;
+        }
+        if end < hex.len() {
+            out.push(',');
+        }
+    }
+    out
+}
+
+/// Field data escaped for `^FH`: `^`, `~`, `_` and bytes outside printable
+/// ASCII become `_` and two hexadecimal digits
+fn escape(payload: &[u8]) -> String {
+    payload
+        .iter()
+        .map(|&byte| match byte {
+            b'^' | b'~' | b'_' => format!("_{byte:02X}"),
+            0x20..=0x7e => (byte as char).to_string(),
+            _ => format!("_{byte:02X}"),
+        })
+        .collect()
+}
+
+impl ZplBuilder {
+    /// Updates margin, in modules (default: 4)
+    pub fn margin(&mut self, margin: usize) -> &mut Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Updates the number of dots per module, at least 1 (default: 4).
+    /// The native command is limited to 10
+    pub fn dots_per_module(&mut self, dots: usize) -> &mut Self {
+        self.dots_per_module = dots.max(1);
+        self
+    }
+
+    /// Updates the field origin, in dots from the top left of the label
+    /// (default: `(0, 0)`)
+    pub fn origin(&mut self, x: usize, y: usize) -> &mut Self {
+        self.origin = (x, y);
+        self
+    }
+
+    /// Writes the graphic field with ZPL ASCII compression, or as plain
+    /// hexadecimal (default: true)
+    pub fn compression(&mut self, compression: bool) -> &mut Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Return a label drawing the qr code as a `^GF` graphic field
+    pub fn to_str(&self, qr: &QRCode) -> String {
+        let rows = dots::rows(qr, self.margin, self.dots_per_module);
+        let width = rows.first().map_or(0, Vec::len);
+        let total = width * rows.len();
+
+        let data = if self.compression {
+            compress(&rows)
+        } else {
+            rows.iter()
+                .flatten()
+                .map(|byte| format!("{byte:02X}"))
+                .collect()
+        };
+
+        format!(
+            "^XA^FO{},{}^GFA,{total},{total},{width},{data}^FS^XZ",
+            self.origin.0, self.origin.1
+        )
+    }
+
+    /// Return a label with the printer's own `^BQ` command, encoding `payload`
+    /// with the ECL and mode of `qr`.
+    ///
+    /// `payload` should be the input the qr code was built from. The printer
+    /// chooses the version and the mask, and draws the quiet zone itself.
+    pub fn to_native(&self, qr: &QRCode, payload: &[u8]) -> String {
+        let ecl = match qr.ecl.unwrap_or(ECL::Q) {
+            ECL::L => 'L',
+            ECL::M => 'M',
+            ECL::Q => 'Q',
+            ECL::H => 'H',
+        };
+        // Manual input, so the printer keeps the same mode
+        let data = match qr.mode.unwrap_or(Mode::Byte) {
+            Mode::Numeric => format!("N{}", escape(payload)),
+            Mode::Alphanumeric => format!("A{}", escape(payload)),
+            Mode::Byte => format!("B{:04}{}", payload.len(), escape(payload)),
+        };
+
+        format!(
+            "^XA^FO{},{}^BQN,2,{}^FH^FD{ecl}M,{data}^FS^XZ",
+            self.origin.0,
+            self.origin.1,
+            self.dots_per_module.min(10)
+        )
+    }
+
+    /// Saves the graphic field label for a qr code to a file
+    ///
+    /// # Errors
+    /// - `io::Error` if the file cannot be written
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn to_file(&self, qr: &QRCode, file: &str) -> io::Result<()> {
+        std::fs::write(file, self.to_str(qr))
+    }
+}
@@ -28,9 +28,13 @@
 //!
 //! ```rust
 //! # use fast_qr::convert::ConvertError;
+//! # #[cfg(feature = "svg")]
 //! use fast_qr::convert::{svg::SvgBuilder, Builder, Shape};
 //! use fast_qr::qr::QRBuilder;
 //!
+//! # #[cfg(not(feature = "svg"))]
+//! # fn main() {}
+//! # #[cfg(feature = "svg")]
 //! # fn main() -> Result<(), ConvertError> {
 //! // QRBuilder::new can fail if content is too big for version,
 //! // please check before unwrapping.
@@ -51,9 +55,13 @@
 //!
 //! ```rust
 //! # use fast_qr::convert::ConvertError;
+//! # #[cfg(feature = "image")]
 //! use fast_qr::convert::{image::ImageBuilder, Builder, Shape};
 //! use fast_qr::qr::QRBuilder;
 //!
+//! # #[cfg(not(feature = "image"))]
+//! # fn main() {}
+//! # #[cfg(feature = "image")]
 //! # fn main() -> Result<(), ConvertError> {
 //! // QRBuilder::new can fail if content is too big for version,
 //! // please check before unwrapping.
@@ -78,6 +86,7 @@ pub use crate::module::{Module, ModuleType};
 pub use crate::qr::{QRBuilder, QRCode};
 pub use crate::version::Version;
 
+pub mod analysis;
 mod compact;
 #[doc(hidden)]
 pub mod datamasking;
@@ -1,13 +1,14 @@
 //! Module `qr` is the entrypoint to start making `QRCodes`
 
+use crate::analysis::{self, Occlusion};
 use crate::module::Module;
 use core::fmt::{Debug, Formatter};
 use core::ops::{Index, IndexMut};
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::convert::terminal::TerminalBuilder;
 use crate::datamasking::Mask;
 use crate::encode::Mode;
-#[cfg(not(target_arch = "wasm32"))]
-use crate::helpers;
 use crate::{encode, Version, ECL};
 
 const QR_MAX_WIDTH: usize = 177;
@@ -60,6 +61,9 @@ pub struct QRCode {
     /// ## Note
     /// Kanji mode is not supported (yet).
     pub mode: Option<Mode>,
+
+    /// Area reserved for a logo, see [`QRCode::logo`]
+    pub(crate) logo: Option<Occlusion>,
 }
 
 impl Debug for QRCode {
@@ -70,6 +74,7 @@ impl Debug for QRCode {
             .field("ecl", &self.ecl)
             .field("mask", &self.mask)
             .field("mode", &self.mode)
+            .field("logo", &self.logo)
             .finish_non_exhaustive()
     }
 }
@@ -85,6 +90,7 @@ impl QRCode {
             ecl: None,
             mask: None,
             mode: None,
+            logo: None,
         }
     }
 }
@@ -109,6 +115,8 @@ pub enum QRCodeError {
     EncodedData,
     /// Specified version too small to contain data
     SpecifiedVersion,
+    /// Logo too big for the `QRCode` to stay readable, at any allowed version and ECL
+    Logo,
 }
 
 // We don't want to use `std::error::Error` on wasm32
@@ -121,6 +129,7 @@ impl std::fmt::Display for QRCodeError {
             QRCodeError::SpecifiedVersion => {
                 f.write_str("Specified version too low to contain data")
             }
+            QRCodeError::Logo => f.write_str("Logo too big to keep the QRCode readable"),
         }
     }
 }
@@ -132,6 +141,7 @@ impl Debug for QRCodeError {
             QRCodeError::SpecifiedVersion => {
                 f.write_str("Specified version too low to contain data")
             }
+            QRCodeError::Logo => f.write_str("Logo too big to keep the QRCode readable"),
         }
     }
 }
@@ -167,20 +177,142 @@ impl QRCode {
         Ok(out)
     }
 
+    /// Creates a new `QRCode` keeping an area free for a logo.
+    ///
+    /// Versions are tried from the smallest, and for each version the strongest
+    /// ECL that can contain the data and keep the logo recoverable is used.
+    ///
+    /// # Errors
+    /// - `QRCodeError::EncodedData` if `input` is too large to be encoded
+    /// - `QRCodeError::SpecifiedVersion` if specified `version` is too small to contain data
+    /// - `QRCodeError::Logo` if no allowed version / ECL keeps the logo recoverable
+    pub(crate) fn with_logo(
+        input: &[u8],
+        ecl: Option<ECL>,
+        v: Option<Version>,
+        mut mask: Option<Mask>,
+        logo: &Logo,
+    ) -> Result<Self, QRCodeError> {
+        use crate::placement::create_matrix;
+
+        const STRONGEST_FIRST: [ECL; 4] = [ECL::H, ECL::Q, ECL::M, ECL::L];
+
+        let mode = encode::best_encoding(input);
+        let levels = match ecl {
+            Some(ref ecl) => core::slice::from_ref(ecl),
+            None => &STRONGEST_FIRST[..],
+        };
+        let versions = match v {
+            Some(version) => version as usize..=version as usize,
+            None => Version::V01 as usize..=Version::V40 as usize,
+        };
+
+        let mut fits = false;
+        for version in versions {
+            let version = Version::from_n(version * 4 + 21);
+
+            for &level in levels {
+                match Version::get(mode, level, input.len()) {
+                    Some(min) if min as usize <= version as usize => fits = true,
+                    _ => continue,
+                }
+
+                let side = logo.size.side(version.size());
+                let placement =
+                    analysis::place_logo(version, level, side, logo.position, logo.safety_margin);
+
+                if let Some(placement) = placement {
+                    let mut out = create_matrix(input, level, mode, version, &mut mask);
+                    out.logo = Some(placement);
+                    return Ok(out);
+                }
+            }
+        }
+
+        if fits {
+            return Err(QRCodeError::Logo);
+        }
+
+        let encodable = levels
+            .iter()
+            .any(|&level| Version::get(mode, level, input.len()).is_some());
+        match v {
+            Some(_) if encodable => Err(QRCodeError::SpecifiedVersion),
+            _ => Err(QRCodeError::EncodedData),
+        }
+    }
+
+    /// Area reserved for a logo, in modules without margin.
+    ///
+    /// `None` if no logo was requested, see [`QRBuilder::logo`].
+    #[must_use]
+    pub const fn logo(&self) -> Option<&Occlusion> {
+        self.logo.as_ref()
+    }
+
     /// Prints the `QRCode` to the terminal
     #[must_use]
     #[cfg(not(target_arch = "wasm32"))]
     pub fn to_str(&self) -> String {
-        helpers::print_matrix_with_margin(self)
+        TerminalBuilder::default().to_str(self)
     }
 
     /// Prints the `QRCode` to the terminal
     #[cfg(not(target_arch = "wasm32"))]
     pub fn print(&self) {
-        println!("{}", helpers::print_matrix_with_margin(self));
+        TerminalBuilder::default().print(self);
     }
 }
 
+/// Size of the area reserved for a logo, see [`QRBuilder::logo`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogoSize {
+    /// Width & height of the logo, in modules
+    Modules(usize),
+    /// Percentage of the `QRCode` area (without margin) covered by the logo,
+    /// rounded up to an odd number of modules so it can be centered
+    Percent(f64),
+}
+
+impl LogoSize {
+    /// Width & height of the logo, in modules, for a `QRCode` of width `size`
+    pub(crate) fn side(self, size: usize) -> usize {
+        match self {
+            LogoSize::Modules(side) => side,
+            LogoSize::Percent(percent) => {
+                let side = (percent.max(0f64) / 100f64).sqrt() * size as f64;
+                let side = side.ceil() as usize;
+                side | 1
+            }
+        }
+    }
+}
+
+/// Position of the area reserved for a logo, see [`QRBuilder::logo_position`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogoPosition {
+    /// Centered on the `QRCode` (default)
+    Center,
+    /// Centered on `(x, y)`, in modules without margin
+    At {
+        /// Column of the center
+        x: f64,
+        /// Row of the center
+        y: f64,
+    },
+    /// Picks the position that leaves the most correction capacity,
+    /// favoring the center
+    Auto,
+}
+
+/// Logo requirements given to [`QRBuilder`]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Logo {
+    size: LogoSize,
+    position: LogoPosition,
+    safety_margin: usize,
+}
+
 /// Builder struct, makes it easier to create a [`QRCode`].
 ///
 /// # Example
@@ -202,6 +334,9 @@ pub struct QRBuilder {
     // mode: Option<Mode>,
     version: Option<Version>,
     mask: Option<Mask>,
+    logo: Option<LogoSize>,
+    logo_position: LogoPosition,
+    logo_safety_margin: usize,
 }
 
 impl QRBuilder {
@@ -214,6 +349,9 @@ impl QRBuilder {
             // mode: None,
             version: None,
             ecl: None,
+            logo: None,
+            logo_position: LogoPosition::Center,
+            logo_safety_margin: 0,
         }
     }
 
@@ -240,12 +378,57 @@ impl QRBuilder {
         self
     }
 
+    /// Reserves an area for a logo.
+    ///
+    /// Modules under the logo are considered unreadable: unless forced, the
+    /// version and ECL are picked so every block can still be corrected.
+    /// The reserved area is available in [`QRCode::logo`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use fast_qr::qr::{LogoSize, QRBuilder};
+    ///
+    /// let qr = QRBuilder::new("https://example.com/")
+    ///     .logo(LogoSize::Percent(10f64))
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(qr.logo().is_some());
+    /// ```
+    pub fn logo(&mut self, size: LogoSize) -> &mut Self {
+        self.logo = Some(size);
+        self
+    }
+
+    /// Updates the position of the logo (default: centered)
+    pub fn logo_position(&mut self, position: LogoPosition) -> &mut Self {
+        self.logo_position = position;
+        self
+    }
+
+    /// Number of codewords each block must still be able to correct once the
+    /// logo is placed (default: 0)
+    pub fn logo_safety_margin(&mut self, codewords: usize) -> &mut Self {
+        self.logo_safety_margin = codewords;
+        self
+    }
+
     /// Computes a [`QRCode`] with given parameters
     ///
     /// # Errors
     /// - `QRCodeError::EncodedData` if `input` is too large to be encoded. See [an online table](https://fast-qr.com/blog/tables/ecl) for more info.
     /// - `QRCodeError::SpecifiedVersion` if specified `version` is too small to contain data
+    /// - `QRCodeError::Logo` if the logo cannot be placed while keeping the `QRCode` readable
     pub fn build(&self) -> Result<QRCode, QRCodeError> {
-        QRCode::new(&self.input, self.ecl, self.version, self.mask)
+        match self.logo {
+            Some(size) => {
+                let logo = Logo {
+                    size,
+                    position: self.logo_position,
+                    safety_margin: self.logo_safety_margin,
+                };
+                QRCode::with_logo(&self.input, self.ecl, self.version, self.mask, &logo)
+            }
+            None => QRCode::new(&self.input, self.ecl, self.version, self.mask),
+        }
     }
 }
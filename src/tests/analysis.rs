@@ -0,0 +1,181 @@
+use std::collections::HashSet;
+
+use crate::analysis::{CodewordMap, DamageReport, Occlusion};
+use crate::{datamasking, encode, hardcode, polynomials, Mask, QRBuilder, Version, ECL};
+
+/// Recomputes each block's codewords (data then error correction)
+fn block_codewords(input: &[u8], ecl: ECL, version: Version) -> Vec<Vec<u8>> {
+    let mode = encode::best_encoding(input);
+    let data = encode::encode(input, ecl, mode, version);
+    let data = data.get_data();
+    let generator = hardcode::get_polynomial(version, ecl);
+
+    let [(g1_count, g1_size), (g2_count, g2_size)] = hardcode::ecc_to_groups(ecl, version);
+    let sizes = core::iter::repeat(g1_size)
+        .take(g1_count)
+        .chain(core::iter::repeat(g2_size).take(g2_count));
+
+    let mut start = 0;
+    let mut blocks = Vec::new();
+    for size in sizes {
+        let mut block = data[start..start + size].to_vec();
+        let division = polynomials::division(&block, generator);
+        block.extend_from_slice(&division[256 - generator.len()..256 - 1]);
+        blocks.push(block);
+        start += size;
+    }
+
+    blocks
+}
+
+#[test]
+fn analysis_map_matches_placement() {
+    let input = b"HELLO WORLD";
+
+    for (version, ecl) in [
+        (Version::V01, ECL::L),
+        (Version::V05, ECL::Q),
+        (Version::V07, ECL::H),
+        (Version::V14, ECL::M),
+    ] {
+        let mut qr = QRBuilder::new(&input[..])
+            .ecl(ecl)
+            .version(version)
+            .mask(Mask::Checkerboard)
+            .build()
+            .unwrap();
+        // Masking twice removes the mask
+        datamasking::mask(&mut qr, Mask::Checkerboard);
+
+        let blocks = block_codewords(input, ecl, version);
+        let map = CodewordMap::new(version, ecl);
+        assert_eq!(map.blocks().len(), blocks.len());
+
+        let mut mapped = 0;
+        for y in 0..qr.size {
+            for x in 0..qr.size {
+                let position = match map.get(y, x) {
+                    Some(position) => position,
+                    None => continue,
+                };
+                mapped += 1;
+
+                let codeword = blocks[position.block][position.codeword];
+                let expected = codeword & (1 << (7 - position.bit)) != 0;
+                assert_eq!(qr[y][x].value(), expected, "({y}, {x}) {position:?}");
+            }
+        }
+
+        assert_eq!(mapped, version.max_bytes() * 8);
+    }
+}
+
+#[test]
+fn analysis_first_codeword_bottom_right() {
+    let map = CodewordMap::new(Version::V01, ECL::M);
+    let position = map.get(20, 20).unwrap();
+
+    assert_eq!(position.block, 0);
+    assert_eq!(position.codeword, 0);
+    assert_eq!(position.bit, 0);
+
+    // Finder pattern
+    assert_eq!(map.get(0, 0), None);
+    // Out of the matrix
+    assert_eq!(map.get(21, 0), None);
+}
+
+#[test]
+fn analysis_block_capacity() {
+    // 1 block of 17 EC codewords, 1 for misdecode protection
+    let map = CodewordMap::new(Version::V01, ECL::H);
+    assert_eq!(map.blocks().len(), 1);
+    assert_eq!(map.blocks()[0].ec_codewords, 17);
+    assert_eq!(map.blocks()[0].capacity, 8);
+
+    // 2 blocks of 15 and 2 blocks of 16 data codewords, 18 EC codewords each
+    let map = CodewordMap::new(Version::V05, ECL::Q);
+    let data: Vec<usize> = map.blocks().iter().map(|b| b.data_codewords).collect();
+    assert_eq!(data, [15, 15, 16, 16]);
+    assert!(map.blocks().iter().all(|b| b.capacity == 9));
+}
+
+#[test]
+fn analysis_no_occlusion() {
+    let qr = QRBuilder::new("https://example.com/").build().unwrap();
+    let report = DamageReport::new(&qr, &Occlusion::Modules(HashSet::new()));
+
+    assert!(report.is_recoverable());
+    assert_eq!(report.damaged_codewords(), 0);
+}
+
+#[test]
+fn analysis_full_occlusion() {
+    let qr = QRBuilder::new("https://example.com/").build().unwrap();
+    let occlusion = Occlusion::Rectangle {
+        x: 0,
+        y: 0,
+        width: qr.size,
+        height: qr.size,
+    };
+    let report = DamageReport::new(&qr, &occlusion);
+
+    assert!(!report.is_recoverable());
+    assert_eq!(report.damaged_codewords(), qr.version.unwrap().max_bytes());
+}
+
+#[test]
+fn analysis_centered_logo() {
+    let qr = QRBuilder::new("https://example.com/")
+        .ecl(ECL::H)
+        .version(Version::V05)
+        .build()
+        .unwrap();
+
+    let center = qr.size as f64 / 2f64;
+    let small = Occlusion::Circle {
+        x: center,
+        y: center,
+        radius: 3f64,
+    };
+    assert!(DamageReport::new(&qr, &small).is_recoverable());
+
+    let big = Occlusion::Circle {
+        x: center,
+        y: center,
+        radius: 10f64,
+    };
+    let report = DamageReport::new(&qr, &big);
+    assert!(!report.is_recoverable());
+
+    let weakest = report.weakest_block();
+    assert!(!weakest.is_recoverable());
+    assert!(report.blocks.iter().all(|b| b.margin() >= weakest.margin()));
+}
+
+#[test]
+fn analysis_occlusion_contains() {
+    let rectangle = Occlusion::Rectangle {
+        x: 2,
+        y: 3,
+        width: 2,
+        height: 1,
+    };
+    assert!(rectangle.contains(3, 2));
+    assert!(rectangle.contains(3, 3));
+    assert!(!rectangle.contains(3, 4));
+    assert!(!rectangle.contains(2, 2));
+
+    let circle = Occlusion::Circle {
+        x: 5f64,
+        y: 5f64,
+        radius: 1f64,
+    };
+    assert!(circle.contains(4, 4));
+    assert!(circle.contains(5, 5));
+    assert!(!circle.contains(3, 5));
+
+    let modules = Occlusion::Modules([(1, 2)].into_iter().collect());
+    assert!(modules.contains(1, 2));
+    assert!(!modules.contains(2, 1));
+}
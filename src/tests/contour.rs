@@ -0,0 +1,81 @@
+use crate::convert::contour::{outlines, Outline};
+use crate::QRBuilder;
+
+/// Twice the signed area, positive for clockwise polygons with `y` going down
+fn area(polygon: &[(usize, usize)]) -> isize {
+    let mut sum = 0isize;
+    for (i, &(x0, y0)) in polygon.iter().enumerate() {
+        let (x1, y1) = polygon[(i + 1) % polygon.len()];
+        sum += x0 as isize * y1 as isize - x1 as isize * y0 as isize;
+    }
+    sum
+}
+
+/// Whether the center of the cell `(x, y)` is inside the outline, with the
+/// even-odd rule
+fn contains(outline: &Outline, x: usize, y: usize) -> bool {
+    let (px, py) = (x as f64 + 0.5f64, y as f64 + 0.5f64);
+    let mut inside = false;
+    for polygon in std::iter::once(&outline.outer).chain(&outline.holes) {
+        for (i, &(x0, y0)) in polygon.iter().enumerate() {
+            let (x1, y1) = polygon[(i + 1) % polygon.len()];
+            // Vertical sides crossed by a ray going right
+            if x0 == x1 && x0 as f64 > px && (y0.min(y1) as f64) < py && py < y0.max(y1) as f64 {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+#[test]
+fn contour_outlines_cover_the_modules() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let margin = 2;
+    let size = qrcode.size + 2 * margin;
+
+    for inverted in [false, true] {
+        let outlines = outlines(&qrcode, margin, inverted);
+        for outline in &outlines {
+            assert!(area(&outline.outer) > 0);
+            assert!(outline.holes.iter().all(|hole| area(hole) < 0));
+        }
+
+        // Every module is in exactly one outline if it is filled
+        for y in 0..size {
+            for x in 0..size {
+                let inside = |i: usize| i >= margin && i < margin + qrcode.size;
+                let dark = inside(x) && inside(y) && qrcode[y - margin][x - margin].value();
+                let count = outlines
+                    .iter()
+                    .filter(|outline| contains(outline, x, y))
+                    .count();
+                assert_eq!(count, usize::from(dark != inverted), "({x}, {y})");
+            }
+        }
+    }
+}
+
+#[test]
+fn contour_finder_patterns_have_holes() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let outlines = outlines(&qrcode, 0, false);
+
+    // Ring of the top left finder pattern, around its light ring
+    let finder = outlines
+        .iter()
+        .find(|outline| outline.outer[0] == (0, 0))
+        .unwrap();
+    assert_eq!(finder.outer, vec![(0, 0), (7, 0), (7, 7), (0, 7)]);
+    assert_eq!(finder.holes, vec![vec![(1, 1), (1, 6), (6, 6), (6, 1)]]);
+
+    // The light modules & margin are a single region, around the finder
+    // patterns and the data
+    let inverted = crate::convert::contour::outlines(&qrcode, 4, true);
+    let size = qrcode.size + 8;
+    assert_eq!(
+        inverted[0].outer,
+        vec![(0, 0), (size, 0), (size, size), (0, size)]
+    );
+    assert!(inverted[0].holes.len() > 3);
+}
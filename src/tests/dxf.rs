@@ -0,0 +1,92 @@
+use crate::convert::contour::outlines;
+use crate::convert::dxf::DxfBuilder;
+use crate::QRBuilder;
+
+/// Group code & value pairs
+fn pairs(dxf: &str) -> Vec<(u16, &str)> {
+    let lines: Vec<&str> = dxf.lines().collect();
+    lines
+        .chunks(2)
+        .map(|pair| (pair[0].parse().unwrap(), pair[1]))
+        .collect()
+}
+
+#[test]
+fn dxf_structure() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let dxf = DxfBuilder::default()
+        .margin(2)
+        .module_size(0.5f64)
+        .layer("ENGRAVE")
+        .to_str(&qrcode);
+    let pairs = pairs(&dxf);
+
+    assert_eq!(
+        &pairs[..4],
+        &[
+            (0, "SECTION"),
+            (2, "HEADER"),
+            (9, "$ACADVER"),
+            (1, "AC1009")
+        ]
+    );
+    // R12 has no units header
+    assert!(!dxf.contains("$INSUNITS"));
+    let extent = (qrcode.size + 4) as f64 * 0.5f64;
+    assert!(dxf.contains(&format!("9\n$EXTMAX\n10\n{extent}\n20\n{extent}\n")));
+    assert_eq!(pairs[pairs.len() - 1], (0, "EOF"));
+
+    // One closed polyline per outer boundary & hole
+    let outlines = outlines(&qrcode, 2, false);
+    let polygons: usize = outlines.iter().map(|outline| 1 + outline.holes.len()).sum();
+    assert_eq!(
+        pairs
+            .iter()
+            .filter(|&&pair| pair == (0, "POLYLINE"))
+            .count(),
+        polygons
+    );
+    assert_eq!(
+        pairs.iter().filter(|&&pair| pair == (0, "SEQEND")).count(),
+        polygons
+    );
+    assert!(dxf.contains("0\nPOLYLINE\n8\nENGRAVE\n66\n1\n10\n0\n20\n0\n30\n0\n70\n1\n"));
+
+    // Vertices in millimeters, `y` going up
+    let vertices = outlines
+        .iter()
+        .flat_map(|outline| std::iter::once(&outline.outer).chain(&outline.holes))
+        .flatten()
+        .count();
+    assert_eq!(
+        pairs.iter().filter(|&&pair| pair == (0, "VERTEX")).count(),
+        vertices
+    );
+    let (x, y) = outlines[0].outer[0];
+    let first = format!(
+        "0\nVERTEX\n8\nENGRAVE\n10\n{}\n20\n{}\n30\n0\n",
+        x as f64 * 0.5f64,
+        (qrcode.size + 4 - y) as f64 * 0.5f64
+    );
+    assert!(dxf.contains(&first));
+}
+
+#[test]
+fn dxf_inverted() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let dxf = DxfBuilder::default().inverted(true).to_str(&qrcode);
+    let side = (qrcode.size + 8).to_string();
+
+    // The first polyline is the outside of the margin
+    let start = dxf.find("0\nPOLYLINE\n").unwrap();
+    let vertices: Vec<(&str, &str)> = pairs(&dxf[start..])
+        .windows(3)
+        .filter(|window| window[0] == (0, "VERTEX"))
+        .take(4)
+        .map(|_| ("", ""))
+        .collect();
+    assert_eq!(vertices.len(), 4);
+    assert!(dxf[start..].starts_with(&format!(
+        "0\nPOLYLINE\n8\nQR\n66\n1\n10\n0\n20\n0\n30\n0\n70\n1\n0\nVERTEX\n8\nQR\n10\n0\n20\n{side}\n30\n0\n0\nVERTEX\n8\nQR\n10\n{side}\n20\n{side}\n"
+    )));
+}
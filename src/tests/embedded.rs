@@ -0,0 +1,99 @@
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Dimensions, OriginDimensions, Point, Size};
+use embedded_graphics_core::pixelcolor::BinaryColor;
+use embedded_graphics_core::primitives::Rectangle;
+use embedded_graphics_core::{Drawable, Pixel};
+
+use crate::convert::embedded::QrDrawable;
+use crate::QRBuilder;
+
+/// Frame buffer counting the fills, pixels are `None` until drawn
+struct Display {
+    side: usize,
+    pixels: Vec<Option<BinaryColor>>,
+    fills: usize,
+}
+
+impl Display {
+    fn new(side: usize) -> Self {
+        Display {
+            side,
+            pixels: vec![None; side * side],
+            fills: 0,
+        }
+    }
+}
+
+impl OriginDimensions for Display {
+    fn size(&self) -> Size {
+        Size::new_equal(self.side as u32)
+    }
+}
+
+impl DrawTarget for Display {
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I: IntoIterator<Item = Pixel<BinaryColor>>>(
+        &mut self,
+        pixels: I,
+    ) -> Result<(), Self::Error> {
+        for Pixel(point, color) in pixels {
+            let (x, y) = (point.x as usize, point.y as usize);
+            assert!(self.pixels[y * self.side + x].is_none(), "({x}, {y})");
+            self.pixels[y * self.side + x] = Some(color);
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: BinaryColor) -> Result<(), Self::Error> {
+        self.fills += 1;
+        let (x0, y0) = (area.top_left.x, area.top_left.y);
+        let (width, height) = (area.size.width as i32, area.size.height as i32);
+        let points =
+            (y0..y0 + height).flat_map(|y| (x0..x0 + width).map(move |x| Point::new(x, y)));
+        self.draw_iter(points.map(|point| Pixel(point, color)))
+    }
+}
+
+#[test]
+fn embedded_draws_runs() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let side = (qrcode.size + 2) * 3;
+    let mut display = Display::new(side + 10);
+
+    let mut drawable = QrDrawable::new(&qrcode, BinaryColor::On, BinaryColor::Off);
+    drawable.position(Point::new(4, 6)).scale(3).margin(1);
+    assert_eq!(
+        drawable.bounding_box(),
+        Rectangle::new(Point::new(4, 6), Size::new_equal(side as u32))
+    );
+    drawable.draw(&mut display).unwrap();
+
+    for y in 0..side + 10 {
+        for x in 0..side + 10 {
+            let pixel = display.pixels[y * (side + 10) + x];
+            let (px, py) = (x.wrapping_sub(4), y.wrapping_sub(6));
+            if px >= side || py >= side {
+                assert_eq!(pixel, None, "({x}, {y})");
+                continue;
+            }
+            let (mx, my) = ((px / 3).wrapping_sub(1), (py / 3).wrapping_sub(1));
+            let dark = mx < qrcode.size && my < qrcode.size && qrcode[my][mx].value();
+            assert_eq!(pixel, Some(BinaryColor::from(dark)), "({x}, {y})");
+        }
+    }
+
+    // One fill per run: the margin rows are a single rectangle
+    let runs: usize = (0..qrcode.size)
+        .map(|y| {
+            // Light margins on both sides of the row
+            let row: Vec<bool> = std::iter::once(false)
+                .chain(qrcode[y].iter().map(|module| module.value()))
+                .chain(std::iter::once(false))
+                .collect();
+            1 + row.windows(2).filter(|pair| pair[0] != pair[1]).count()
+        })
+        .sum();
+    assert_eq!(display.fills, 2 + runs);
+}
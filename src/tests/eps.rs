@@ -0,0 +1,176 @@
+use crate::convert::eps::{ColorModel, EpsBuilder};
+use crate::convert::{Builder, LinearGradient, Shape};
+use crate::QRBuilder;
+
+/// Drawing operators, between the prolog and the trailer
+fn body(eps: &str) -> &str {
+    let start = eps.find("fast_qr begin gsave\n").unwrap() + 20;
+    let end = eps.rfind("grestore end\n").unwrap();
+    &eps[start..end]
+}
+
+#[test]
+fn eps_structure() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let eps = EpsBuilder::default()
+        .module_size(0.5f64)
+        .to_str(&qrcode)
+        .unwrap();
+
+    assert!(eps.starts_with("%!PS-Adobe-3.0 EPSF-3.0\n"));
+    assert!(eps.ends_with("showpage\n%%EOF\n"));
+    assert!(eps.contains("%%LanguageLevel: 2\n"));
+
+    // Bounding box of the whole code, margin included
+    let side = (qrcode.size + 8) as f64 * 0.5f64 * 72f64 / 25.4f64;
+    assert!(eps.contains(&format!("%%BoundingBox: 0 0 {0} {0}\n", side.ceil())));
+    assert!(eps.contains(&format!("%%HiResBoundingBox: 0 0 {0:.4} {0:.4}\n", side)));
+    assert!(body(&eps).starts_with(&format!("0 {side:.4} translate 1.4173 -1.4173 scale\n")));
+
+    // Every graphics state is restored
+    assert_eq!(
+        eps.matches("gsave").count(),
+        eps.matches("grestore").count()
+    );
+    assert!(body(&eps).contains("0 0 0 setrgbcolor "));
+    assert!(body(&eps).contains("1 1 1 setrgbcolor "));
+}
+
+#[test]
+fn eps_shapes() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+
+    for shape in [
+        Shape::Square,
+        Shape::Circle,
+        Shape::RoundedSquare,
+        Shape::Vertical,
+        Shape::Horizontal,
+        Shape::Diamond,
+    ] {
+        let eps = EpsBuilder::default().shape(shape).to_str(&qrcode).unwrap();
+        let body = body(&eps);
+        // Background, modules, finder & alignment patterns
+        assert!(body.matches(" grestore\n").count() >= 2, "{shape:?}");
+        assert!(!body.contains('M') && !body.contains('z'), "{shape:?}");
+
+        // Arcs become curves, rounded squares are stroked with round joins
+        assert_eq!(body.contains(" c "), shape == Shape::Circle, "{shape:?}");
+        assert_eq!(
+            body.contains("1 setlinejoin stroke"),
+            shape == Shape::RoundedSquare,
+            "{shape:?}"
+        );
+    }
+}
+
+#[test]
+fn eps_cmyk_and_spot_colors() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+
+    let eps = EpsBuilder::default()
+        .color_model(ColorModel::Cmyk)
+        .module_color("#ff8000")
+        .to_str(&qrcode)
+        .unwrap();
+    assert!(body(&eps).contains("0 0.498 1 0 setcmykcolor "));
+    assert!(body(&eps).contains("0 0 0 0 setcmykcolor "));
+    assert!(!eps.contains("setrgbcolor"));
+
+    let eps = EpsBuilder::default()
+        .color_model(ColorModel::Cmyk)
+        .spot_color("black", "PANTONE (Black) C", [0.1f64, 0f64, 0f64, 1f64])
+        .to_str(&qrcode)
+        .unwrap();
+    assert!(eps.contains("%%DocumentCustomColors: (PANTONE \\(Black\\) C)\n"));
+    assert!(eps.contains("%%CMYKCustomColor: 0.1 0 0 1 (PANTONE \\(Black\\) C)\n"));
+    assert!(eps.contains("/S0 [/Separation (PANTONE \\(Black\\) C) /DeviceCMYK { dup 0.1 mul exch dup 0 mul exch dup 0 mul exch 1 mul }] def\n"));
+    assert!(body(&eps).contains("S0 setcolorspace 1 setcolor "));
+    assert!(!body(&eps).contains("0 0 0 1 setcmykcolor"));
+}
+
+#[test]
+fn eps_paints() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let eps = EpsBuilder::default()
+        .module_color(
+            LinearGradient::new(90f64)
+                .stop(0f64, "#ff0000")
+                .stop(1f64, "#0000ff"),
+        )
+        .background_color([255, 255, 255, 0])
+        .to_str(&qrcode)
+        .unwrap();
+
+    assert!(eps.contains("%%LanguageLevel: 3\n"));
+    // Transparent background is not drawn
+    assert!(!body(&eps).contains("1 1 1 setrgbcolor"));
+    assert!(body(&eps).contains("clip newpath << /ShadingType 2 /ColorSpace /DeviceRGB"));
+    assert!(body(&eps).contains("/C0 [1 0 0] /C1 [0 0 1] /N 1 >> /Extend [true true] >> shfill"));
+}
+
+#[test]
+fn eps_embeds_logos() {
+    use crate::convert::svg::SvgError;
+    use crate::ECL;
+
+    // 16x16 RGB PNG
+    let png = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAABAAAAAQCAIAAACQkWg2AAAAFUlEQVR4AWP4oyVDEhrGGkY1jGoAABACQhA+7XDPAAAAAElFTkSuQmCC";
+    let qrcode = QRBuilder::new("https://example.com/")
+        .ecl(ECL::H)
+        .build()
+        .unwrap();
+
+    let eps = EpsBuilder::default()
+        .image(String::from(png))
+        .color_model(ColorModel::Cmyk)
+        .to_str(&qrcode)
+        .unwrap();
+    let body = body(&eps);
+    assert!(body.contains("/DeviceCMYK setcolorspace\n"));
+    assert!(body.contains("<< /ImageType 1 /Width 16 /Height 16 /BitsPerComponent 8 /Decode [0 1 0 1 0 1 0 1] /ImageMatrix [16 0 0 16 0 0] /DataSource Data >> image Data flushfile"));
+
+    // 16 lines of 16 pixels, 4 bytes each
+    let start = body.find("} exec\n").unwrap() + 7;
+    let end = start + body[start..].find('>').unwrap();
+    let data: String = body[start..end].split_whitespace().collect();
+    assert_eq!(data.len(), 16 * 16 * 4 * 2);
+    assert!(data.chars().all(|c| c.is_ascii_hexdigit()));
+
+    // SVG logos can't be embedded, which is an error
+    let eps = EpsBuilder::default()
+        .image(String::from("data:image/svg+xml;base64,PHN2Zz48L3N2Zz4="))
+        .to_str(&qrcode);
+    assert!(matches!(eps, Err(SvgError::SvgError(_))));
+}
+
+#[test]
+fn eps_draws_frames() {
+    use crate::convert::frame::{CaptionPosition, Frame, FrameStyle};
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let eps = EpsBuilder::default()
+        .frame(
+            Frame::new(FrameStyle::Tab)
+                .caption("SCAN ME")
+                .position(CaptionPosition::Above),
+        )
+        .to_str(&qrcode)
+        .unwrap();
+
+    // Border of 1 module, tab of 1 + 5.5 modules above the code
+    let width = (qrcode.size + 10) as f64 * 72f64 / 25.4f64;
+    let height = (qrcode.size + 10) as f64 * 72f64 / 25.4f64 + 6.5f64 * 72f64 / 25.4f64;
+    assert!(eps.contains(&format!("%%HiResBoundingBox: 0 0 {width:.4} {height:.4}\n")));
+
+    // The code is moved by the border, the frame is drawn from the top left
+    let body = body(&eps);
+    let (translate, rest) = body.split_once(" translate").unwrap();
+    let y: f64 = translate.strip_prefix("2.8346 ").unwrap().parse().unwrap();
+    assert!((y - (height - 7.5f64 * 72f64 / 25.4f64)).abs() < 1e-3);
+    assert!(rest.starts_with(" 2.8346 -2.8346 scale\ngsave -1 -7.5 translate\n"));
+    assert_eq!(
+        eps.matches("gsave").count(),
+        eps.matches("grestore").count()
+    );
+}
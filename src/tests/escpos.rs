@@ -0,0 +1,47 @@
+use crate::convert::escpos::EscPosBuilder;
+use crate::{QRBuilder, ECL};
+
+#[test]
+fn escpos_raster_bit_image() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let bytes = EscPosBuilder::default()
+        .margin(1)
+        .dots_per_module(5)
+        .to_bytes(&qrcode);
+
+    let side = (qrcode.size + 2) * 5;
+    let width = (side + 7) / 8;
+    assert_eq!(&bytes[..4], &[0x1d, b'v', b'0', 0]);
+    assert_eq!(u16::from_le_bytes([bytes[4], bytes[5]]) as usize, width);
+    assert_eq!(u16::from_le_bytes([bytes[6], bytes[7]]) as usize, side);
+    assert_eq!(bytes.len(), 8 + width * side);
+
+    let data = &bytes[8..];
+    for y in 0..side {
+        for x in 0..width * 8 {
+            let dot = data[y * width + x / 8] >> (7 - x % 8) & 1 == 1;
+            let (mx, my) = ((x / 5).wrapping_sub(1), (y / 5).wrapping_sub(1));
+            let dark = x < side && mx < qrcode.size && my < qrcode.size && qrcode[my][mx].value();
+            assert_eq!(dot, dark, "({x}, {y})");
+        }
+    }
+}
+
+#[test]
+fn escpos_native() {
+    let payload = b"https://example.com/";
+    let qrcode = QRBuilder::new(&payload[..]).ecl(ECL::H).build().unwrap();
+    let bytes = EscPosBuilder::default()
+        .dots_per_module(20)
+        .to_native(&qrcode, payload);
+
+    let mut expected = vec![
+        0x1d, b'(', b'k', 4, 0, 49, b'A', 50, 0, // Model 2
+        0x1d, b'(', b'k', 3, 0, 49, b'C', 16, // Module size
+        0x1d, b'(', b'k', 3, 0, 49, b'E', 51, // ECL H
+        0x1d, b'(', b'k', 23, 0, 49, b'P', 48,
+    ];
+    expected.extend_from_slice(payload);
+    expected.extend_from_slice(&[0x1d, b'(', b'k', 3, 0, 49, b'Q', 48]);
+    assert_eq!(bytes, expected);
+}
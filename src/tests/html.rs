@@ -0,0 +1,112 @@
+use crate::convert::html::{HtmlBuilder, Layout};
+use crate::convert::LinearGradient;
+use crate::QRBuilder;
+
+/// Attribute values of the opening tags of `tag`
+fn tags<'a>(html: &'a str, tag: &str) -> Vec<&'a str> {
+    html.split(&format!("<{tag}"))
+        .skip(1)
+        .map(|rest| &rest[..rest.find('>').unwrap()])
+        .collect()
+}
+
+/// Number of modules a cell spans
+fn span(attributes: &str, key: &str) -> usize {
+    attributes.find(key).map_or(1, |start| {
+        let rest = &attributes[start + key.len()..];
+        let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap();
+        rest[..end].parse().unwrap()
+    })
+}
+
+#[test]
+fn html_table() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let html = HtmlBuilder::default()
+        .margin(2)
+        .module_color("#1a237e")
+        .background_color("#fafafa")
+        .module_size(3)
+        .to_str(&qrcode);
+    let columns = qrcode.size + 4;
+
+    assert!(html.starts_with(&format!(
+        r#"<table role="presentation" cellpadding="0" cellspacing="0" border="0" width="{0}" style="border-collapse:collapse;table-layout:fixed;width:{0}px;background:#fafafa">"#,
+        columns * 3
+    )));
+    assert!(html.ends_with("</table>"));
+
+    // Margin rows, then one row per row of modules
+    let rows: Vec<&str> = html.split("<tr").skip(1).collect();
+    assert_eq!(rows.len(), qrcode.size + 2);
+    let margin = format!(r#"<td colspan="{columns}" height="6">"#);
+    assert!(rows[0].contains(&margin) && rows[qrcode.size + 1].contains(&margin));
+
+    for (y, row) in rows[1..=qrcode.size].iter().enumerate() {
+        let mut modules = Vec::new();
+        let mut previous = None;
+        for cell in tags(row, "td") {
+            let dark = cell.contains("background:#1a237e");
+            // Runs are merged
+            assert_ne!(previous, Some(dark));
+            previous = Some(dark);
+            modules.extend(std::iter::repeat(dark).take(span(cell, "colspan=\"")));
+        }
+
+        assert_eq!(modules.len(), columns);
+        assert!(modules[..2]
+            .iter()
+            .chain(&modules[columns - 2..])
+            .all(|&dark| !dark));
+        for x in 0..qrcode.size {
+            assert_eq!(modules[x + 2], qrcode[y][x].value());
+        }
+    }
+}
+
+#[test]
+fn html_grid() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let html = HtmlBuilder::default()
+        .layout(Layout::Grid)
+        .module_color(
+            LinearGradient::new(0f64)
+                .stop(0f64, "red")
+                .stop(1f64, "blue"),
+        )
+        .to_str(&qrcode);
+
+    assert!(html.starts_with(&format!(
+        r#"<div style="display:inline-grid;grid-template-columns:repeat({},4px);grid-auto-rows:4px;padding:16px;background:#ffffff">"#,
+        qrcode.size
+    )));
+
+    let modules: Vec<bool> = tags(&html, "div")
+        .iter()
+        .skip(1)
+        .flat_map(|cell| {
+            // Gradients are drawn with their first color
+            let dark = cell.ends_with(";background:red\"");
+            std::iter::repeat(dark).take(span(cell, "span "))
+        })
+        .collect();
+    assert_eq!(modules.len(), qrcode.size * qrcode.size);
+    for (i, &dark) in modules.iter().enumerate() {
+        assert_eq!(dark, qrcode[i / qrcode.size][i % qrcode.size].value());
+    }
+}
+
+#[test]
+fn html_escapes_colors() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let html = HtmlBuilder::default()
+        .module_color(r#"red" onmouseover="alert(1)"#)
+        .background_color("rgb(1, 2, 3);position:fixed")
+        .to_str(&qrcode);
+
+    // Colors cannot close the attribute or add declarations
+    assert!(!html.contains("onmouseover="));
+    assert!(!html.contains(";position"));
+    assert!(html.contains(r#"style="background:red onmouseoveralert(1)""#));
+    assert!(html.contains("background:rgb(1, 2, 3)positionfixed\""));
+}
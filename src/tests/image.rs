@@ -0,0 +1,169 @@
+use crate::convert::image::{ImageBuilder, ImageError, ImageFormat};
+use crate::convert::Builder;
+use crate::QRBuilder;
+
+#[test]
+fn image_format_from_extension() {
+    assert_eq!(
+        ImageFormat::from_extension("out.png"),
+        Some(ImageFormat::Png)
+    );
+    assert_eq!(
+        ImageFormat::from_extension("dir/OUT.JPG"),
+        Some(ImageFormat::Jpeg { quality: 90 })
+    );
+    assert_eq!(
+        ImageFormat::from_extension("a.tif"),
+        Some(ImageFormat::Tiff)
+    );
+    assert_eq!(ImageFormat::from_extension("a.pbm"), Some(ImageFormat::Pbm));
+    assert_eq!(ImageFormat::from_extension("a.svg"), None);
+    assert_eq!(ImageFormat::from_extension("png"), None);
+}
+
+#[test]
+fn image_pbm_matches_modules() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let side = (qrcode.size + 8) * 4;
+    let bytes = ImageBuilder::default()
+        .fit_width(side as u32)
+        .format(ImageFormat::Pbm)
+        .to_bytes(&qrcode)
+        .unwrap();
+
+    let header = format!("P4\n{side} {side}\n");
+    assert!(bytes.starts_with(header.as_bytes()));
+    let stride = (side + 7) / 8;
+    let pixels = &bytes[header.len()..];
+    assert_eq!(pixels.len(), stride * side);
+
+    let black = |x: usize, y: usize| pixels[y * stride + x / 8] & (0x80 >> (x % 8)) != 0;
+    for y in 0..qrcode.size {
+        for x in 0..qrcode.size {
+            let center = ((x + 4) * 4 + 2, (y + 4) * 4 + 2);
+            assert_eq!(black(center.0, center.1), qrcode[y][x].value());
+        }
+    }
+    assert!(!black(0, 0));
+}
+
+#[test]
+fn image_bmp_and_pgm() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let mut builder = ImageBuilder::default();
+    // 3 bytes per pixel are padded to a multiple of 4
+    builder.fit_width(99).module_color("#FF0000");
+
+    let bmp = builder.format(ImageFormat::Bmp).to_bytes(&qrcode).unwrap();
+    assert_eq!(&bmp[..2], b"BM");
+    assert_eq!(bmp.len(), 54 + 300 * 99);
+    assert_eq!(
+        u32::from_le_bytes([bmp[2], bmp[3], bmp[4], bmp[5]]) as usize,
+        bmp.len()
+    );
+    assert_eq!(&bmp[18..26], &[99, 0, 0, 0, 99, 0, 0, 0]);
+    // Bottom left is the background, stored first
+    assert_eq!(&bmp[54..57], &[255, 255, 255]);
+
+    let pgm = builder.format(ImageFormat::Pgm).to_bytes(&qrcode).unwrap();
+    assert!(pgm.starts_with(b"P5\n99 99\n255\n"));
+    assert_eq!(pgm.len(), 13 + 99 * 99);
+    // Red is a dark gray
+    assert!(pgm[13..].contains(&76));
+}
+
+#[test]
+fn image_to_file_detects_format() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let file = std::env::temp_dir().join("fast_qr_image_to_file.bmp");
+    let file = file.to_str().unwrap();
+
+    ImageBuilder::default().to_file(&qrcode, file).unwrap();
+    let bytes = std::fs::read(file).unwrap();
+    std::fs::remove_file(file).unwrap();
+    assert_eq!(&bytes[..2], b"BM");
+
+    // Formats that are not enabled are written as PNG
+    let file = std::env::temp_dir().join("fast_qr_image_to_file.jpg");
+    let file = file.to_str().unwrap();
+    ImageBuilder::default().to_file(&qrcode, file).unwrap();
+    let bytes = std::fs::read(file).unwrap();
+    std::fs::remove_file(file).unwrap();
+    let jpeg = ImageFormat::Jpeg { quality: 90 };
+    let magic: &[u8] = if jpeg.is_enabled() {
+        &[0xff, 0xd8, 0xff]
+    } else {
+        b"\x89PNG"
+    };
+    assert!(bytes.starts_with(magic));
+    assert_eq!(jpeg.is_enabled(), cfg!(feature = "jpeg"));
+}
+
+#[test]
+fn image_optional_formats() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let encode = |format| {
+        ImageBuilder::default()
+            .fit_width(120)
+            .format(format)
+            .to_bytes(&qrcode)
+    };
+    let check = |result: Result<Vec<u8>, ImageError>, enabled: bool, magic: &[u8]| match result {
+        Ok(bytes) => assert!(enabled && bytes.starts_with(magic)),
+        Err(ImageError::EncodingError(err)) => assert!(!enabled && err.contains("feature")),
+        Err(err) => panic!("{err:?}"),
+    };
+
+    check(
+        encode(ImageFormat::Jpeg { quality: 80 }),
+        cfg!(feature = "jpeg"),
+        &[0xff, 0xd8, 0xff],
+    );
+    check(encode(ImageFormat::WebP), cfg!(feature = "webp"), b"RIFF");
+    check(encode(ImageFormat::Gif), cfg!(feature = "gif"), b"GIF89a");
+    check(encode(ImageFormat::Tiff), cfg!(feature = "tiff"), b"II*\0");
+}
+
+#[test]
+fn image_dark_mode_keeps_module_sizes() {
+    use crate::convert::svg::SvgBuilder;
+    use crate::convert::Shape;
+    use resvg::tiny_skia::{Pixmap, Transform};
+    use resvg::usvg;
+
+    // Whether each pixel is dark, 10 pixels per module
+    let render = |svg: &str| -> Vec<bool> {
+        let tree = usvg::Tree::from_data(svg.as_bytes(), &usvg::Options::default()).unwrap();
+        let size = tree.size.to_screen_size();
+        let mut pixmap = Pixmap::new(size.width() * 10, size.height() * 10).unwrap();
+        resvg::render(
+            &tree,
+            usvg::FitTo::Zoom(10f32),
+            Transform::default(),
+            pixmap.as_mut(),
+        )
+        .unwrap();
+        pixmap
+            .pixels()
+            .iter()
+            .map(|pixel| pixel.red() < 128)
+            .collect()
+    };
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    for shape in [Shape::Square, Shape::RoundedSquare, Shape::Circle] {
+        let light = SvgBuilder::default()
+            .shape(shape)
+            .dark_mode("#ffffff", "#000000")
+            .to_str(&qrcode);
+        // Applies the dark colors whatever the color scheme
+        let dark = light
+            .replace("@media (prefers-color-scheme: dark) { ", "")
+            .replace(" }</style>", "</style>");
+
+        // Modules swap colors but keep their size
+        let (light, dark) = (render(&light), render(&dark));
+        let same = light.iter().zip(&dark).filter(|(l, d)| l == d).count();
+        assert!(same * 1000 < light.len(), "{shape:?}: {same} pixels");
+    }
+}
@@ -0,0 +1,70 @@
+use crate::convert::image_crate::image::{Pixel, Rgba};
+use crate::convert::image_crate::ImageCrateBuilder;
+use crate::QRBuilder;
+
+#[test]
+fn image_crate_buffers() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let (dark, light) = (Rgba([26, 35, 126, 255]), Rgba([255, 255, 255, 0]));
+    let mut builder = ImageCrateBuilder::default();
+    builder
+        .margin(2)
+        .scale(3)
+        .module_color(dark)
+        .background_color(light);
+
+    let rgba = builder.to_rgba(&qrcode);
+    let side = (qrcode.size + 4) as u32 * 3;
+    assert_eq!(rgba.dimensions(), (side, side));
+    for (x, y, pixel) in rgba.enumerate_pixels() {
+        let (mx, my) = ((x / 3) as usize, (y / 3) as usize);
+        let (mx, my) = (mx.wrapping_sub(2), my.wrapping_sub(2));
+        let module = mx < qrcode.size && my < qrcode.size && qrcode[my][mx].value();
+        assert_eq!(*pixel, if module { dark } else { light }, "({x}, {y})");
+    }
+
+    // Luma of the same colors
+    assert_ne!(dark.to_luma(), light.to_luma());
+    let gray = builder.to_gray(&qrcode);
+    assert_eq!(gray.dimensions(), (side, side));
+    for (x, y, pixel) in gray.enumerate_pixels() {
+        assert_eq!(*pixel, rgba.get_pixel(x, y).to_luma(), "({x}, {y})");
+    }
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn image_crate_from_pixmap() {
+    use crate::convert::image::ImageBuilder;
+    use crate::convert::image_crate::from_pixmap;
+    use crate::convert::Builder;
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let pixmap = ImageBuilder::default()
+        .module_color([255, 0, 0, 128])
+        .background_color([0, 0, 0, 0])
+        .fit_width(330)
+        .to_pixmap(&qrcode);
+    let image = from_pixmap(&pixmap);
+    assert_eq!(image.dimensions(), (pixmap.width(), pixmap.height()));
+
+    // Straight alpha, over the transparent parts too
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let premultiplied = pixmap.pixel(x, y).unwrap();
+        assert_eq!(pixel[3], premultiplied.alpha());
+        let expected = premultiplied.demultiply();
+        assert_eq!(
+            *pixel,
+            Rgba([
+                expected.red(),
+                expected.green(),
+                expected.blue(),
+                expected.alpha()
+            ])
+        );
+    }
+    // Center of the top left module of the finder pattern
+    let module = pixmap.width() / (qrcode.size as u32 + 8);
+    let center = module * 4 + module / 2;
+    assert_eq!(*image.get_pixel(center, center), Rgba([255, 0, 0, 128]));
+}
@@ -0,0 +1,155 @@
+use crate::analysis::{DamageReport, Occlusion};
+use crate::qr::{LogoPosition, LogoSize, QRCodeError};
+use crate::{QRBuilder, Version, ECL};
+
+fn assert_recoverable(qr: &crate::QRCode, safety_margin: isize) {
+    let logo = qr.logo().unwrap();
+    let report = DamageReport::new(qr, logo);
+
+    assert!(report.is_recoverable());
+    assert!(report.weakest_block().margin() >= safety_margin);
+}
+
+#[test]
+fn logo_centered() {
+    let qr = QRBuilder::new("https://example.com/")
+        .logo(LogoSize::Modules(7))
+        .build()
+        .unwrap();
+
+    let center = (qr.size - 7) / 2;
+    assert_eq!(
+        qr.logo(),
+        Some(&Occlusion::Rectangle {
+            x: center,
+            y: center,
+            width: 7,
+            height: 7,
+        })
+    );
+    assert_recoverable(&qr, 0);
+}
+
+#[test]
+fn logo_percent() {
+    let qr = QRBuilder::new("https://example.com/")
+        .logo(LogoSize::Percent(10f64))
+        .build()
+        .unwrap();
+
+    match qr.logo() {
+        Some(Occlusion::Rectangle { width, height, .. }) => {
+            assert_eq!(width, height);
+            assert_eq!(width % 2, 1);
+            assert!(width * width * 10 >= qr.size * qr.size);
+        }
+        _ => panic!("Logo should be a rectangle"),
+    }
+    assert_recoverable(&qr, 0);
+}
+
+#[test]
+fn logo_prefers_smallest_version() {
+    let without_logo = QRBuilder::new("https://example.com/")
+        .ecl(ECL::L)
+        .build()
+        .unwrap();
+    let qr = QRBuilder::new("https://example.com/")
+        .logo(LogoSize::Modules(3))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        qr.version.unwrap() as usize,
+        without_logo.version.unwrap() as usize
+    );
+    assert_recoverable(&qr, 0);
+}
+
+#[test]
+fn logo_bumps_version_for_forced_ecl() {
+    let qr = QRBuilder::new("https://example.com/")
+        .ecl(ECL::L)
+        .logo(LogoSize::Modules(9))
+        .build()
+        .unwrap();
+
+    assert!(qr.version.unwrap() as usize > Version::V02 as usize);
+    assert!(matches!(qr.ecl, Some(ECL::L)));
+    assert_recoverable(&qr, 0);
+}
+
+#[test]
+fn logo_safety_margin() {
+    let qr = QRBuilder::new("https://example.com/")
+        .logo(LogoSize::Modules(7))
+        .logo_safety_margin(4)
+        .build()
+        .unwrap();
+
+    assert_recoverable(&qr, 4);
+}
+
+#[test]
+fn logo_auto_position() {
+    let qr = QRBuilder::new("https://example.com/")
+        .ecl(ECL::H)
+        .version(Version::V05)
+        .logo(LogoSize::Modules(9))
+        .logo_position(LogoPosition::Auto)
+        .build()
+        .unwrap();
+
+    assert_recoverable(&qr, 0);
+}
+
+#[test]
+fn logo_fixed_position() {
+    let qr = QRBuilder::new("https://example.com/")
+        .ecl(ECL::H)
+        .version(Version::V05)
+        .logo(LogoSize::Modules(5))
+        .logo_position(LogoPosition::At { x: 20.5, y: 12.5 })
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        qr.logo(),
+        Some(&Occlusion::Rectangle {
+            x: 18,
+            y: 10,
+            width: 5,
+            height: 5,
+        })
+    );
+}
+
+#[test]
+fn logo_cannot_cover_finder_pattern() {
+    let qr = QRBuilder::new("https://example.com/")
+        .version(Version::V05)
+        .logo(LogoSize::Modules(3))
+        .logo_position(LogoPosition::At { x: 1.5, y: 1.5 })
+        .build();
+
+    assert!(matches!(qr, Err(QRCodeError::Logo)));
+}
+
+#[test]
+fn logo_too_big() {
+    let qr = QRBuilder::new("https://example.com/")
+        .logo(LogoSize::Percent(60f64))
+        .build();
+
+    assert!(matches!(qr, Err(QRCodeError::Logo)));
+}
+
+#[test]
+fn logo_specified_version_too_small() {
+    let qr = QRBuilder::new("https://example.com/a-very-long-path/to-some-page")
+        .version(Version::V01)
+        .logo(LogoSize::Modules(3))
+        .build();
+
+    assert!(matches!(qr, Err(QRCodeError::SpecifiedVersion)));
+}
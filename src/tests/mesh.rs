@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use crate::convert::mesh::MeshBuilder;
+use crate::{QRBuilder, QRCode};
+
+fn dark_modules(qrcode: &QRCode) -> usize {
+    (0..qrcode.size)
+        .flat_map(|y| (0..qrcode.size).map(move |x| (x, y)))
+        .filter(|&(x, y)| qrcode[y][x].value())
+        .count()
+}
+
+/// Triangles of a binary STL
+fn triangles(stl: &[u8]) -> Vec<[[f32; 3]; 3]> {
+    let float = |i: usize| f32::from_le_bytes(stl[i..i + 4].try_into().unwrap());
+    let count = u32::from_le_bytes(stl[80..84].try_into().unwrap()) as usize;
+    assert_eq!(stl.len(), 84 + count * 50);
+
+    (0..count)
+        .map(|t| {
+            // Skips the normal
+            let start = 84 + t * 50 + 12;
+            [0, 1, 2].map(|v| [0, 1, 2].map(|c| float(start + v * 12 + c * 4)))
+        })
+        .collect()
+}
+
+/// Volume of a closed mesh, every edge must be used as often in both
+/// directions. Modules touching by a corner share a vertical edge, so edges
+/// can be used more than once
+fn volume(triangles: &[[[f32; 3]; 3]]) -> f64 {
+    let key = |v: [f32; 3]| v.map(|c| (c * 1000f32).round() as i64);
+    let mut edges: HashMap<([i64; 3], [i64; 3]), isize> = HashMap::new();
+    let mut volume = 0f64;
+
+    for triangle in triangles {
+        for i in 0..3 {
+            let (a, b) = (key(triangle[i]), key(triangle[(i + 1) % 3]));
+            *edges.entry((a, b)).or_default() += 1;
+        }
+        let [a, b, c] = triangle.map(|v| v.map(f64::from));
+        volume += (a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+            + a[2] * (b[0] * c[1] - b[1] * c[0]))
+            / 6f64;
+    }
+    for (&(a, b), &count) in &edges {
+        assert_eq!(edges.get(&(b, a)), Some(&count), "{a:?} → {b:?}");
+    }
+    volume
+}
+
+#[test]
+fn mesh_stl_is_watertight() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let dark = dark_modules(&qrcode) as f64;
+    let side = (qrcode.size + 4) as f64 * 0.5f64;
+
+    let stl = MeshBuilder::default()
+        .margin(2)
+        .module_size(0.5f64)
+        .base_thickness(1.5f64)
+        .raised_height(0.75f64)
+        .to_stl(&qrcode);
+    assert!(stl.starts_with(b"fast_qr\0"));
+    let solid = triangles(&stl);
+    let expected = side * side * 1.5f64 + dark * 0.25f64 * 0.75f64;
+    assert!((volume(&solid) - expected).abs() < 1e-3);
+
+    // Bounding box
+    let top = solid.iter().flatten().map(|v| v[2]).fold(0f32, f32::max);
+    let right = solid.iter().flatten().map(|v| v[0]).fold(0f32, f32::max);
+    assert_eq!((top, right), (2.25f32, side as f32));
+
+    // Without plate, only the dark modules remain
+    let stl = MeshBuilder::default().base_thickness(0f64).to_stl(&qrcode);
+    assert!((volume(&triangles(&stl)) - dark).abs() < 1e-3);
+
+    // Dark modules on the sides of the plate, or a flat plate
+    let side = qrcode.size as f64;
+    let stl = MeshBuilder::default().margin(0).to_stl(&qrcode);
+    assert!((volume(&triangles(&stl)) - (side * side * 2f64 + dark)).abs() < 1e-3);
+    let stl = MeshBuilder::default().raised_height(0f64).to_stl(&qrcode);
+    let side = (qrcode.size + 8) as f64;
+    assert!((volume(&triangles(&stl)) - side * side * 2f64).abs() < 1e-3);
+    assert_eq!(triangles(&stl).len(), 12);
+}
+
+#[test]
+fn mesh_merged_is_manifold() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let size = qrcode.size + 8;
+
+    let builders: [fn(&mut MeshBuilder) -> &mut MeshBuilder; 3] = [
+        |builder| builder,
+        |builder| builder.margin(0),
+        |builder| builder.base_thickness(0f64),
+    ];
+    for builder in builders {
+        let package = builder(&mut MeshBuilder::default()).to_3mf(&qrcode);
+        let model = String::from_utf8_lossy(&package);
+        let index = |triangle: &str, name: &str| -> usize {
+            let start = triangle.find(name).unwrap() + name.len() + 2;
+            let end = start + triangle[start..].find('"').unwrap();
+            triangle[start..end].parse().unwrap()
+        };
+
+        // Every edge is used once in each direction, modules touching by a
+        // corner do not share the vertices of that corner
+        let mut edges = HashMap::new();
+        let triangles: Vec<&str> = model.split("<triangle ").skip(1).collect();
+        for triangle in &triangles {
+            let [a, b, c] = ["v1", "v2", "v3"].map(|name| index(triangle, name));
+            for edge in [(a, b), (b, c), (c, a)] {
+                *edges.entry(edge).or_insert(0) += 1;
+            }
+        }
+        for (&(a, b), &count) in &edges {
+            assert_eq!((count, edges.get(&(b, a))), (1, Some(&1)), "{a} → {b}");
+        }
+
+        // Far less than the 4 triangles per module of one face per module
+        assert!(triangles.len() < 2 * size * size);
+    }
+}
+
+#[test]
+fn mesh_unmerged_boxes() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let dark = dark_modules(&qrcode);
+
+    let stl = MeshBuilder::default().merge(false).to_stl(&qrcode);
+    // Plate, then one box of 12 triangles per dark module
+    let boxes = triangles(&stl);
+    assert_eq!(boxes.len(), 12 * (1 + dark));
+    for shell in boxes.chunks(12) {
+        volume(shell);
+    }
+}
+
+#[test]
+fn mesh_3mf_package() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let builder = MeshBuilder::default();
+    let package = builder.to_3mf(&qrcode);
+
+    // Stored entries, read back from their local headers
+    let mut files = HashMap::new();
+    let mut offset = 0;
+    while package[offset..].starts_with(&[0x50, 0x4b, 0x03, 0x04]) {
+        let u16_at = |i: usize| u16::from_le_bytes([package[i], package[i + 1]]) as usize;
+        let u32_at = |i: usize| u32::from_le_bytes(package[i..i + 4].try_into().unwrap());
+        assert_eq!(u16_at(offset + 8), 0);
+        let (size, name_len) = (u32_at(offset + 18) as usize, u16_at(offset + 26));
+        let name = std::str::from_utf8(&package[offset + 30..offset + 30 + name_len]).unwrap();
+        let data = &package[offset + 30 + name_len..offset + 30 + name_len + size];
+        assert_eq!(u32_at(offset + 14), crate::convert::crc::crc32(data));
+        files.insert(name, std::str::from_utf8(data).unwrap());
+        offset += 30 + name_len + size;
+    }
+    assert!(package[offset..].starts_with(&[0x50, 0x4b, 0x01, 0x02]));
+    assert!(package[package.len() - 22..].starts_with(&[0x50, 0x4b, 0x05, 0x06]));
+
+    assert!(files["[Content_Types].xml"].contains(r#"Extension="model""#));
+    assert!(files["_rels/.rels"].contains(r#"Target="/3D/3dmodel.model""#));
+    let model = files["3D/3dmodel.model"];
+    assert!(model.contains(r#"<model unit="millimeter""#));
+    assert!(model.contains(r#"<build><item objectid="1"/></build>"#));
+
+    // Same triangles as the STL, with shared vertices
+    let stl = triangles(&builder.to_stl(&qrcode));
+    assert_eq!(model.matches("<triangle ").count(), stl.len());
+    assert!(model.matches("<vertex ").count() < stl.len());
+    assert!(model.contains(r#"<vertex x="0" y="0" z="0"/>"#));
+}
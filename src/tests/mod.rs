@@ -1,11 +1,45 @@
+#![allow(clippy::identity_op, clippy::bool_assert_comparison)]
+
+mod analysis;
 mod bytes;
 mod compact;
+mod contour;
 mod datamasking;
 mod default;
+#[cfg(feature = "dxf")]
+mod dxf;
+#[cfg(feature = "embedded-graphics")]
+mod embedded;
 mod encode;
+#[cfg(feature = "eps")]
+mod eps;
 mod error_correction;
+#[cfg(feature = "escpos")]
+mod escpos;
+#[cfg(feature = "html")]
+mod html;
+#[cfg(feature = "image")]
+mod image;
+#[cfg(feature = "image-crate")]
+mod image_crate;
+mod logo;
+#[cfg(feature = "mesh")]
+mod mesh;
+#[cfg(feature = "pdf")]
+mod pdf;
+#[cfg(any(feature = "pdf", feature = "eps"))]
+mod png;
 mod polynomials;
+#[cfg(feature = "raster")]
+mod raster;
 mod score;
+#[cfg(feature = "source")]
+mod source;
 mod structure;
 mod svg;
+mod terminal;
+#[cfg(feature = "ratatui")]
+mod tui;
 mod version;
+#[cfg(feature = "zpl")]
+mod zpl;
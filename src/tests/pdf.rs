@@ -0,0 +1,408 @@
+use crate::convert::path::{parse, Segment};
+use crate::convert::pdf::{PageSize, PdfBuilder};
+use crate::convert::{Builder, Color, LinearGradient, Shape};
+use crate::QRBuilder;
+
+/// Content stream of the page
+fn content(pdf: &[u8]) -> String {
+    let text = String::from_utf8_lossy(pdf);
+    let start = text.find("4 0 obj").unwrap();
+    let start = start + text[start..].find("stream\n").unwrap() + 7;
+    let end = start + text[start..].find("\nendstream").unwrap();
+    text[start..end].to_string()
+}
+
+#[test]
+fn pdf_path_parsing() {
+    assert_eq!(
+        parse("M1,2h3v-1.5L0 0z"),
+        vec![
+            Segment::Move(1f64, 2f64),
+            Segment::Line(4f64, 2f64),
+            Segment::Line(4f64, 0.5f64),
+            Segment::Line(0f64, 0f64),
+            Segment::Close,
+        ]
+    );
+
+    // Implicit lines after a move, numbers without separators
+    assert_eq!(
+        parse("m1.5.5 .5.5-1,0"),
+        vec![
+            Segment::Move(1.5f64, 0.5f64),
+            Segment::Line(2f64, 1f64),
+            Segment::Line(1f64, 1f64),
+        ]
+    );
+
+    // Half circle from the left to the right, through the top
+    let segments = parse("M0,1a1,1 0 0,1 2,0");
+    assert_eq!(segments.len(), 3);
+    match segments[2] {
+        Segment::Cubic(_, _, _, _, x, y) => assert_eq!((x, y), (2f64, 1f64)),
+        _ => panic!("arcs are drawn as cubic curves"),
+    }
+    if let Segment::Cubic(_, _, _, _, x, y) = segments[1] {
+        assert!((x - 1f64).abs() < 1e-9 && y.abs() < 1e-9);
+    }
+
+    // Stops at malformed data
+    assert_eq!(parse("M1,1L2"), vec![Segment::Move(1f64, 1f64)]);
+}
+
+#[test]
+fn pdf_colors() {
+    assert_eq!(Color::from("#1a2B3c").rgba(), Some([26, 43, 60, 255]));
+    assert_eq!(Color::from("#f008").rgba(), Some([255, 0, 0, 136]));
+    assert_eq!(Color::from([1, 2, 3, 4]).rgba(), Some([1, 2, 3, 4]));
+    assert_eq!(
+        Color::from("rgb(10, 20, 30)").rgba(),
+        Some([10, 20, 30, 255])
+    );
+    assert_eq!(
+        Color::from("rgba(10,20,30,0.5)").rgba(),
+        Some([10, 20, 30, 128])
+    );
+    assert_eq!(Color::from("White").rgba(), Some([255, 255, 255, 255]));
+    assert_eq!(Color::from("#12345").rgba(), None);
+    assert_eq!(Color::from("chartreuse").rgba(), Some([127, 255, 0, 255]));
+    assert_eq!(
+        Color::from("RebeccaPurple").rgba(),
+        Some([102, 51, 153, 255])
+    );
+    assert_eq!(Color::from("transparent").rgba(), Some([0, 0, 0, 0]));
+    assert_eq!(Color::from("notacolor").rgba(), None);
+}
+
+#[test]
+fn pdf_structure() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let pdf = PdfBuilder::default().to_bytes(&qrcode).unwrap();
+    let text = String::from_utf8_lossy(&pdf);
+
+    assert!(pdf.starts_with(b"%PDF-1.4\n"));
+    assert!(pdf.ends_with(b"%%EOF\n"));
+
+    // The cross-reference table points to each object
+    let startxref = text.rfind("startxref\n").unwrap() + 10;
+    let xref: usize = text[startxref..].lines().next().unwrap().parse().unwrap();
+    assert!(pdf[xref..].starts_with(b"xref\n0 5\n"));
+    let table = String::from_utf8_lossy(&pdf[xref..]);
+    for (i, entry) in table.lines().skip(3).take(4).enumerate() {
+        assert_eq!(entry.len(), 19);
+        let offset: usize = entry[..10].parse().unwrap();
+        assert!(pdf[offset..].starts_with(format!("{} 0 obj\n", i + 1).as_bytes()));
+    }
+
+    // Stream lengths are exact
+    let content = content(&pdf);
+    assert!(text.contains(&format!("<< /Length {} >>", content.len())));
+    assert!(content.starts_with("q 2.8346 0 0 -2.8346 0 "));
+}
+
+#[test]
+fn pdf_page_and_module_sizes() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let size = (qrcode.size + 8) as f64;
+    let media_box = |builder: &PdfBuilder| {
+        let pdf = builder.to_bytes(&qrcode).unwrap();
+        let text = String::from_utf8_lossy(&pdf).to_string();
+        let start = text.find("/MediaBox [0 0 ").unwrap() + 15;
+        let end = start + text[start..].find(']').unwrap();
+        let values: Vec<f64> = text[start..end]
+            .split(' ')
+            .map(|value| value.parse().unwrap())
+            .collect();
+        (values[0], values[1], content(&pdf))
+    };
+    let points = |mm: f64| mm * 72f64 / 25.4f64;
+
+    // Exact module size, the page is the code
+    let (width, height, _) = media_box(PdfBuilder::default().module_size(0.5f64));
+    assert!((width - points(size * 0.5f64)).abs() < 1e-3);
+    assert_eq!(width, height);
+
+    // As large as the page allows, centered
+    let (width, height, content) = media_box(PdfBuilder::default().page_size(PageSize::A4));
+    assert_eq!((width, height), (595.2756f64, 841.8898f64));
+    let scale = points(210f64) / size;
+    let top = (height + width) / 2f64;
+    assert!(content.starts_with(&format!("q {:.4} 0 0 -{:.4} 0 {:.4} cm", scale, scale, top)));
+
+    // Both, the code is centered on the page
+    let (width, _, content) = media_box(
+        PdfBuilder::default()
+            .page_size(PageSize::Custom(100f64, 50f64))
+            .module_size(1f64),
+    );
+    assert!((width - points(100f64)).abs() < 1e-3);
+    let left = format!(" {:.4} ", points(100f64 - size) / 2f64);
+    assert!(content.lines().next().unwrap().contains(&left));
+}
+
+#[test]
+fn pdf_paints() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let pdf = PdfBuilder::default()
+        .module_color(
+            LinearGradient::new(0f64)
+                .stop(0f64, "#ff0000")
+                .stop(0.5f64, "#00ff00")
+                .stop(1f64, "#0000ff"),
+        )
+        .background_color([255, 255, 255, 0])
+        .shape(Shape::Square)
+        .shape_color(Shape::Circle, "#00000080")
+        .to_bytes(&qrcode)
+        .unwrap();
+    let text = String::from_utf8_lossy(&pdf);
+    let content = content(&pdf);
+
+    // Transparent background is not drawn
+    assert!(!content.contains("1 1 1 rg"));
+    assert!(content.contains("/Pattern cs /P0 scn"));
+    assert!(content.contains("0 0 0 rg /GS0 gs"));
+    assert!(text.contains("/ShadingType 2"));
+    assert!(text.contains("/Bounds [0.5] /Encode [0 1 0 1]"));
+    assert!(text.contains("/GS0 << /ca 0.502 /CA 0.502 >>"));
+}
+
+#[test]
+fn pdf_embeds_logos() {
+    use crate::convert::svg::SvgError;
+    use crate::ECL;
+
+    // 16x16 RGB PNG
+    let png = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAABAAAAAQCAIAAACQkWg2AAAAFUlEQVR4AWP4oyVDEhrGGkY1jGoAABACQhA+7XDPAAAAAElFTkSuQmCC";
+    let qrcode = QRBuilder::new("https://example.com/")
+        .ecl(ECL::H)
+        .build()
+        .unwrap();
+
+    let pdf = PdfBuilder::default()
+        .image(String::from(png))
+        .to_bytes(&qrcode)
+        .unwrap();
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(content(&pdf).contains("/Im0 Do"));
+    assert!(text.contains("/XObject << /Im0 5 0 R >>"));
+    assert!(text.contains("/Subtype /Image /Width 16 /Height 16 /ColorSpace /DeviceRGB"));
+    assert!(!text.contains("/SMask"));
+
+    // Unsupported images are errors, instead of a hole in the code
+    for image in [
+        "https://example.com/logo.png",
+        "data:image/svg+xml;base64,PHN2Zz48L3N2Zz4=",
+        "data:image/png;base64,iVBORw0KGgo=",
+    ] {
+        let pdf = PdfBuilder::default()
+            .image(String::from(image))
+            .clear_under_image(true)
+            .to_bytes(&qrcode);
+        assert!(matches!(pdf, Err(SvgError::SvgError(_))));
+    }
+
+    // Paths are only read through `image_file`
+    let path = std::env::temp_dir().join("fast_qr_pdf_logo.png");
+    let bytes = crate::convert::embed::decode_base64(&png[22..]).unwrap();
+    std::fs::write(&path, bytes).unwrap();
+    let pdf = PdfBuilder::default()
+        .image(path.to_string_lossy().into_owned())
+        .to_bytes(&qrcode);
+    assert!(pdf.is_err());
+    let pdf = PdfBuilder::default()
+        .image_file(&path)
+        .unwrap()
+        .to_bytes(&qrcode)
+        .unwrap();
+    assert!(content(&pdf).contains("/Im0 Do"));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn pdf_decodes_png() {
+    use crate::convert::png::decode;
+    use resvg::tiny_skia::{Color as SkiaColor, Paint, PathBuilder, Pixmap, Transform};
+
+    let mut pixmap = Pixmap::new(37, 23).unwrap();
+    pixmap.fill(SkiaColor::from_rgba8(10, 200, 30, 255));
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(200, 20, 150, 128);
+    let circle = PathBuilder::from_circle(18f32, 11f32, 9f32).unwrap();
+    pixmap.fill_path(
+        &circle,
+        &paint,
+        resvg::tiny_skia::FillRule::Winding,
+        Transform::identity(),
+        None,
+    );
+    let bytes = pixmap.encode_png().unwrap();
+
+    let decoded = decode(&bytes).unwrap();
+    assert_eq!((decoded.width, decoded.height), (37, 23));
+    for (i, pixel) in pixmap.pixels().iter().enumerate() {
+        let color = pixel.demultiply();
+        assert_eq!(
+            &decoded.pixels[i * 4..i * 4 + 4],
+            &[color.red(), color.green(), color.blue(), color.alpha()]
+        );
+    }
+
+    // Transparent PNGs get a soft mask
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let mut transparent = Pixmap::new(8, 8).unwrap();
+    transparent.fill_path(
+        &circle,
+        &paint,
+        resvg::tiny_skia::FillRule::Winding,
+        Transform::identity(),
+        None,
+    );
+    let uri = crate::convert::embed::data_uri(&transparent.encode_png().unwrap()).unwrap();
+    let pdf = PdfBuilder::default().image(uri).to_bytes(&qrcode).unwrap();
+    assert!(String::from_utf8_lossy(&pdf).contains("/SMask 6 0 R"));
+}
+
+/// Draws the filled paths of a content stream in black, as svg
+#[cfg(feature = "image")]
+fn content_to_svg(content: &str, size: usize) -> String {
+    let mut out =
+        format!(r#"<svg viewBox="0 0 {size} {size}" xmlns="http://www.w3.org/2000/svg">"#);
+    let mut path = String::new();
+    let mut operands: Vec<&str> = Vec::new();
+    let mut fill = String::from("#000000");
+
+    // Skips the transformation to page space
+    for token in content.lines().skip(1).flat_map(str::split_whitespace) {
+        match token {
+            "m" | "l" | "c" => {
+                let command = match token {
+                    "m" => 'M',
+                    "l" => 'L',
+                    _ => 'C',
+                };
+                path.push_str(&format!("{command}{}", operands.join(",")));
+            }
+            "h" => path.push('Z'),
+            "rg" => {
+                let channel = |value: &str| (value.parse::<f64>().unwrap() * 255f64).round() as u8;
+                fill = crate::convert::rgba2hex([
+                    channel(operands[0]),
+                    channel(operands[1]),
+                    channel(operands[2]),
+                    255,
+                ]);
+            }
+            "f" | "B" => {
+                let stroke = if token == "B" {
+                    format!(r#" stroke="{fill}" stroke-width=".3" stroke-linejoin="round""#)
+                } else {
+                    String::new()
+                };
+                out.push_str(&format!(r#"<path d="{path}"{stroke} fill="{fill}"/>"#));
+                path.clear();
+            }
+            _ => (),
+        }
+        if token.chars().next().map_or(false, char::is_alphabetic) {
+            operands.clear();
+        } else {
+            operands.push(token);
+        }
+    }
+
+    out.push_str("</svg>");
+    out
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn pdf_draws_like_svg() {
+    use crate::convert::image::ImageBuilder;
+    use crate::convert::{ConnectedShape, EyeBallShape, EyeFrameShape};
+    use resvg::{tiny_skia, usvg};
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let size = qrcode.size + 8;
+    let scale = 6u32;
+    let width = size as u32 * scale;
+
+    let render = |svg: &str| {
+        let tree = usvg::Tree::from_data(svg.as_bytes(), &usvg::Options::default()).unwrap();
+        let mut pixmap = tiny_skia::Pixmap::new(width, width).unwrap();
+        resvg::render(
+            &tree,
+            usvg::FitTo::Width(width),
+            tiny_skia::Transform::default(),
+            pixmap.as_mut(),
+        )
+        .unwrap();
+        pixmap
+    };
+
+    for shape in [
+        Shape::Circle,
+        Shape::RoundedSquare,
+        Shape::Diamond,
+        Shape::Square,
+    ] {
+        let mut pdf = PdfBuilder::default();
+        let mut image = ImageBuilder::default();
+        pdf.shape(shape);
+        image.shape(shape).fit_width(width);
+        if shape == Shape::Square {
+            // Also checks renderers and eyes
+            pdf.renderer(ConnectedShape::Liquid)
+                .finder_shape(EyeFrameShape::RoundedSquare, EyeBallShape::Circle);
+            image
+                .renderer(ConnectedShape::Liquid)
+                .finder_shape(EyeFrameShape::RoundedSquare, EyeBallShape::Circle);
+        }
+
+        let svg = content_to_svg(&content(&pdf.to_bytes(&qrcode).unwrap()), size);
+        let actual = render(&svg);
+        let expected = image.to_pixmap(&qrcode);
+
+        let different = actual
+            .pixels()
+            .iter()
+            .zip(expected.pixels())
+            .filter(|(a, b)| (i16::from(a.red()) - i16::from(b.red())).abs() > 64)
+            .count();
+        assert!(
+            different * 200 < (width * width) as usize,
+            "{shape:?}: {different} pixels differ"
+        );
+    }
+}
+
+#[test]
+fn pdf_draws_frames() {
+    use crate::convert::frame::{Frame, FrameStyle};
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let size = (qrcode.size + 8) as f64;
+    let pdf = PdfBuilder::default()
+        .frame(Frame::new(FrameStyle::Card).caption("SCAN ME"))
+        .to_bytes(&qrcode)
+        .unwrap();
+    let text = String::from_utf8_lossy(&pdf);
+    let content = content(&pdf);
+
+    // The page holds the frame and its caption band
+    let start = text.find("/MediaBox [0 0 ").unwrap() + 15;
+    let end = start + text[start..].find(']').unwrap();
+    let values: Vec<f64> = text[start..end]
+        .split(' ')
+        .map(|value| value.parse().unwrap())
+        .collect();
+    let points = |mm: f64| mm * 72f64 / 25.4f64;
+    assert!((values[0] - points(size + 2f64)).abs() < 1e-3);
+    assert!(values[1] > values[0]);
+
+    // The frame is drawn first, from the top left of the page
+    let frame = content.lines().nth(1).unwrap();
+    assert_eq!(frame, "q 1 0 0 1 -1 -1 cm");
+    assert!(content.contains("\nQ\n"));
+}
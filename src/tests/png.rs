@@ -0,0 +1,99 @@
+use crate::convert::png::decode;
+
+/// Builds a PNG from its header fields and filtered rows, stored uncompressed
+fn png(width: u32, height: u32, header: [u8; 5], rows: &[u8]) -> Vec<u8> {
+    let chunk = |out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]| {
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        out.extend_from_slice(kind);
+        out.extend_from_slice(data);
+        // The CRC is not checked
+        out.extend_from_slice(&[0; 4]);
+    };
+
+    let mut ihdr = Vec::new();
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    ihdr.extend_from_slice(&header);
+
+    // Zlib header, then a single stored block
+    let len = rows.len() as u16;
+    let mut idat = vec![0x78, 0x01, 1];
+    idat.extend_from_slice(&len.to_le_bytes());
+    idat.extend_from_slice(&(!len).to_le_bytes());
+    idat.extend_from_slice(rows);
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut out, b"IHDR", &ihdr);
+    chunk(&mut out, b"IDAT", &idat);
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+#[test]
+fn png_decodes_gray() {
+    let bytes = png(2, 2, [8, 0, 0, 0, 0], &[0, 0, 255, 2, 10, 0]);
+    let image = decode(&bytes).unwrap();
+    assert_eq!((image.width, image.height), (2, 2));
+    assert_eq!(
+        image.pixels,
+        [0, 0, 0, 255, 255, 255, 255, 255, 10, 10, 10, 255, 255, 255, 255, 255]
+    );
+}
+
+#[test]
+fn png_rejects_malformed_headers() {
+    let rows = [0, 0, 255, 2, 10, 0];
+
+    // Bit depths not allowed for the color type
+    for &(depth, color_type) in &[(0, 0), (3, 0), (32, 0), (4, 2), (16, 3), (1, 4), (2, 6)] {
+        assert!(decode(&png(2, 2, [depth, color_type, 0, 0, 0], &rows)).is_none());
+    }
+    // Unknown color type, compression, filter or interlace method
+    assert!(decode(&png(2, 2, [8, 5, 0, 0, 0], &rows)).is_none());
+    assert!(decode(&png(2, 2, [8, 0, 1, 0, 0], &rows)).is_none());
+    assert!(decode(&png(2, 2, [8, 0, 0, 1, 0], &rows)).is_none());
+    assert!(decode(&png(2, 2, [8, 0, 0, 0, 1], &rows)).is_none());
+    // Empty images
+    assert!(decode(&png(0, 2, [8, 0, 0, 0, 0], &rows)).is_none());
+    assert!(decode(&png(2, 0, [8, 0, 0, 0, 0], &rows)).is_none());
+    // Dimensions larger than the data, without allocating them
+    assert!(decode(&png(u32::MAX, u32::MAX, [16, 6, 0, 0, 0], &rows)).is_none());
+    assert!(decode(&png(3, 2, [8, 0, 0, 0, 0], &rows)).is_none());
+    // Missing signature
+    assert!(decode(&png(2, 2, [8, 0, 0, 0, 0], &rows)[1..]).is_none());
+
+    // Chunk length past the end
+    let mut bytes = png(2, 2, [8, 0, 0, 0, 0], &rows);
+    bytes[33..37].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert!(decode(&bytes).is_none());
+}
+
+#[test]
+fn png_survives_corrupted_bytes() {
+    let bytes = png(2, 2, [8, 0, 0, 0, 0], &[0, 0, 255, 2, 10, 0]);
+
+    // Truncated files
+    for len in 0..bytes.len() {
+        decode(&bytes[..len]);
+    }
+    // Every value of every byte
+    for i in 8..bytes.len() {
+        for value in 0..=255 {
+            let mut corrupted = bytes.clone();
+            corrupted[i] = value;
+            decode(&corrupted);
+        }
+    }
+    // Random data, after a valid header
+    let mut state = 0x2545_f491u32;
+    for _ in 0..200 {
+        let mut corrupted = bytes[..33].to_vec();
+        for _ in 0..64 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            corrupted.push(state as u8);
+        }
+        decode(&corrupted);
+    }
+}
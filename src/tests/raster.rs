@@ -0,0 +1,107 @@
+use crate::convert::crc::crc32;
+use crate::convert::png::zlib;
+use crate::convert::raster::{BitDepth, RasterBuilder};
+use crate::convert::Shape;
+use crate::QRBuilder;
+
+#[test]
+fn raster_checksums() {
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+
+    // Header, then a fixed Huffman block, then adler32
+    let compressed = zlib(b"Wikipedia");
+    assert_eq!(&compressed[..2], &[0x78, 0x01]);
+    assert_eq!(
+        &compressed[compressed.len() - 4..],
+        &[0x11, 0xe6, 0x03, 0x98]
+    );
+}
+
+#[test]
+fn raster_module_aligned() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let bitmap = RasterBuilder::default()
+        .margin(2)
+        .scale(3)
+        .to_bitmap(&qrcode);
+
+    assert_eq!(bitmap.width, (qrcode.size + 4) * 3);
+    assert_eq!(bitmap.height, bitmap.width);
+    for y in 0..bitmap.height {
+        for x in 0..bitmap.width {
+            let (my, mx) = (y / 3, x / 3);
+            let inside = (2..qrcode.size + 2).contains(&my) && (2..qrcode.size + 2).contains(&mx);
+            let dark = inside && qrcode[my - 2][mx - 2].value();
+            assert_eq!(bitmap.get(x, y), if dark { 0 } else { 255 }, "({y}, {x})");
+        }
+    }
+}
+
+#[test]
+fn raster_shapes() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let bitmap = RasterBuilder::default()
+        .margin(0)
+        .scale(10)
+        .shape(Shape::Circle)
+        .module_color(20)
+        .background_color(240)
+        .to_bitmap(&qrcode);
+
+    // Top left module of the finder pattern
+    assert_eq!(bitmap.get(5, 5), 20);
+    assert_eq!(bitmap.get(0, 0), 240);
+    assert_eq!(bitmap.get(9, 0), 240);
+
+    let bitmap = RasterBuilder::default()
+        .margin(0)
+        .scale(10)
+        .shape(Shape::Horizontal)
+        .to_bitmap(&qrcode);
+    assert_eq!(bitmap.get(0, 0), 255);
+    assert_eq!(bitmap.get(0, 1), 0);
+    assert_eq!(bitmap.get(0, 8), 0);
+    assert_eq!(bitmap.get(0, 9), 255);
+}
+
+#[test]
+fn raster_png_size() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let builder = RasterBuilder::default();
+
+    let one = builder.to_png(&qrcode, BitDepth::One);
+    let eight = builder.to_png(&qrcode, BitDepth::Eight);
+    assert!(one.starts_with(b"\x89PNG\r\n\x1a\n"));
+    assert!(one.len() < eight.len());
+    // Repeated rows & runs of modules compress well
+    assert!(eight.len() < 8 * 1024);
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn raster_png_decodes() {
+    use resvg::tiny_skia::Pixmap;
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let mut builder = RasterBuilder::default();
+    builder.scale(3).module_color(64);
+    let bitmap = builder.to_bitmap(&qrcode);
+
+    for depth in [BitDepth::One, BitDepth::Eight] {
+        let pixmap = Pixmap::decode_png(&bitmap.to_png(depth)).unwrap();
+        assert_eq!(pixmap.width() as usize, bitmap.width);
+        assert_eq!(pixmap.height() as usize, bitmap.height);
+
+        for y in 0..bitmap.height {
+            for x in 0..bitmap.width {
+                let expected = match (depth, bitmap.get(x, y)) {
+                    (BitDepth::One, gray) if gray < 128 => 0,
+                    (BitDepth::One, _) => 255,
+                    (BitDepth::Eight, gray) => gray,
+                };
+                let pixel = pixmap.pixel(x as u32, y as u32).unwrap();
+                assert_eq!(pixel.red(), expected, "{depth:?} ({y}, {x})");
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::convert::source::{BitOrder, Language, SourceBuilder};
+use crate::QRBuilder;
+
+/// Hexadecimal literals between the braces or brackets of the array
+fn literals(source: &str, open: char, close: char) -> Vec<u8> {
+    let start = source.rfind(open).unwrap() + 1;
+    let end = source.rfind(close).unwrap();
+    source[start..end]
+        .split(',')
+        .map(str::trim)
+        .filter(|literal| !literal.is_empty())
+        .map(|literal| u8::from_str_radix(literal.trim_start_matches("0x"), 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn source_bit_packing() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let side = qrcode.size + 2;
+    let row = (side + 7) / 8;
+
+    for bit_order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+        let bytes = SourceBuilder::default()
+            .margin(1)
+            .bit_order(bit_order)
+            .to_bytes(&qrcode);
+        assert_eq!(bytes.len(), side * row);
+
+        for y in 0..side {
+            for x in 0..row * 8 {
+                let shift = match bit_order {
+                    BitOrder::MsbFirst => 7 - x % 8,
+                    BitOrder::LsbFirst => x % 8,
+                };
+                let bit = bytes[y * row + x / 8] >> shift & 1 == 1;
+                let (mx, my) = (x.wrapping_sub(1), y.wrapping_sub(1));
+                let dark = mx < qrcode.size && my < qrcode.size && qrcode[my][mx].value();
+                assert_eq!(bit, dark, "({x}, {y})");
+            }
+        }
+    }
+}
+
+#[test]
+fn source_languages() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let mut builder = SourceBuilder::default();
+    builder.name("login-qr").margin(0);
+    let bytes = builder.to_bytes(&qrcode);
+    let side = qrcode.size;
+
+    let c = builder.to_str(&qrcode, Language::C);
+    assert!(c.starts_with("#ifndef LOGIN_QR_H\n#define LOGIN_QR_H\n\n#include <stdint.h>\n"));
+    assert!(c.contains(&format!("#define LOGIN_QR_WIDTH {side}\n#define LOGIN_QR_HEIGHT {side}\n#define LOGIN_QR_BYTES_PER_ROW 4\n")));
+    assert!(c.contains(&format!(
+        "static const uint8_t login_qr[{}] = {{\n",
+        bytes.len()
+    )));
+    assert!(c.ends_with("#endif /* LOGIN_QR_H */\n"));
+    assert_eq!(literals(&c, '{', '}'), bytes);
+
+    let rust = builder.to_str(&qrcode, Language::Rust);
+    assert!(rust.contains(&format!("pub const LOGIN_QR_WIDTH: usize = {side};\n")));
+    assert!(rust.contains(&format!("pub const LOGIN_QR: [u8; {}] = [\n", bytes.len())));
+    assert_eq!(literals(&rust, '[', ']'), bytes);
+
+    // X BitMaps are least significant bit first, whatever the bit order
+    let xbm = builder.to_str(&qrcode, Language::Xbm);
+    assert!(xbm.starts_with(&format!("#define login_qr_width {side}\n#define login_qr_height {side}\nstatic unsigned char login_qr_bits[] = {{\n")));
+    let lsb = builder.bit_order(BitOrder::LsbFirst).to_bytes(&qrcode);
+    assert_eq!(literals(&xbm, '{', '}'), lsb);
+    assert_eq!(builder.to_str(&qrcode, Language::Xbm), xbm);
+
+    assert!(SourceBuilder::default()
+        .name("2fa")
+        .to_str(&qrcode, Language::C)
+        .contains("uint8_t _2fa["));
+}
@@ -20,3 +20,737 @@ fn it_embeds_an_image_via_data_uri() {
     let expected_href = format!(r#"href="{data_uri}""#);
     assert!(svg.contains(&expected_href));
 }
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_places_the_image_on_the_reserved_logo_area() {
+    use crate::convert::svg::SvgBuilder;
+    use crate::convert::Builder;
+    use crate::qr::LogoSize;
+    use crate::QRBuilder;
+
+    let qrcode = QRBuilder::new("https://example.com/")
+        .logo(LogoSize::Modules(7))
+        .build()
+        .unwrap();
+
+    let svg = SvgBuilder::default()
+        .margin(4)
+        .image(String::from("data:image/png;base64,"))
+        .to_str(&qrcode);
+
+    let start = (qrcode.size - 7) / 2 + 4;
+    let expected_rect = format!(r#"<rect x="{start}" y="{start}" width="7" height="7""#);
+    assert!(svg.contains(&expected_rect));
+
+    let expected_image = format!(r#"<image x="{0}.00" y="{0}.00" width="5.00""#, start + 1);
+    assert!(svg.contains(&expected_image));
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_clears_modules_under_the_image() {
+    use crate::analysis::{DamageReport, Occlusion};
+    use crate::convert::svg::SvgBuilder;
+    use crate::convert::{Builder, ImageBackgroundShape};
+    use crate::{ModuleType, QRBuilder, Version, ECL};
+
+    let qrcode = QRBuilder::new("https://example.com/")
+        .ecl(ECL::H)
+        .version(Version::V05)
+        .build()
+        .unwrap();
+
+    let mut builder = SvgBuilder::default();
+    builder
+        .image(String::from("data:image/png;base64,"))
+        .image_background_shape(ImageBackgroundShape::Circle)
+        .clear_under_image(true);
+
+    let cleared = match builder.cleared_modules(&qrcode) {
+        Occlusion::Modules(modules) => modules,
+        _ => unreachable!(),
+    };
+    assert!(!cleared.is_empty());
+    assert!(cleared
+        .iter()
+        .all(|&(y, x)| qrcode[y][x].module_type() == ModuleType::Data));
+
+    let svg = builder.to_str(&qrcode);
+    for &(y, x) in &cleared {
+        let command = format!("M{},{}h1v1h-1", x + 4, y + 4);
+        assert!(!svg.contains(&command));
+    }
+
+    let report = DamageReport::new(&qrcode, &Occlusion::Modules(cleared));
+    assert!(report.is_recoverable());
+
+    // Function patterns are kept, here the alignment pattern centered on (30, 30)
+    let svg = builder
+        .image_size(5f64, 1f64)
+        .image_position(30.5 + 4f64, 30.5 + 4f64)
+        .to_str(&qrcode);
+    let command = format!("M{0},{0}h1v1h-1", 30 + 4);
+    assert!(svg.contains(&command));
+    assert!(!svg.contains(r##"fill="#ffffff"/><rect"##));
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_sniffs_and_inlines_images() {
+    use crate::convert::base64;
+    use crate::convert::embed::MimeType;
+    use base64::engine::general_purpose;
+    use base64::Engine;
+
+    for bytes in [&b""[..], b"f", b"fo", b"foo", b"foob", b"fooba", b"foobar"] {
+        assert_eq!(base64(bytes), general_purpose::STANDARD.encode(bytes));
+    }
+
+    let png = include_bytes!("../../assets/banner.png");
+    assert_eq!(MimeType::sniff(png), Some(MimeType::Png));
+    let svg = include_bytes!("../../assets/banner.svg");
+    assert_eq!(MimeType::sniff(svg), Some(MimeType::Svg));
+    assert_eq!(
+        MimeType::sniff(b"\xff\xd8\xff\xe0\x00\x10JFIF"),
+        Some(MimeType::Jpeg)
+    );
+    assert_eq!(
+        MimeType::sniff(b"RIFF\x24\x00\x00\x00WEBPVP8L"),
+        Some(MimeType::Webp)
+    );
+    assert_eq!(MimeType::sniff(b"GIF89a"), None);
+    assert_eq!(MimeType::sniff(b"<html></html>"), None);
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_embeds_an_image_file() {
+    use crate::convert::svg::{SvgBuilder, SvgError};
+    use crate::convert::Builder;
+    use crate::QRBuilder;
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+
+    let svg = SvgBuilder::default()
+        .image_file("./assets/example.com.svg")
+        .unwrap()
+        .to_str(&qrcode);
+    assert!(svg.contains(r#"href="data:image/svg+xml;base64,"#));
+    assert!(!svg.contains("example.com.svg"));
+
+    let svg = SvgBuilder::default()
+        .margin(2)
+        .image_bytes(include_bytes!("../../assets/banner.png"))
+        .unwrap()
+        .to_str(&qrcode);
+    assert!(svg.contains(r#"href="data:image/png;base64,iVBORw0KGgo"#));
+
+    let mut builder = SvgBuilder::default();
+    let missing = builder.image_file("./assets/missing.png");
+    assert!(matches!(missing, Err(SvgError::IoError(_))));
+
+    let unsupported = builder.image_bytes(b"GIF89a");
+    assert!(matches!(unsupported, Err(SvgError::SvgError(_))));
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn it_downscales_embedded_images() {
+    use crate::convert::embed::downscale;
+
+    let png = include_bytes!("../../assets/banner.png");
+    let downscaled = downscale(png, 100).unwrap();
+
+    let pixmap = resvg::tiny_skia::Pixmap::decode_png(&downscaled).unwrap();
+    assert_eq!((pixmap.width(), pixmap.height()), (100, 24));
+    assert!(downscaled.len() < png.len());
+
+    let svg = include_bytes!("../../assets/banner.svg");
+    assert_eq!(downscale(svg, 100).unwrap(), svg.to_vec());
+
+    assert!(downscale(b"RIFF\x24\x00\x00\x00WEBPVP8L", 100).is_err());
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_draws_modules_at_their_position() {
+    use crate::convert::svg::SvgBuilder;
+    use crate::convert::Builder;
+    use crate::QRBuilder;
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let svg = SvgBuilder::default().margin(0).to_str(&qrcode);
+
+    for y in 0..qrcode.size {
+        for x in 0..qrcode.size {
+            let command = format!("M{x},{y}h1v1h-1");
+            assert_eq!(svg.contains(&command), qrcode[y][x].value());
+        }
+    }
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_draws_finder_patterns_as_a_whole() {
+    use crate::convert::svg::SvgBuilder;
+    use crate::convert::{Builder, EyeBallShape, EyeFrameShape};
+    use crate::{ModuleType, QRBuilder};
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let mut builder = SvgBuilder::default();
+    builder
+        .margin(0)
+        .finder_shape(EyeFrameShape::RoundedSquare, EyeBallShape::Circle)
+        .finder_color("#ff0000", "#00ff00");
+    let svg = builder.to_str(&qrcode);
+
+    // Finder modules are not drawn one by one
+    for y in 0..qrcode.size {
+        for x in 0..qrcode.size {
+            let command = format!("M{x},{y}h1v1h-1");
+            let is_finder = qrcode[y][x].module_type() == ModuleType::FinderPattern;
+            assert_eq!(svg.contains(&command), qrcode[y][x].value() && !is_finder);
+        }
+    }
+
+    // One frame and one ball per finder pattern
+    let n = qrcode.size;
+    for (y, x) in [(0, 0), (0, n - 7), (n - 7, 0)] {
+        assert!(svg.contains(&EyeFrameShape::RoundedSquare(y, x, 7)));
+        assert!(svg.contains(&EyeBallShape::Circle(y + 2, x + 2, 3)));
+    }
+    assert!(svg.contains(r##"fill="#ff0000""##));
+    assert!(svg.contains(r##"fill="#00ff00""##));
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_draws_alignment_patterns_as_a_whole() {
+    use crate::convert::svg::SvgBuilder;
+    use crate::convert::{Builder, EyeBallShape, EyeFrameShape};
+    use crate::{QRBuilder, Version};
+
+    fn custom(y: usize, x: usize, size: usize) -> String {
+        format!("M{x},{y}l{size},{size}")
+    }
+
+    let qrcode = QRBuilder::new("https://example.com/")
+        .version(Version::V07)
+        .build()
+        .unwrap();
+    let mut builder = SvgBuilder::default();
+    builder
+        .margin(0)
+        .alignment_shape(EyeFrameShape::Command(custom), EyeBallShape::Diamond);
+    let svg = builder.to_str(&qrcode);
+
+    // Version 7 has 6 alignment patterns, centered on 6, 22 and 38
+    for (y, x) in [(4, 20), (20, 4), (20, 20), (20, 36), (36, 20), (36, 36)] {
+        assert!(svg.contains(&format!("M{x},{y}l5,5")));
+    }
+    assert_eq!(svg.matches("l5,5").count(), 6);
+
+    // Finder patterns are still drawn module by module
+    assert!(svg.contains("M0,0h1v1h-1"));
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_fills_with_gradients_and_patterns() {
+    use crate::convert::svg::SvgBuilder;
+    use crate::convert::{Builder, LinearGradient, Pattern, RadialGradient, Shape};
+    use crate::QRBuilder;
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let gradient = || {
+        LinearGradient::new(0f64)
+            .stop(0f64, "#ff0000")
+            .stop(1f64, "#0000ff")
+    };
+    let mut builder = SvgBuilder::default();
+    builder
+        .margin(0)
+        .module_color(gradient())
+        .shape_color(Shape::Square, gradient())
+        .shape_color(
+            Shape::Circle,
+            RadialGradient::new(0.5f64, 0.5f64, 0.5f64).stop(0f64, [0, 255, 0]),
+        )
+        .background_color(Pattern::new(
+            2f64,
+            2f64,
+            r##"<rect width="1" height="1" fill="#eee"/>"##,
+        ));
+    let svg = builder.to_str(&qrcode);
+
+    let n = qrcode.size;
+    assert!(svg.contains(&format!(
+        r##"<linearGradient id="fqr-paint-1" gradientUnits="userSpaceOnUse" x1="0" y1="{0}" x2="{1}" y2="{0}"><stop offset="0" stop-color="#ff0000"/><stop offset="1" stop-color="#0000ff"/></linearGradient>"##,
+        n as f64 / 2f64,
+        n
+    )));
+    assert!(svg.contains(r##"<stop offset="0" stop-color="#00ff00"/></radialGradient>"##));
+    assert!(svg.contains(
+        r#"<pattern id="fqr-paint-0" patternUnits="userSpaceOnUse" width="2" height="2">"#
+    ));
+
+    // Identical gradients are only defined once
+    assert_eq!(svg.matches("<linearGradient").count(), 1);
+    assert_eq!(svg.matches(r#"fill="url(#fqr-paint-1)""#).count(), 1);
+    assert!(svg.contains(&format!(
+        r#"<rect width="{n}px" height="{n}px" fill="url(#fqr-paint-0)"/>"#
+    )));
+    assert!(svg.find("</defs>").unwrap() < svg.find(&format!(r#"<rect width="{n}px""#)).unwrap());
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn it_renders_gradients_as_image() {
+    use crate::convert::image::ImageBuilder;
+    use crate::convert::{Builder, LinearGradient};
+    use crate::QRBuilder;
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let pixmap = ImageBuilder::default()
+        .background_color(
+            LinearGradient::new(90f64)
+                .stop(0f64, "#ff0000")
+                .stop(1f64, "#0000ff"),
+        )
+        .fit_width(100)
+        .to_pixmap(&qrcode);
+
+    // Top is red, bottom is blue
+    let top = pixmap.pixel(50, 0).unwrap();
+    let bottom = pixmap.pixel(50, pixmap.height() - 1).unwrap();
+    assert!(top.red() > 200 && top.blue() < 50);
+    assert!(bottom.blue() > 200 && bottom.red() < 50);
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_draws_modules_with_renderers() {
+    use crate::convert::svg::SvgBuilder;
+    use crate::convert::{Builder, ModuleContext, ModuleOutput};
+    use crate::QRBuilder;
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+
+    // Captured state, colors by row parity
+    let colors = [String::from("#ff0000"), String::from("#0000ff")];
+    let mut builder = SvgBuilder::default();
+    builder.margin(1).renderer(move |module: &ModuleContext| {
+        let path = format!("M{},{}h1v1h-1", module.x, module.y);
+        let (y, _) = module.position();
+        ModuleOutput::colored(path, colors[y % 2].as_str())
+    });
+    let svg = builder.to_str(&qrcode);
+
+    // No default square layer, one path per color
+    assert_eq!(svg.matches("<path").count(), 2);
+    let red = svg.find(r##"fill="#ff0000""##).unwrap();
+    let blue = svg.find(r##"fill="#0000ff""##).unwrap();
+    assert!(svg[..red].contains("M1,1h1v1h-1"));
+    assert!(svg[red..blue].contains("M1,2h1v1h-1"));
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_gives_renderers_the_neighbourhood() {
+    use crate::convert::svg::SvgBuilder;
+    use crate::convert::{Builder, ModuleContext};
+    use crate::QRBuilder;
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+
+    // Only draws modules without a dark module on their right
+    let mut builder = SvgBuilder::default();
+    builder.margin(0).renderer(|module: &ModuleContext| {
+        assert_eq!(module.neighbour(0, 0), Some(module.module));
+        assert_eq!(module.neighbour(-(module.y as isize) - 1, 0), None);
+        if module.is_dark(0, 1) {
+            String::new()
+        } else {
+            format!("M{},{}h1v1h-1", module.x, module.y)
+        }
+    });
+    let svg = builder.to_str(&qrcode);
+
+    for y in 0..qrcode.size {
+        for x in 0..qrcode.size {
+            let right = x + 1 < qrcode.size && qrcode[y][x + 1].value();
+            let command = format!("M{x},{y}h1v1h-1");
+            assert_eq!(svg.contains(&command), qrcode[y][x].value() && !right);
+        }
+    }
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_connects_neighbouring_modules() {
+    use crate::convert::{ConnectedShape, ModuleContext, ModuleRenderer};
+    use crate::QRBuilder;
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let render =
+        |shape: ConnectedShape, y, x| shape.render(&ModuleContext::new(&qrcode, y, x, 0)).path;
+
+    // Top left corner of the top left finder pattern: only the outer corner is rounded
+    assert_eq!(
+        render(ConnectedShape::Rounded, 0, 0),
+        "M0.5,0h.5v.5v.5h-.5h-.5v-.5a.5,.5 0 0,1 .5,-.5z"
+    );
+    // Middle of the top side of the finder pattern, joined on both sides
+    assert_eq!(
+        render(ConnectedShape::HorizontalPills, 0, 3),
+        "M3.5,0h.5v.5v.5h-.5h-.5v-.5v-.5h.5z"
+    );
+    assert_eq!(
+        render(ConnectedShape::VerticalPills, 0, 3),
+        "M3.5,0a.5,.5 0 0,1 .5,.5a.5,.5 0 0,1 -.5,.5a.5,.5 0 0,1 -.5,-.5a.5,.5 0 0,1 .5,-.5z"
+    );
+
+    // The corner of the finder pattern ring has an inner corner at (1, 1)
+    let liquid = render(ConnectedShape::Liquid, 0, 0);
+    assert!(liquid.ends_with("M1,1.5v-.5h.5a.5,.5 0 0,0 -.5,.5z"));
+    assert!(!render(ConnectedShape::Rounded, 0, 0).contains("M1,1.5"));
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_keeps_compact_svgs_small() {
+    use crate::convert::svg::{PathMode, SvgBuilder};
+    use crate::{QRBuilder, Version, ECL};
+
+    // Size budgets in bytes for `PathMode::Modules`, `Runs` and `Outline`
+    for (version, budgets) in [
+        (Version::V02, [4_274, 2_229, 1_249]),
+        (Version::V10, [20_402, 10_581, 8_073]),
+        (Version::V40, [215_263, 97_396, 79_435]),
+    ] {
+        let qrcode = QRBuilder::new("https://example.com/")
+            .version(version)
+            .ecl(ECL::L)
+            .build()
+            .unwrap();
+
+        for (mode, budget) in [PathMode::Modules, PathMode::Runs, PathMode::Outline]
+            .into_iter()
+            .zip(budgets)
+        {
+            let len = SvgBuilder::default().path_mode(mode).to_str(&qrcode).len();
+            assert!(len <= budget, "{version:?} {mode:?}: {len} > {budget}");
+        }
+    }
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_writes_runs_and_outlines() {
+    use crate::convert::svg::{PathMode, SvgBuilder};
+    use crate::convert::Builder;
+    use crate::QRBuilder;
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+
+    // The top left finder pattern starts with a 7 modules run, then its ring
+    let runs = SvgBuilder::default()
+        .margin(0)
+        .path_mode(PathMode::Runs)
+        .to_str(&qrcode);
+    assert!(runs.contains(r#"<path d="M0,0h7v1h-7z"#));
+
+    let outline = SvgBuilder::default()
+        .margin(0)
+        .path_mode(PathMode::Outline)
+        .to_str(&qrcode);
+    assert!(outline.contains(r#"<path d="M0,0h7v7h-7z"#));
+    // Hole of the finder pattern, counterclockwise
+    assert!(outline.contains("m-17,1v5h5v-5z"));
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn it_renders_compact_svgs_identically() {
+    use crate::convert::image::ImageBuilder;
+    use crate::convert::svg::PathMode;
+    use crate::{QRBuilder, Version};
+
+    let qrcode = QRBuilder::new("https://example.com/")
+        .version(Version::V07)
+        .build()
+        .unwrap();
+    let scale = 4;
+    let width = (qrcode.size + 8) as u32 * scale;
+
+    let render = |mode: PathMode| {
+        let mut builder = ImageBuilder::default();
+        builder.fit_width(width).path_mode(mode);
+        builder.to_pixmap(&qrcode)
+    };
+    let expected = render(PathMode::Modules);
+
+    for mode in [PathMode::Runs, PathMode::Outline] {
+        let pixmap = render(mode);
+        for y in 0..qrcode.size + 8 {
+            for x in 0..qrcode.size + 8 {
+                let (px, py) = (x as u32 * scale + scale / 2, y as u32 * scale + scale / 2);
+                assert_eq!(
+                    pixmap.pixel(px, py).unwrap().red(),
+                    expected.pixel(px, py).unwrap().red(),
+                    "{mode:?} ({y}, {x})"
+                );
+            }
+        }
+    }
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_groups_modules_by_type() {
+    use crate::convert::svg::SvgBuilder;
+    use crate::convert::{Builder, EyeBallShape, EyeFrameShape};
+    use crate::{QRBuilder, Version};
+
+    let qrcode = QRBuilder::new("https://example.com/")
+        .version(Version::V07)
+        .build()
+        .unwrap();
+    let svg = SvgBuilder::default()
+        .margin(0)
+        .group_by_type(true)
+        .class_prefix("qr-")
+        .to_str(&qrcode);
+
+    for group in [
+        "finder",
+        "alignment",
+        "timing",
+        "format",
+        "version",
+        "dark-module",
+        "data",
+    ] {
+        assert!(svg.contains(&format!(r#"<g class="qr-{group}"><path d=""#)));
+    }
+    assert!(svg.contains(r#"<rect width="45px" height="45px" class="qr-background""#));
+
+    // Top left finder pattern corner is in the finder group only
+    let finder = svg.find(r#"<g class="qr-finder">"#).unwrap();
+    let alignment = svg.find(r#"<g class="qr-alignment">"#).unwrap();
+    let corner = svg.find(r#"d="M0,0h1v1h-1"#).unwrap();
+    assert!(finder < corner && corner < alignment);
+    assert_eq!(svg.matches("M0,0h1v1h-1").count(), 1);
+    assert!(!svg.contains(r#"d="""#));
+
+    // Styled finders and alignments are only drawn in their group, with no
+    // empty layer for the modules they replace
+    let svg = SvgBuilder::default()
+        .group_by_type(true)
+        .class_prefix("qr-")
+        .finder_shape(EyeFrameShape::Square, EyeBallShape::Square)
+        .alignment_shape(EyeFrameShape::Square, EyeBallShape::Square)
+        .to_str(&qrcode);
+    assert!(!svg.contains(r#"d="""#));
+    assert_eq!(svg.matches("<g ").count(), 7);
+    assert_eq!(svg.matches("<path").count(), 2 + 2 + 5);
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_sanitizes_prefixes() {
+    use crate::convert::svg::SvgBuilder;
+    use crate::convert::{Builder, LinearGradient};
+    use crate::QRBuilder;
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let svg = SvgBuilder::default()
+        .group_by_type(true)
+        .id_prefix(r#"a"><script>-"#)
+        .class_prefix("b\" {} .c_")
+        .background_color(LinearGradient::new(0f64).stop(0f64, "#ffffff"))
+        .to_str(&qrcode);
+
+    assert!(!svg.contains("<script>"));
+    assert!(svg.contains(r#"<linearGradient id="ascript-paint-0""#));
+    assert!(svg.contains(r#"<g class="bc_finder">"#));
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_adds_accessibility_and_dark_mode() {
+    use crate::convert::svg::SvgBuilder;
+    use crate::convert::{Builder, LinearGradient};
+    use crate::QRBuilder;
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let svg = SvgBuilder::default()
+        .title("Example & co")
+        .description("Link to <example.com>")
+        .id_prefix("qr1-")
+        .dark_mode("#ffffff", "#000000")
+        .background_color(LinearGradient::new(0f64).stop(0f64, "#ffffff"))
+        .to_str(&qrcode);
+
+    assert!(svg.starts_with(
+        r#"<svg viewBox="0 0 33 33" xmlns="http://www.w3.org/2000/svg" role="img" aria-labelledby="qr1-title qr1-desc"><title id="qr1-title">Example &amp; co</title><desc id="qr1-desc">Link to &lt;example.com&gt;</desc><style>@media (prefers-color-scheme: dark)"#
+    ));
+    assert!(svg.contains(".fqr-module { fill: #ffffff; } .fqr-stroked { stroke: #ffffff; }"));
+    assert!(svg.contains(".fqr-background { fill: #000000; }"));
+    assert!(svg.contains(r##"class="fqr-module" fill="#000000""##));
+    assert!(svg.contains(r#"<linearGradient id="qr1-paint-0""#));
+
+    // Nothing is added by default
+    let svg = SvgBuilder::default().to_str(&qrcode);
+    assert!(!svg.contains("class="));
+    assert!(!svg.contains("<style>"));
+    assert!(!svg.contains("role="));
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_draws_captions_as_paths() {
+    use crate::convert::font::{text_path, width};
+
+    // 'I' is a 3 pixels wide bar with serifs, 'l' is drawn as 'L'
+    assert_eq!(
+        text_path("I", 0f64, 0f64, 1f64),
+        "M1,0L4,0L4,1L3,1L3,6L4,6L4,7L1,7L1,6L2,6L2,1L1,1z"
+    );
+    assert_eq!(
+        text_path("l", 0f64, 0f64, 1f64),
+        text_path("L", 0f64, 0f64, 1f64)
+    );
+    // Unknown characters are drawn as '?'
+    assert_eq!(
+        text_path("é", 1f64, 2f64, 0.5f64),
+        text_path("?", 1f64, 2f64, 0.5f64)
+    );
+
+    assert_eq!(width(""), 0);
+    assert_eq!(width("SCAN ME"), 7 * 6 - 1);
+    assert!(text_path(" ", 0f64, 0f64, 1f64).is_empty());
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_frames_the_code() {
+    use crate::convert::frame::{CaptionPosition, Frame, FrameStyle};
+    use crate::convert::svg::SvgBuilder;
+    use crate::convert::Builder;
+    use crate::QRBuilder;
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let size = qrcode.size + 8;
+
+    // Border of 1 module, caption band of 7 * .5 + 2 modules
+    let svg = SvgBuilder::default()
+        .frame(Frame::new(FrameStyle::Border).caption("Scan me"))
+        .to_str(&qrcode);
+    assert!(svg.starts_with(&format!(
+        r#"<svg viewBox="0 0 {} {}.5" xmlns="http://www.w3.org/2000/svg">"#,
+        size + 2,
+        size + 2 + 5
+    )));
+    assert!(svg.contains(r#"<g transform="translate(1,1)"><rect"#));
+    assert!(!svg.contains("<text"));
+
+    // The code is moved under the tab
+    let svg = SvgBuilder::default()
+        .frame(
+            Frame::new(FrameStyle::Tab)
+                .caption("SCAN ME")
+                .position(CaptionPosition::Above),
+        )
+        .to_str(&qrcode);
+    assert!(svg.contains(r#"<g transform="translate(1,7.5)">"#));
+    assert!(svg.contains(&format!(
+        r#"<svg viewBox="0 0 {} {}.5""#,
+        size + 2,
+        size + 2 + 1 + 5
+    )));
+
+    // Card caption is drawn in the background color
+    let svg = SvgBuilder::default()
+        .module_color("#112233")
+        .background_color("#aabbcc")
+        .frame(Frame::new(FrameStyle::Card).caption("SCAN ME"))
+        .to_str(&qrcode);
+    assert!(svg.contains(r##"a2,2 0 0 1 2,-2z" fill="#112233"/><path d="M"##));
+    assert!(svg.contains(r##"z" fill="#aabbcc"/><g transform"##));
+
+    // No caption, no band
+    let svg = SvgBuilder::default()
+        .frame(Frame::new(FrameStyle::Tab))
+        .to_str(&qrcode);
+    assert!(svg.starts_with(&format!(r#"<svg viewBox="0 0 {0} {0}""#, size + 2)));
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn it_checks_the_image_area() {
+    use crate::convert::svg::SvgBuilder;
+    use crate::convert::Builder;
+    use crate::qr::LogoSize;
+    use crate::{QRBuilder, ECL};
+
+    let qrcode = QRBuilder::new("https://example.com/")
+        .ecl(ECL::H)
+        .build()
+        .unwrap();
+    let start = (qrcode.size - 5) / 2 + 4;
+
+    let mut builder = SvgBuilder::default();
+    builder
+        .image(String::from("data:image/png;base64,"))
+        .image_area(LogoSize::Modules(5));
+    let svg = builder.to_str(&qrcode);
+    let expected_background = format!(r#"<rect x="{0}.00" y="{0}.00" width="5.00""#, start);
+    assert!(svg.contains(&expected_background));
+    assert!(builder.check_image(&qrcode).is_ok());
+
+    builder.image_area(LogoSize::Percent(60f64));
+    assert!(builder.check_image(&qrcode).is_err());
+    // Only checked when asked
+    assert!(builder.to_str(&qrcode).contains("<image"));
+
+    #[cfg(feature = "image")]
+    {
+        let mut image = crate::convert::image::ImageBuilder::default();
+        image.image_area(LogoSize::Percent(60f64));
+        assert!(image.check_image(&qrcode).is_ok());
+        image.image(String::from("data:image/png;base64,"));
+        assert!(image.check_image(&qrcode).is_err());
+    }
+    #[cfg(feature = "pdf")]
+    {
+        let mut pdf = crate::convert::pdf::PdfBuilder::default();
+        pdf.image(String::from("data:image/png;base64,"))
+            .image_area(LogoSize::Percent(60f64));
+        assert!(pdf.check_image(&qrcode).is_err());
+    }
+    #[cfg(feature = "eps")]
+    {
+        let mut eps = crate::convert::eps::EpsBuilder::default();
+        eps.image(String::from("data:image/png;base64,"))
+            .image_area(LogoSize::Percent(60f64));
+        assert!(eps.check_image(&qrcode).is_err());
+    }
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn builders_are_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<crate::convert::svg::SvgBuilder>();
+    #[cfg(feature = "image")]
+    assert_send_sync::<crate::convert::image::ImageBuilder>();
+    #[cfg(feature = "pdf")]
+    assert_send_sync::<crate::convert::pdf::PdfBuilder>();
+    #[cfg(feature = "eps")]
+    assert_send_sync::<crate::convert::eps::EpsBuilder>();
+    #[cfg(feature = "html")]
+    assert_send_sync::<crate::convert::html::HtmlBuilder>();
+}
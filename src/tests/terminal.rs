@@ -0,0 +1,255 @@
+use crate::convert::terminal::{AnsiColor, Charset, Polarity, TerminalBuilder};
+use crate::{Mask, QRBuilder, QRCode, Version, ECL};
+
+fn qrcode() -> QRCode {
+    QRBuilder::new("fast_qr")
+        .version(Version::V01)
+        .ecl(ECL::L)
+        .mask(Mask::Checkerboard)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn terminal_default_is_to_str() {
+    let qrcode = qrcode();
+    let expected = "▄▄▄▄▄▄▄▄▄▄▄▄▄▄▄▄▄▄▄▄▄▄▄
+█ ▄▄▄▄▄ ██ ▀ ▄█ ▄▄▄▄▄ █
+█ █   █ █▄ █ ▄█ █   █ █
+█ █▄▄▄█ ███▄█ █ █▄▄▄█ █
+█▄▄▄▄▄▄▄█ ▀▄▀ █▄▄▄▄▄▄▄█
+█  ▄▀  ▄▄▄  ▄█▄ ▀█▀▄▀▀█
+██▄▄ ▀▀▄▄ ▄▀█▄█▀▀ ▄▄  █
+█▄▄█▄██▄█ ▄▀▀▄▀▄█  █  █
+█ ▄▄▄▄▄ █ ▄█ ▀ ▄▄▄▄█▄ █
+█ █   █ █▄  ▄█▄ █ ▄█ ▄█
+█ █▄▄▄█ █  ▄█▄█▀▄ █▄▀▄█
+█▄▄▄▄▄▄▄█▄█▄█▄█▄▄█▄▄▄▄█";
+
+    assert_eq!(qrcode.to_str(), expected);
+    assert_eq!(TerminalBuilder::default().to_str(&qrcode), expected);
+}
+
+#[test]
+fn terminal_ascii_and_full_blocks() {
+    let qrcode = qrcode();
+    let ascii = TerminalBuilder::default()
+        .charset(Charset::Ascii)
+        .polarity(Polarity::Normal)
+        .margin(4)
+        .to_str(&qrcode);
+
+    let lines: Vec<&str> = ascii.lines().collect();
+    assert_eq!(lines.len(), qrcode.size + 8);
+    assert!(lines[..4].iter().all(|line| line.trim().is_empty()));
+    for (y, line) in lines[4..4 + qrcode.size].iter().enumerate() {
+        assert_eq!(line.len(), (qrcode.size + 8) * 2);
+        for x in 0..qrcode.size {
+            let drawn = &line[(x + 4) * 2..(x + 5) * 2];
+            assert_eq!(drawn == "##", qrcode[y][x].value());
+        }
+    }
+
+    // Same layout, inverted
+    let blocks = TerminalBuilder::default()
+        .charset(Charset::FullBlocks)
+        .margin(4)
+        .to_str(&qrcode);
+    let inverted: String = ascii
+        .chars()
+        .map(|c| match c {
+            '#' => ' ',
+            ' ' => '█',
+            c => c,
+        })
+        .collect();
+    assert_eq!(blocks, inverted);
+}
+
+#[test]
+fn terminal_compact_charsets() {
+    let qrcode = qrcode();
+
+    for (charset, width, height) in [(Charset::Quadrants, 2, 2), (Charset::Sextants, 2, 3)] {
+        let text = TerminalBuilder::default()
+            .charset(charset)
+            .polarity(Polarity::Normal)
+            .margin(0)
+            .to_str(&qrcode);
+        let lines: Vec<Vec<char>> = text.lines().map(|line| line.chars().collect()).collect();
+        let top = (height - qrcode.size % height) % height;
+        assert_eq!(lines.len(), (qrcode.size + top) / height);
+        assert_eq!(lines[0].len(), (qrcode.size + 1) / width);
+
+        // Reads the modules back from the characters
+        for y in 0..qrcode.size {
+            for x in 0..qrcode.size {
+                let c = lines[(y + top) / height][x / width];
+                let bit = ((y + top) % height) * width + x % width;
+                let bits = match (charset, c) {
+                    (_, ' ') => 0,
+                    (_, '█') => 0b11_1111,
+                    (Charset::Sextants, '▌') => 0b01_0101,
+                    (Charset::Sextants, '▐') => 0b10_1010,
+                    (Charset::Sextants, c) => {
+                        let index = c as u32 - 0x1fb00 + 1;
+                        index + u32::from(index >= 0b01_0101) + u32::from(index >= 0b10_1010 - 1)
+                    }
+                    (_, c) => " ▘▝▀▖▌▞▛▗▚▐▜▄▙▟█".chars().position(|q| q == c).unwrap() as u32,
+                };
+                assert_eq!(bits >> bit & 1 == 1, qrcode[y][x].value(), "{charset:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn terminal_colors() {
+    let qrcode = qrcode();
+    let text = TerminalBuilder::default()
+        .foreground(AnsiColor::Ansi16(9))
+        .background(AnsiColor::Ansi256(231))
+        .to_str(&qrcode);
+    for line in text.lines() {
+        assert!(line.starts_with("\x1b[91;48;5;231m"));
+        assert!(line.ends_with("\x1b[0m"));
+    }
+
+    let text = TerminalBuilder::default()
+        .background(AnsiColor::Ansi16(0))
+        .foreground(AnsiColor::TrueColor(255, 128, 0))
+        .to_str(&qrcode);
+    assert!(text.starts_with("\x1b[38;2;255;128;0;40m"));
+}
+
+#[cfg(feature = "raster")]
+#[test]
+fn terminal_sixel() {
+    use crate::convert::raster::RasterBuilder;
+    use crate::convert::terminal::Protocol;
+
+    let qrcode = qrcode();
+    // 29 modules, 3 pixels each
+    let sixel = TerminalBuilder::default()
+        .margin(4)
+        .image_width(100)
+        .to_protocol(&qrcode, Protocol::Sixel);
+    let bitmap = RasterBuilder::default()
+        .margin(4)
+        .scale(3)
+        .to_bitmap(&qrcode);
+
+    let header = "\x1bPq\"1;1;87;87#0;2;100;100;100#1;2;0;0;0";
+    assert!(sixel.starts_with(header));
+    assert!(sixel.ends_with("\x1b\\"));
+
+    // Draws the sixels back
+    let mut dark = vec![vec![None; 87]; 87];
+    let (mut x, mut band, mut register) = (0, 0, 0);
+    let mut chars = sixel[header.len()..sixel.len() - 2].chars().peekable();
+    while let Some(c) = chars.next() {
+        let mut count = 1;
+        let c = match c {
+            '#' => {
+                register = chars.next().unwrap().to_digit(10).unwrap();
+                continue;
+            }
+            '$' => {
+                x = 0;
+                continue;
+            }
+            '-' => {
+                x = 0;
+                band += 6;
+                continue;
+            }
+            '!' => {
+                let mut digits = String::new();
+                while chars.peek().unwrap().is_ascii_digit() {
+                    digits.push(chars.next().unwrap());
+                }
+                count = digits.parse().unwrap();
+                chars.next().unwrap()
+            }
+            c => c,
+        };
+        for _ in 0..count {
+            let bits = c as u32 - 0x3f;
+            for dy in (0..6).filter(|dy| bits >> dy & 1 == 1) {
+                dark[band + dy][x] = Some(register == 1);
+            }
+            x += 1;
+        }
+    }
+
+    for (y, row) in dark.iter().enumerate() {
+        for (x, &pixel) in row.iter().enumerate() {
+            assert_eq!(pixel, Some(bitmap.get(x, y) < 128), "({x}, {y})");
+        }
+    }
+}
+
+#[cfg(feature = "raster")]
+#[test]
+fn terminal_kitty_and_iterm2() {
+    use crate::convert::raster::{BitDepth, RasterBuilder};
+    use crate::convert::terminal::Protocol;
+    use base64::engine::general_purpose;
+    use base64::Engine;
+
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let png = RasterBuilder::default()
+        .margin(1)
+        .scale(4)
+        .to_png(&qrcode, BitDepth::One);
+    let decode = |data: &str| general_purpose::STANDARD.decode(data).unwrap();
+
+    let kitty = TerminalBuilder::default().to_protocol(&qrcode, Protocol::Kitty);
+    let chunks: Vec<&str> = kitty
+        .strip_suffix("\x1b\\")
+        .unwrap()
+        .split("\x1b\\")
+        .collect();
+    assert!(chunks[0].starts_with("\x1b_Ga=T,f=100,q=2,m="));
+    let mut data = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let (keys, payload) = chunk.split_once(';').unwrap();
+        let more = if i + 1 < chunks.len() { "m=1" } else { "m=0" };
+        assert!(keys.ends_with(more));
+        assert!(payload.len() <= 4096);
+        data.push_str(payload);
+    }
+    assert_eq!(decode(&data), png);
+
+    let iterm2 = TerminalBuilder::default()
+        .image_width(120)
+        .to_protocol(&qrcode, Protocol::ITerm2);
+    let (header, data) = iterm2
+        .strip_suffix('\x07')
+        .unwrap()
+        .split_once(':')
+        .unwrap();
+    let png = RasterBuilder::default()
+        .margin(1)
+        .scale(120 / (qrcode.size + 2))
+        .to_png(&qrcode, BitDepth::One);
+    assert_eq!(decode(data), png);
+    let side = (qrcode.size + 2) * (120 / (qrcode.size + 2));
+    assert_eq!(
+        header,
+        format!(
+            "\x1b]1337;File=inline=1;size={};width={side}px;height={side}px;preserveAspectRatio=1",
+            png.len()
+        )
+    );
+
+    // Text is the last resort
+    let text = TerminalBuilder::default()
+        .protocols(&[Protocol::Text, Protocol::Kitty])
+        .to_str(&qrcode);
+    assert_eq!(text, qrcode.to_str());
+    assert_eq!(
+        TerminalBuilder::default().protocols(&[]).to_str(&qrcode),
+        text
+    );
+}
@@ -0,0 +1,96 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::Widget;
+
+use crate::convert::terminal::{Charset, Polarity, TerminalBuilder};
+use crate::convert::tui::QrWidget;
+use crate::QRBuilder;
+
+/// Symbols of a line of the buffer
+fn line(buffer: &Buffer, y: u16) -> String {
+    let area = buffer.area;
+    (area.x..area.x + area.width)
+        .map(|x| buffer[(x, y)].symbol())
+        .collect()
+}
+
+#[test]
+fn tui_charsets_and_centering() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    // 25 modules and a margin of 1, the densest charset comes first
+    assert_eq!(
+        QrWidget::new(&qrcode).charset_for(Rect::new(0, 0, 80, 40)),
+        Some((Charset::Sextants, 14, 9))
+    );
+
+    // Larger blocks first, falling back to denser ones
+    let mut widget = QrWidget::new(&qrcode);
+    widget.charsets(&[
+        Charset::FullBlocks,
+        Charset::HalfBlocks,
+        Charset::Quadrants,
+        Charset::Sextants,
+    ]);
+    for (area, charset, width, height) in [
+        (Rect::new(0, 0, 80, 40), Charset::FullBlocks, 54, 27),
+        (Rect::new(5, 2, 40, 20), Charset::HalfBlocks, 27, 14),
+        (Rect::new(0, 0, 20, 14), Charset::Quadrants, 14, 14),
+        (Rect::new(3, 3, 14, 10), Charset::Sextants, 14, 9),
+    ] {
+        assert_eq!(
+            widget.charset_for(area),
+            Some((charset, width, height)),
+            "{area:?}"
+        );
+
+        let mut buffer = Buffer::empty(area);
+        (&widget).render(area, &mut buffer);
+        let expected = TerminalBuilder::default()
+            .charset(charset)
+            .polarity(Polarity::Normal)
+            .to_str(&qrcode);
+
+        let (x, y) = ((area.width - width) / 2, (area.height - height) / 2);
+        for (i, text) in expected.lines().enumerate() {
+            let line = line(&buffer, area.y + y + i as u16);
+            let start = line.char_indices().nth(usize::from(x)).unwrap().0;
+            assert!(line[start..].starts_with(text), "{charset:?}");
+            assert!(line[..start].trim().is_empty());
+        }
+        let cell = &buffer[(area.x + x, area.y + y)];
+        assert_eq!((cell.fg, cell.bg), (Color::Black, Color::White));
+        // Nothing drawn outside of the code
+        let corner = (area.right() - 1, area.bottom() - 1);
+        assert_eq!(buffer[corner].bg, Color::Reset);
+    }
+}
+
+#[test]
+fn tui_too_small() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let mut widget = QrWidget::new(&qrcode);
+    widget
+        .charsets(&[Charset::HalfBlocks])
+        .margin(4)
+        .style(Style::new().fg(Color::White).bg(Color::Black));
+
+    let area = Rect::new(0, 0, 32, 17);
+    assert_eq!(widget.charset_for(area), None);
+    let mut buffer = Buffer::empty(area);
+    widget.render(area, &mut buffer);
+    assert_eq!(
+        line(&buffer, 0).trim_end(),
+        "Area too small for the QR code"
+    );
+
+    // The margin is kept rather than dropped
+    let area = Rect::new(0, 0, 33, 17);
+    assert_eq!(
+        QrWidget::new(&qrcode)
+            .charsets(&[Charset::HalfBlocks])
+            .margin(4)
+            .charset_for(area),
+        Some((Charset::HalfBlocks, 33, 17))
+    );
+}
@@ -0,0 +1,94 @@
+use crate::convert::zpl::ZplBuilder;
+use crate::{QRBuilder, ECL};
+
+/// Expands the ZPL ASCII compression back to hexadecimal rows
+fn decompress(data: &str, width: usize) -> Vec<String> {
+    let mut rows: Vec<String> = Vec::new();
+    let (mut row, mut count) = (String::new(), 0);
+    for c in data.chars() {
+        match c {
+            'G'..='Y' => count += c as usize - 'G' as usize + 1,
+            'g'..='z' => count += (c as usize - 'g' as usize + 1) * 20,
+            ':' => rows.push(rows.last().unwrap().clone()),
+            ',' => {
+                row.push_str(&"0".repeat(width * 2 - row.len()));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => {
+                row.push_str(&c.to_string().repeat(count.max(1)));
+                count = 0;
+                if row.len() == width * 2 {
+                    rows.push(std::mem::take(&mut row));
+                }
+            }
+        }
+    }
+    assert!(row.is_empty());
+    rows
+}
+
+#[test]
+fn zpl_graphic_field() {
+    let qrcode = QRBuilder::new("https://example.com/").build().unwrap();
+    let builder = || {
+        let mut builder = ZplBuilder::default();
+        builder.margin(2).dots_per_module(3).origin(10, 20);
+        builder
+    };
+    // 29 + 4 modules of 3 dots, 13 bytes per row
+    let (side, width) = ((qrcode.size + 4) * 3, ((qrcode.size + 4) * 3 + 7) / 8);
+    let total = side * width;
+
+    let plain = builder().compression(false).to_str(&qrcode);
+    let header = format!("^XA^FO10,20^GFA,{total},{total},{width},");
+    assert!(plain.starts_with(&header));
+    assert!(plain.ends_with("^FS^XZ"));
+    let hex = &plain[header.len()..plain.len() - 6];
+    assert_eq!(hex.len(), total * 2);
+
+    // Dots are read back as modules
+    for y in 0..side {
+        for x in 0..side {
+            let digit = u8::from_str_radix(&hex[y * width * 2 + x / 4..][..1], 16).unwrap();
+            let dot = digit >> (3 - x % 4) & 1 == 1;
+            let (mx, my) = ((x / 3).wrapping_sub(2), (y / 3).wrapping_sub(2));
+            let dark = mx < qrcode.size && my < qrcode.size && qrcode[my][mx].value();
+            assert_eq!(dot, dark, "({x}, {y})");
+        }
+    }
+
+    let compressed = builder().to_str(&qrcode);
+    assert!(compressed.starts_with(&header));
+    let data = &compressed[header.len()..compressed.len() - 6];
+    assert!(data.len() * 3 < hex.len());
+    assert!(data.contains(':') && data.contains(','));
+    assert_eq!(decompress(data, width).concat(), hex);
+}
+
+#[test]
+fn zpl_native() {
+    let qrcode = QRBuilder::new("https://example.com/")
+        .ecl(ECL::M)
+        .build()
+        .unwrap();
+    assert_eq!(
+        ZplBuilder::default()
+            .dots_per_module(12)
+            .to_native(&qrcode, b"https://example.com/"),
+        "^XA^FO0,0^BQN,2,10^FH^FDMM,B0020https://example.com/^FS^XZ"
+    );
+
+    let qrcode = QRBuilder::new("0123456789").build().unwrap();
+    assert_eq!(
+        ZplBuilder::default().to_native(&qrcode, b"0123456789"),
+        "^XA^FO0,0^BQN,2,4^FH^FDQM,N0123456789^FS^XZ"
+    );
+
+    // Command prefixes and bytes outside ASCII are escaped
+    let payload = "a^b~c_dé".as_bytes();
+    let qrcode = QRBuilder::new(payload).ecl(ECL::H).build().unwrap();
+    assert_eq!(
+        ZplBuilder::default().to_native(&qrcode, payload),
+        "^XA^FO0,0^BQN,2,4^FH^FDHM,B0009a_5Eb_7Ec_5Fd_C3_A9^FS^XZ"
+    );
+}